@@ -0,0 +1,305 @@
+use clap::{App, ArgSettings};
+use serde_json::json;
+use std::fmt::Write;
+
+// clap 2 only generates completions for bash, zsh, fish, PowerShell and elvish, so these walk
+// the arguments of the app, which clap exposes for its own completion generators. Those fields
+// are `#[doc(hidden)]` and may change in any release, which is why clap is pinned to an exact
+// version in Cargo.toml: check these still hold before upgrading it.
+
+/// The shells whose completions are generated by starship rather than clap
+pub const EXTRA_SHELLS: [&str; 2] = ["nushell", "fig"];
+
+/// A flag or an option of a command
+struct Flag<'a> {
+    long: Option<&'a str>,
+    short: Option<char>,
+    help: Option<&'a str>,
+    value_name: Option<&'a str>,
+    possible_values: Option<&'a [&'a str]>,
+}
+
+/// A positional argument of a command
+struct Positional<'a> {
+    name: &'a str,
+    help: Option<&'a str>,
+    required: bool,
+    possible_values: Option<&'a [&'a str]>,
+}
+
+fn flags<'a>(app: &'a App) -> Vec<Flag<'a>> {
+    let flags = app
+        .p
+        .flags
+        .iter()
+        .filter(|flag| !flag.b.settings.is_set(ArgSettings::Hidden))
+        .map(|flag| Flag {
+            long: flag.s.long,
+            short: flag.s.short,
+            help: flag.b.help,
+            value_name: None,
+            possible_values: None,
+        });
+    let options = app
+        .p
+        .opts
+        .iter()
+        .filter(|option| !option.b.settings.is_set(ArgSettings::Hidden))
+        .map(|option| Flag {
+            long: option.s.long,
+            short: option.s.short,
+            help: option.b.help,
+            value_name: option
+                .v
+                .val_names
+                .as_ref()
+                .and_then(|names| names.values().next().copied())
+                .or(Some(option.b.name)),
+            possible_values: option.v.possible_vals.as_deref(),
+        });
+    flags.chain(options).collect()
+}
+
+fn positionals<'a>(app: &'a App) -> Vec<Positional<'a>> {
+    app.p
+        .positionals
+        .values()
+        .filter(|positional| !positional.b.settings.is_set(ArgSettings::Hidden))
+        .map(|positional| Positional {
+            name: positional.b.name,
+            help: positional.b.help,
+            required: positional.b.settings.is_set(ArgSettings::Required),
+            possible_values: positional.v.possible_vals.as_deref(),
+        })
+        .collect()
+}
+
+/// Generates the completions of an app for nushell, as `extern` definitions in a module
+pub fn nushell(app: &App) -> String {
+    fn quoted(text: &str) -> String {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+
+    fn add_command(out: &mut String, app: &App, path: &str) {
+        let mut signature = String::new();
+        let mut completers = String::new();
+        let completer = |out: &mut String, name: &str, values: &[&str]| -> String {
+            let completer = format!("nu-complete {} {}", path, name);
+            let values: Vec<String> = values.iter().map(|value| quoted(value)).collect();
+            writeln!(
+                out,
+                "  def {} [] {{ [ {} ] }}",
+                quoted(&completer),
+                values.join(" ")
+            )
+            .unwrap();
+            format!("@{}", quoted(&completer))
+        };
+
+        for positional in positionals(app) {
+            let values = positional
+                .possible_values
+                .map(|values| completer(&mut completers, positional.name, values))
+                .unwrap_or_default();
+            let optional = if positional.required { "" } else { "?" };
+            write!(
+                signature,
+                "    {}{}: string{}",
+                positional.name, optional, values
+            )
+            .unwrap();
+            writeln_help(&mut signature, positional.help);
+        }
+        for flag in flags(app) {
+            let long = match (flag.long, flag.short) {
+                (Some(long), Some(short)) => format!("--{}(-{})", long, short),
+                (Some(long), None) => format!("--{}", long),
+                (None, Some(short)) => format!("-{}", short),
+                (None, None) => continue,
+            };
+            let value = match flag.value_name {
+                Some(name) => {
+                    let values = flag
+                        .possible_values
+                        .map(|values| completer(&mut completers, flag.long.unwrap_or(name), values))
+                        .unwrap_or_default();
+                    format!(": string{}", values)
+                }
+                None => String::new(),
+            };
+            write!(signature, "    {}{}", long, value).unwrap();
+            writeln_help(&mut signature, flag.help);
+        }
+
+        out.push_str(&completers);
+        if let Some(about) = app.p.meta.about {
+            writeln!(out, "\n  # {}", about.lines().next().unwrap_or_default()).unwrap();
+        } else {
+            out.push('\n');
+        }
+        writeln!(out, "  export extern {} [", quoted(path)).unwrap();
+        out.push_str(&signature);
+        out.push_str("  ]\n");
+
+        for subcommand in &app.p.subcommands {
+            add_command(
+                out,
+                subcommand,
+                &format!("{} {}", path, subcommand.p.meta.name),
+            );
+        }
+    }
+
+    fn writeln_help(out: &mut String, help: Option<&str>) {
+        match help.and_then(|help| help.lines().next()) {
+            Some(help) => writeln!(out, "  # {}", help).unwrap(),
+            None => out.push('\n'),
+        }
+    }
+
+    let name = app.p.meta.bin_name.as_deref().unwrap_or(&app.p.meta.name);
+    let mut out = String::from("module completions {\n");
+    add_command(&mut out, app, name);
+    out.push_str("}\n\nuse completions *\n");
+    out
+}
+
+/// Generates the completion spec of an app for Fig, which carapace can also read
+pub fn fig(app: &App) -> String {
+    fn command(app: &App, name: &str) -> serde_json::Value {
+        let options: Vec<serde_json::Value> = flags(app)
+            .iter()
+            .map(|flag| {
+                let names: Vec<String> = flag
+                    .long
+                    .map(|long| format!("--{}", long))
+                    .into_iter()
+                    .chain(flag.short.map(|short| format!("-{}", short)))
+                    .collect();
+                let mut option = json!({ "name": names });
+                if let Some(help) = flag.help {
+                    option["description"] = json!(help);
+                }
+                if let Some(name) = flag.value_name {
+                    option["args"] = json!({ "name": name });
+                    if let Some(values) = flag.possible_values {
+                        option["args"]["suggestions"] = json!(values);
+                    }
+                }
+                option
+            })
+            .collect();
+        let args: Vec<serde_json::Value> = positionals(app)
+            .iter()
+            .map(|positional| {
+                let mut arg = json!({ "name": positional.name });
+                if let Some(help) = positional.help {
+                    arg["description"] = json!(help);
+                }
+                if !positional.required {
+                    arg["isOptional"] = json!(true);
+                }
+                if let Some(values) = positional.possible_values {
+                    arg["suggestions"] = json!(values);
+                }
+                arg
+            })
+            .collect();
+        let subcommands: Vec<serde_json::Value> = app
+            .p
+            .subcommands
+            .iter()
+            .map(|subcommand| command(subcommand, &subcommand.p.meta.name))
+            .collect();
+
+        let mut spec = json!({ "name": name });
+        if let Some(about) = app.p.meta.about {
+            spec["description"] = json!(about);
+        }
+        for (key, values) in &[
+            ("subcommands", subcommands),
+            ("options", options),
+            ("args", args),
+        ] {
+            if !values.is_empty() {
+                spec[*key] = json!(values);
+            }
+        }
+        spec
+    }
+
+    let name = app.p.meta.bin_name.as_deref().unwrap_or(&app.p.meta.name);
+    let spec = serde_json::to_string_pretty(&command(app, name)).unwrap();
+    format!(
+        "const completionSpec: Fig.Spec = {};\n\nexport default completionSpec;\n",
+        spec
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Arg, SubCommand};
+
+    fn app() -> App<'static, 'static> {
+        App::new("starship").subcommand(
+            SubCommand::with_name("init")
+                .about("Prints the shell function used to execute starship")
+                .arg(
+                    Arg::with_name("shell")
+                        .help("The name of the shell")
+                        .possible_values(&["bash", "zsh"])
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("print_full_init")
+                        .long("print-full-init")
+                        .help("Print the main initialization script"),
+                )
+                .arg(
+                    Arg::with_name("status_code")
+                        .short("s")
+                        .long("status")
+                        .value_name("STATUS_CODE")
+                        .takes_value(true),
+                ),
+        )
+    }
+
+    #[test]
+    fn nushell_completions() {
+        let completions = nushell(&app());
+        assert!(completions
+            .contains("  def \"nu-complete starship init shell\" [] { [ \"bash\" \"zsh\" ] }\n"));
+        assert!(completions.contains(
+            "  # Prints the shell function used to execute starship\n  export extern \"starship init\" [\n    shell: string@\"nu-complete starship init shell\"  # The name of the shell\n    --print-full-init  # Print the main initialization script\n    --status(-s): string\n  ]\n"
+        ));
+        assert!(completions.ends_with("use completions *\n"));
+    }
+
+    #[test]
+    fn fig_completions() {
+        let completions = fig(&app());
+        let spec = completions
+            .strip_prefix("const completionSpec: Fig.Spec = ")
+            .and_then(|spec| spec.strip_suffix(";\n\nexport default completionSpec;\n"))
+            .unwrap();
+        let spec: serde_json::Value = serde_json::from_str(spec).unwrap();
+
+        let init = &spec["subcommands"][0];
+        assert_eq!(spec["name"], "starship");
+        assert_eq!(init["name"], "init");
+        assert_eq!(
+            init["args"],
+            json!([{
+                "name": "shell",
+                "description": "The name of the shell",
+                "suggestions": ["bash", "zsh"],
+            }])
+        );
+        assert_eq!(
+            init["options"][1],
+            json!({ "name": ["--status", "-s"], "args": { "name": "STATUS_CODE" } })
+        );
+    }
+}
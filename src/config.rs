@@ -1,13 +1,23 @@
 use crate::configs::StarshipRootConfig;
+use crate::palette;
+use crate::symbol_set;
 use crate::utils;
 use ansi_term::{Color, Style};
 use indexmap::IndexMap;
 
 use std::clone::Clone;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::io::ErrorKind;
 use std::marker::Sized;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
 
+use clap::crate_version;
+use serde::{Deserialize, Serialize};
 use std::env;
 use toml::Value;
 
@@ -205,14 +215,11 @@ pub struct StarshipConfig {
 impl StarshipConfig {
     /// Initialize the Config struct
     pub fn initialize() -> Self {
-        if let Some(file_data) = Self::config_from_file() {
-            StarshipConfig {
-                config: Some(file_data),
-            }
-        } else {
-            StarshipConfig {
-                config: Some(Value::Table(toml::value::Table::new())),
-            }
+        let mut config =
+            Self::config_from_file().unwrap_or_else(|| Value::Table(toml::value::Table::new()));
+        symbol_set::apply(&mut config);
+        StarshipConfig {
+            config: Some(config),
         }
     }
 
@@ -221,17 +228,55 @@ impl StarshipConfig {
         let file_path = if let Ok(path) = env::var("STARSHIP_CONFIG") {
             // Use $STARSHIP_CONFIG as the config path if available
             log::debug!("STARSHIP_CONFIG is set: {}", &path);
-            path
+            PathBuf::from(path)
         } else {
             // Default to using ~/.config/starship.toml
             log::debug!("STARSHIP_CONFIG is not set");
             let config_path = dirs_next::home_dir()?.join(".config/starship.toml");
-            let config_path_str = config_path.to_str()?.to_owned();
-            log::debug!("Using default config path: {}", config_path_str);
-            config_path_str
+            log::debug!("Using default config path: {:?}", config_path);
+            config_path
         };
 
-        let toml_content = match utils::read_file(&file_path) {
+        let cache_dir = env::var_os("STARSHIP_CACHE")
+            .map(PathBuf::from)
+            .or_else(|| dirs_next::home_dir().map(|home| home.join(".cache/starship")))
+            .map(|cache_dir| cache_dir.join("config"));
+        Self::load_config(&file_path, cache_dir.as_deref())
+    }
+
+    /// Load a config file, reusing the parsed config cached for an unchanged file
+    ///
+    /// Parsing a large config on every prompt is noticeable, while loading the parsed config
+    /// from JSON is much faster. The cache is keyed by the path of the config file, and is
+    /// replaced whenever its modification time, its size or the version of starship changes.
+    /// Any failure to use the cache only means that the config file is parsed.
+    fn load_config(file_path: &Path, cache_dir: Option<&Path>) -> Option<Value> {
+        let cache_key = fs::metadata(file_path).ok().and_then(|metadata| {
+            let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+            Some(format!(
+                "{} {} {}",
+                crate_version!(),
+                modified.as_nanos(),
+                metadata.len()
+            ))
+        });
+        let cache_file = cache_dir.map(|cache_dir| {
+            let mut hasher = DefaultHasher::new();
+            file_path.hash(&mut hasher);
+            cache_dir.join(format!("{:016x}.json", hasher.finish()))
+        });
+
+        if let (Some(cache_file), Some(cache_key)) = (&cache_file, &cache_key) {
+            match read_cached_config(cache_file) {
+                Some(cached) if &cached.key == cache_key => {
+                    log::trace!("Using the cached config for {:?}", file_path);
+                    return Some(cached.config);
+                }
+                _ => log::trace!("No cached config for {:?}", file_path),
+            }
+        }
+
+        let toml_content = match utils::read_file(file_path) {
             Ok(content) => {
                 log::trace!("Config file content: \"\n{}\"", &content);
                 Some(content)
@@ -248,16 +293,31 @@ impl StarshipConfig {
             }
         }?;
 
-        match toml::from_str(&toml_content) {
+        let config: Value = match toml::from_str(&toml_content) {
             Ok(parsed) => {
                 log::debug!("Config parsed: {:?}", &parsed);
-                Some(parsed)
+                parsed
             }
             Err(error) => {
                 log::error!("Unable to parse the config file: {}", error);
-                None
+                return None;
+            }
+        };
+
+        if let (Some(cache_dir), Some(cache_file), Some(cache_key)) =
+            (cache_dir, cache_file, cache_key)
+        {
+            let cached = CachedConfig {
+                path: file_path.to_path_buf(),
+                key: cache_key,
+                config,
+            };
+            if let Err(error) = write_cached_config(cache_dir, &cache_file, &cached) {
+                log::debug!("Unable to cache the parsed config: {}", error);
             }
+            return Some(cached.config);
         }
+        Some(config)
     }
 
     /// Get the subset of the table for a module by its name
@@ -337,6 +397,11 @@ impl StarshipConfig {
         module_config
     }
 
+    /// Get the subset of the table for a plugin module by its name
+    pub fn get_plugin_config(&self, plugin_name: &str) -> Option<&Value> {
+        self.get_config(&["plugin", plugin_name])
+    }
+
     /// Get the table of all the registered custom modules, if any
     pub fn get_custom_modules(&self) -> Option<&toml::value::Table> {
         self.get_config(&["custom"])?.as_table()
@@ -467,10 +532,55 @@ fn parse_color_string(color_string: &str) -> Option<ansi_term::Color> {
 
     if predefined_color.is_some() {
         log::trace!("Read predefined color: {}", color_string);
-    } else {
-        log::debug!("Could not parse color in string: {}", color_string);
+        return predefined_color;
+    }
+
+    // A slot of the palette, e.g. `accent`, whose color can't be another slot
+    if let Some(color) = palette::lookup(&color_string.to_lowercase()) {
+        log::trace!("Read palette color: {} = {}", color_string, color);
+        let _palette = palette::use_palette(Arc::default());
+        return parse_color_string(&color);
+    }
+
+    log::debug!("Could not parse color in string: {}", color_string);
+    None
+}
+
+/// A parsed config file, as cached in the cache directory
+#[derive(Serialize, Deserialize)]
+struct CachedConfig {
+    path: PathBuf,
+    key: String,
+    config: Value,
+}
+
+fn read_cached_config(cache_file: &Path) -> Option<CachedConfig> {
+    let cached = fs::read(cache_file).ok()?;
+    serde_json::from_slice(&cached).ok()
+}
+
+/// Write a parsed config to the cache, and remove the cached configs of config files which
+/// no longer exist
+fn write_cached_config(
+    cache_dir: &Path,
+    cache_file: &Path,
+    cached: &CachedConfig,
+) -> std::io::Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    for entry in fs::read_dir(cache_dir)? {
+        let path = entry?.path();
+        let is_stale = match read_cached_config(&path) {
+            Some(other) => !other.path.exists(),
+            None => true,
+        };
+        if path != cache_file && is_stale {
+            log::trace!("Removing the stale cached config {:?}", path);
+            fs::remove_file(&path)?;
+        }
     }
-    predefined_color
+
+    let json = serde_json::to_vec(cached)?;
+    fs::write(cache_file, json)
 }
 
 #[cfg(test)]
@@ -478,6 +588,79 @@ mod tests {
     use super::*;
     use starship_module_config_derive::ModuleConfig;
 
+    #[test]
+    fn test_parsed_config_is_cached() -> std::io::Result<()> {
+        let config_dir = tempfile::tempdir()?;
+        let cache_dir = tempfile::tempdir()?;
+        let cache = Some(cache_dir.path());
+        let config_file = config_dir.path().join("starship.toml");
+        let content = "format = \"$all\"\n[time]\ntime_range = 1979-05-27T07:32:00Z\n";
+        fs::write(&config_file, content)?;
+
+        let parsed = StarshipConfig::load_config(&config_file, cache);
+        assert_eq!(parsed, toml::from_str(content).ok());
+        // The cached config round-trips, datetimes included
+        assert_eq!(StarshipConfig::load_config(&config_file, cache), parsed);
+
+        // The cache is used while the file is unchanged
+        let cache_file = fs::read_dir(cache_dir.path())?.next().unwrap()?.path();
+        let cached = fs::read_to_string(&cache_file)?.replace("$all", "$cached");
+        fs::write(&cache_file, cached)?;
+        let cached = StarshipConfig::load_config(&config_file, cache);
+        assert_eq!(
+            cached.as_ref().and_then(|config| config.get("format")),
+            Some(&Value::from("$cached"))
+        );
+
+        // and replaced once it changes
+        let changed = content.replace("$all", "$directory");
+        fs::write(&config_file, &changed)?;
+        let parsed = StarshipConfig::load_config(&config_file, cache);
+        assert_eq!(parsed, toml::from_str(&changed).ok());
+        assert_eq!(fs::read_dir(cache_dir.path())?.count(), 1);
+
+        config_dir.close()?;
+        cache_dir.close()
+    }
+
+    #[test]
+    fn test_stale_cached_configs_are_removed() -> std::io::Result<()> {
+        let config_dir = tempfile::tempdir()?;
+        let cache_dir = tempfile::tempdir()?;
+        let cache = Some(cache_dir.path());
+        let removed_file = config_dir.path().join("removed.toml");
+        fs::write(&removed_file, "add_newline = false\n")?;
+        StarshipConfig::load_config(&removed_file, cache);
+        fs::remove_file(&removed_file)?;
+
+        let config_file = config_dir.path().join("starship.toml");
+        fs::write(&config_file, "add_newline = true\n")?;
+        StarshipConfig::load_config(&config_file, cache);
+
+        let cached: Vec<CachedConfig> = fs::read_dir(cache_dir.path())?
+            .filter_map(|entry| read_cached_config(&entry.ok()?.path()))
+            .collect();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].path, config_file);
+
+        config_dir.close()?;
+        cache_dir.close()
+    }
+
+    #[test]
+    fn test_config_loads_without_cache() -> std::io::Result<()> {
+        let config_dir = tempfile::tempdir()?;
+        let config_file = config_dir.path().join("starship.toml");
+        fs::write(&config_file, "add_newline = false\n")?;
+        // The cache directory can't be created below a file
+        let cache_dir = config_file.join("cache");
+
+        let parsed = StarshipConfig::load_config(&config_file, Some(&cache_dir));
+        assert_eq!(parsed, toml::from_str("add_newline = false\n").ok());
+
+        config_dir.close()
+    }
+
     #[test]
     fn test_load_config() {
         #[derive(Clone, ModuleConfig)]
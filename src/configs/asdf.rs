@@ -0,0 +1,28 @@
+use crate::config::{ModuleConfig, RootModuleConfig};
+
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig)]
+pub struct AsdfConfig<'a> {
+    pub format: &'a str,
+    pub tool_format: &'a str,
+    pub separator: &'a str,
+    pub symbol: &'a str,
+    pub style: &'a str,
+    pub tools: Vec<&'a str>,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for AsdfConfig<'a> {
+    fn new() -> Self {
+        AsdfConfig {
+            format: "with [$symbol$versions]($style) ",
+            tool_format: "$tool $version",
+            separator: ", ",
+            symbol: "🧰 ",
+            style: "bold blue",
+            tools: vec![],
+            disabled: true,
+        }
+    }
+}
@@ -8,17 +8,51 @@ pub struct AwsConfig<'a> {
     pub symbol: &'a str,
     pub style: &'a str,
     pub disabled: bool,
-    pub region_aliases: HashMap<String, &'a str>,
+    pub region_aliases: HashMap<String, AwsAliasConfig<'a>>,
+    pub profile_aliases: HashMap<String, AwsAliasConfig<'a>>,
+    pub expiration_symbol: &'a str,
+    pub expiration_threshold: i64,
+    pub expiration_style: &'a str,
 }
 
 impl<'a> RootModuleConfig<'a> for AwsConfig<'a> {
     fn new() -> Self {
         AwsConfig {
-            format: "on [$symbol$profile(\\($region\\))]($style) ",
+            format: "on [$symbol$profile(\\($region\\))(\\[$expiration\\])]($style) ",
             symbol: "☁️  ",
             style: "bold yellow",
             disabled: false,
             region_aliases: HashMap::new(),
+            profile_aliases: HashMap::new(),
+            expiration_symbol: "X",
+            expiration_threshold: 900,
+            expiration_style: "bold red",
         }
     }
 }
+
+/// The name and style a profile or region is displayed with, either given as
+/// `{ name = "...", style = "..." }` or as just the name
+#[derive(Clone, Default)]
+pub struct AwsAliasConfig<'a> {
+    pub name: Option<&'a str>,
+    pub style: Option<&'a str>,
+}
+
+impl<'a> ModuleConfig<'a> for AwsAliasConfig<'a> {
+    fn from_config(config: &'a toml::Value) -> Option<Self> {
+        if let Some(name) = config.as_str() {
+            return Some(AwsAliasConfig {
+                name: Some(name),
+                style: None,
+            });
+        }
+
+        let config = config.as_table()?;
+
+        Some(AwsAliasConfig {
+            name: config.get("name").and_then(toml::Value::as_str),
+            style: config.get("style").and_then(toml::Value::as_str),
+        })
+    }
+}
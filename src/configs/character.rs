@@ -1,4 +1,5 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
+use indexmap::IndexMap;
 
 use starship_module_config_derive::ModuleConfig;
 
@@ -7,7 +8,11 @@ pub struct CharacterConfig<'a> {
     pub format: &'a str,
     pub success_symbol: &'a str,
     pub error_symbol: &'a str,
+    pub exit_code_symbols: IndexMap<String, &'a str>,
     pub vicmd_symbol: &'a str,
+    pub vimcmd_visual_symbol: &'a str,
+    pub vimcmd_replace_symbol: &'a str,
+    pub vimcmd_replace_one_symbol: &'a str,
     pub disabled: bool,
 }
 
@@ -17,7 +22,11 @@ impl<'a> RootModuleConfig<'a> for CharacterConfig<'a> {
             format: "$symbol ",
             success_symbol: "[❯](bold green)",
             error_symbol: "[❯](bold red)",
+            exit_code_symbols: IndexMap::new(),
             vicmd_symbol: "[❮](bold green)",
+            vimcmd_visual_symbol: "[❮](bold yellow)",
+            vimcmd_replace_symbol: "[❮](bold purple)",
+            vimcmd_replace_one_symbol: "[❮](bold purple)",
             disabled: false,
         }
     }
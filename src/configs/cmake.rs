@@ -5,8 +5,12 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct CMakeConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -14,8 +18,12 @@ impl<'a> RootModuleConfig<'a> for CMakeConfig<'a> {
     fn new() -> Self {
         CMakeConfig {
             format: "via [$symbol($version )]($style)",
+            version_format: None,
             symbol: "喝 ",
             style: "bold blue",
+            detect_extensions: vec![],
+            detect_files: vec!["CMakeLists.txt", "CMakeCache.txt"],
+            detect_folders: vec![],
             disabled: false,
         }
     }
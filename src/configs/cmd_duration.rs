@@ -8,9 +8,14 @@ pub struct CmdDurationConfig<'a> {
     pub format: &'a str,
     pub style: &'a str,
     pub show_milliseconds: bool,
+    pub show_milliseconds_below: i64,
+    pub duration_format: &'a str,
+    pub precision: i64,
     pub disabled: bool,
+    pub excluded_commands: Vec<&'a str>,
     pub show_notifications: bool,
     pub min_time_to_notify: i64,
+    pub notification_timeout: i64,
 }
 
 impl<'a> RootModuleConfig<'a> for CmdDurationConfig<'a> {
@@ -19,10 +24,15 @@ impl<'a> RootModuleConfig<'a> for CmdDurationConfig<'a> {
             min_time: 2_000,
             format: "took [$duration]($style) ",
             show_milliseconds: false,
+            show_milliseconds_below: 1_000,
+            duration_format: "compact",
+            precision: 1,
             style: "yellow bold",
             disabled: false,
+            excluded_commands: vec![],
             show_notifications: false,
             min_time_to_notify: 45_000,
+            notification_timeout: 750,
         }
     }
 }
@@ -9,6 +9,7 @@ pub struct CondaConfig<'a> {
     pub symbol: &'a str,
     pub style: &'a str,
     pub ignore_base: bool,
+    pub stack_separator: &'a str,
     pub disabled: bool,
 }
 
@@ -20,6 +21,7 @@ impl<'a> RootModuleConfig<'a> for CondaConfig<'a> {
             symbol: "🅒 ",
             style: "green bold",
             ignore_base: true,
+            stack_separator: " > ",
             disabled: false,
         }
     }
@@ -11,15 +11,45 @@ pub struct Extensions<'a>(pub Vec<&'a str>);
 #[derive(Clone, Default, PartialEq)]
 pub struct Directories<'a>(pub Vec<&'a str>);
 
+/// A command that can be overridden per operating system, with `command.linux`,
+/// `command.macos` and `command.windows`
+#[derive(Clone, Default, PartialEq)]
+pub struct PlatformCommand<'a> {
+    pub default: &'a str,
+    pub linux: Option<&'a str>,
+    pub macos: Option<&'a str>,
+    pub windows: Option<&'a str>,
+}
+
+impl<'a> PlatformCommand<'a> {
+    /// The command to run on the current operating system
+    pub fn get(&self) -> &'a str {
+        let platform_command = match std::env::consts::OS {
+            "linux" => self.linux,
+            "macos" => self.macos,
+            "windows" => self.windows,
+            _ => None,
+        };
+        platform_command.unwrap_or(self.default)
+    }
+}
+
 #[derive(Clone, ModuleConfig)]
 pub struct CustomConfig<'a> {
     pub format: &'a str,
     pub symbol: &'a str,
-    pub command: &'a str,
+    pub command: PlatformCommand<'a>,
+    pub regex: Option<&'a str>,
     pub when: Option<&'a str>,
+    pub condition: Option<&'a str>,
     pub shell: VecOr<&'a str>,
+    pub cache_duration: u64,
+    pub cache_scope: &'a str,
+    pub async_refresh: bool,
+    pub placeholder: &'a str,
     pub description: &'a str,
     pub style: &'a str,
+    pub show_on_failure: bool,
     pub disabled: bool,
     pub files: Files<'a>,
     pub extensions: Extensions<'a>,
@@ -31,11 +61,18 @@ impl<'a> RootModuleConfig<'a> for CustomConfig<'a> {
         CustomConfig {
             format: "[$symbol$output]($style) ",
             symbol: "",
-            command: "",
+            command: PlatformCommand::default(),
+            regex: None,
             when: None,
+            condition: None,
             shell: VecOr::default(),
+            cache_duration: 0,
+            cache_scope: "session",
+            async_refresh: false,
+            placeholder: "",
             description: "<custom config>",
             style: "green bold",
+            show_on_failure: false,
             disabled: false,
             files: Files::default(),
             extensions: Extensions::default(),
@@ -91,3 +128,28 @@ impl<'a> ModuleConfig<'a> for Directories<'a> {
         Some(Directories(directories))
     }
 }
+
+impl<'a> ModuleConfig<'a> for PlatformCommand<'a> {
+    fn from_config(config: &'a toml::Value) -> Option<Self> {
+        if let Some(command) = config.as_str() {
+            return Some(PlatformCommand {
+                default: command,
+                ..PlatformCommand::default()
+            });
+        }
+
+        let mut platform_command = PlatformCommand::default();
+        for (platform, command) in config.as_table()? {
+            let command = command.as_str();
+            match platform.as_str() {
+                "default" => platform_command.default = command?,
+                "linux" => platform_command.linux = command,
+                "macos" => platform_command.macos = command,
+                "windows" => platform_command.windows = command,
+                _ => log::warn!("Unexpected platform {:?} in command", platform),
+            }
+        }
+
+        Some(platform_command)
+    }
+}
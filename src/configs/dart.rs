@@ -5,8 +5,12 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct DartConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -14,8 +18,12 @@ impl<'a> RootModuleConfig<'a> for DartConfig<'a> {
     fn new() -> Self {
         DartConfig {
             format: "via [$symbol$version]($style) ",
+            version_format: None,
             symbol: "🎯 ",
             style: "bold blue",
+            detect_extensions: vec!["dart"],
+            detect_files: vec!["pubspec.yaml", "pubspec.yml", "pubspec.lock"],
+            detect_folders: vec![".dart_tool"],
             disabled: false,
         }
     }
@@ -6,12 +6,17 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct DirectoryConfig<'a> {
     pub truncation_length: i64,
+    pub truncation_width_percentage: i64,
     pub truncate_to_repo: bool,
     pub substitutions: IndexMap<String, &'a str>,
+    pub icons: IndexMap<String, &'a str>,
     pub fish_style_pwd_dir_length: i64,
     pub use_logical_path: bool,
     pub format: &'a str,
+    pub repo_root_format: &'a str,
     pub style: &'a str,
+    pub before_repo_root_style: Option<&'a str>,
+    pub repo_root_style: Option<&'a str>,
     pub disabled: bool,
     pub read_only: &'a str,
     pub read_only_style: &'a str,
@@ -22,12 +27,17 @@ impl<'a> RootModuleConfig<'a> for DirectoryConfig<'a> {
     fn new() -> Self {
         DirectoryConfig {
             truncation_length: 3,
+            truncation_width_percentage: 0,
             truncate_to_repo: true,
             fish_style_pwd_dir_length: 0,
             substitutions: IndexMap::new(),
+            icons: IndexMap::new(),
             use_logical_path: true,
             format: "[$path]($style)[$read_only]($read_only_style) ",
+            repo_root_format: "[$before_root_path]($before_repo_root_style)[$repo_root]($repo_root_style)[$path]($style)[$read_only]($read_only_style) ",
             style: "cyan bold",
+            before_repo_root_style: None,
+            repo_root_style: None,
             disabled: false,
             read_only: "🔒",
             read_only_style: "red",
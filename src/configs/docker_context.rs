@@ -6,8 +6,13 @@ use starship_module_config_derive::ModuleConfig;
 pub struct DockerContextConfig<'a> {
     pub symbol: &'a str,
     pub style: &'a str,
+    pub remote_symbol: &'a str,
+    pub remote_style: &'a str,
     pub format: &'a str,
     pub only_with_files: bool,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -16,8 +21,19 @@ impl<'a> RootModuleConfig<'a> for DockerContextConfig<'a> {
         DockerContextConfig {
             symbol: "🐳 ",
             style: "blue bold",
-            format: "via [$symbol$context]($style) ",
+            remote_symbol: "🐳 ",
+            remote_style: "red bold",
+            format: "via [$symbol$context( @ $host)]($style) ",
             only_with_files: true,
+            detect_extensions: vec![],
+            detect_files: vec![
+                "docker-compose.yml",
+                "docker-compose.yaml",
+                "compose.yml",
+                "compose.yaml",
+                "Dockerfile",
+            ],
+            detect_folders: vec![],
             disabled: false,
         }
     }
@@ -5,9 +5,14 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct DotnetConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub not_capable_style: &'a str,
     pub heuristic: bool,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -15,9 +20,20 @@ impl<'a> RootModuleConfig<'a> for DotnetConfig<'a> {
     fn new() -> Self {
         DotnetConfig {
             format: "[$symbol$version( 🎯 $tfm)]($style) ",
+            version_format: None,
             symbol: "•NET ",
             style: "blue bold",
+            not_capable_style: "bold red",
             heuristic: true,
+            detect_extensions: vec!["sln", "csproj", "fsproj", "xproj"],
+            detect_files: vec![
+                "global.json",
+                "project.json",
+                "Directory.Build.props",
+                "Directory.Build.targets",
+                "Packages.props",
+            ],
+            detect_folders: vec![],
             disabled: false,
         }
     }
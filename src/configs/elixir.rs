@@ -5,8 +5,13 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct ElixirConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub not_capable_style: &'a str,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -14,8 +19,13 @@ impl<'a> RootModuleConfig<'a> for ElixirConfig<'a> {
     fn new() -> Self {
         ElixirConfig {
             format: "via [$symbol$version \\(OTP $otp_version\\)]($style) ",
+            version_format: None,
             symbol: "💧 ",
             style: "bold purple",
+            not_capable_style: "bold red",
+            detect_extensions: vec![],
+            detect_files: vec!["mix.exs"],
+            detect_folders: vec![],
             disabled: false,
         }
     }
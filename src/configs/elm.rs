@@ -5,8 +5,12 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct ElmConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -14,8 +18,12 @@ impl<'a> RootModuleConfig<'a> for ElmConfig<'a> {
     fn new() -> Self {
         ElmConfig {
             format: "via [$symbol$version]($style) ",
+            version_format: None,
             symbol: "🌳 ",
             style: "cyan bold",
+            detect_extensions: vec!["elm"],
+            detect_files: vec!["elm.json", "elm-package.json", ".elm-version"],
+            detect_folders: vec!["elm-stuff"],
             disabled: false,
         }
     }
@@ -8,6 +8,7 @@ pub struct EnvVarConfig<'a> {
     pub style: &'a str,
     pub variable: Option<&'a str>,
     pub default: Option<&'a str>,
+    pub regex: Option<&'a str>,
     pub format: &'a str,
     pub disabled: bool,
 }
@@ -19,6 +20,7 @@ impl<'a> RootModuleConfig<'a> for EnvVarConfig<'a> {
             style: "black bold dimmed",
             variable: None,
             default: None,
+            regex: None,
             format: "with [$env_value]($style) ",
             disabled: false,
         }
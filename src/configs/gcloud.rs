@@ -9,6 +9,7 @@ pub struct GcloudConfig<'a> {
     pub style: &'a str,
     pub disabled: bool,
     pub region_aliases: HashMap<String, &'a str>,
+    pub project_aliases: HashMap<String, &'a str>,
 }
 
 impl<'a> RootModuleConfig<'a> for GcloudConfig<'a> {
@@ -19,6 +20,7 @@ impl<'a> RootModuleConfig<'a> for GcloudConfig<'a> {
             style: "bold blue",
             disabled: false,
             region_aliases: HashMap::new(),
+            project_aliases: HashMap::new(),
         }
     }
 }
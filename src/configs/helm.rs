@@ -5,8 +5,12 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct HelmConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -14,8 +18,12 @@ impl<'a> RootModuleConfig<'a> for HelmConfig<'a> {
     fn new() -> Self {
         HelmConfig {
             format: "via [$symbol$version]($style) ",
+            version_format: None,
             symbol: "⎈ ",
             style: "bold white",
+            detect_extensions: vec![],
+            detect_files: vec!["helmfile.yaml", "Chart.yaml"],
+            detect_folders: vec![],
             disabled: false,
         }
     }
@@ -1,4 +1,5 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
+use indexmap::IndexMap;
 
 use starship_module_config_derive::ModuleConfig;
 
@@ -6,8 +7,11 @@ use starship_module_config_derive::ModuleConfig;
 pub struct HostnameConfig<'a> {
     pub ssh_only: bool,
     pub trim_at: &'a str,
+    pub trim_regex: &'a str,
+    pub aliases: IndexMap<String, HostnameAliasConfig<'a>>,
     pub format: &'a str,
     pub style: &'a str,
+    pub remote_detection: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -16,9 +20,38 @@ impl<'a> RootModuleConfig<'a> for HostnameConfig<'a> {
         HostnameConfig {
             ssh_only: true,
             trim_at: ".",
+            trim_regex: "",
+            aliases: IndexMap::new(),
             format: "[$hostname]($style) in ",
             style: "green dimmed bold",
+            remote_detection: vec!["env"],
             disabled: false,
         }
     }
 }
+
+/// The name and style a host is displayed with, either given as
+/// `{ name = "...", style = "..." }` or as just the name
+#[derive(Clone, Default)]
+pub struct HostnameAliasConfig<'a> {
+    pub name: Option<&'a str>,
+    pub style: Option<&'a str>,
+}
+
+impl<'a> ModuleConfig<'a> for HostnameAliasConfig<'a> {
+    fn from_config(config: &'a toml::Value) -> Option<Self> {
+        if let Some(name) = config.as_str() {
+            return Some(HostnameAliasConfig {
+                name: Some(name),
+                style: None,
+            });
+        }
+
+        let config = config.as_table()?;
+
+        Some(HostnameAliasConfig {
+            name: config.get("name").and_then(toml::Value::as_str),
+            style: config.get("style").and_then(toml::Value::as_str),
+        })
+    }
+}
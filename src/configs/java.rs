@@ -1,22 +1,43 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
 use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig)]
 pub struct JavaConfig<'a> {
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub style: &'a str,
+    pub not_capable_style: &'a str,
     pub symbol: &'a str,
+    pub vendor_symbols: HashMap<String, &'a str>,
 }
 
 impl<'a> RootModuleConfig<'a> for JavaConfig<'a> {
     fn new() -> Self {
         JavaConfig {
             format: "via [$symbol$version]($style) ",
+            version_format: None,
+            detect_extensions: vec!["java", "class", "jar", "gradle", "clj", "cljc"],
+            detect_files: vec![
+                "pom.xml",
+                "build.gradle.kts",
+                "build.sbt",
+                ".java-version",
+                "deps.edn",
+                "project.clj",
+                "build.boot",
+            ],
+            detect_folders: vec![],
             disabled: false,
             style: "red dimmed",
+            not_capable_style: "bold red",
             symbol: "☕ ",
+            vendor_symbols: HashMap::new(),
         }
     }
 }
@@ -5,8 +5,11 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct JobsConfig<'a> {
     pub threshold: i64,
+    pub symbol_threshold: i64,
+    pub number_threshold: i64,
     pub format: &'a str,
     pub symbol: &'a str,
+    pub stopped_symbol: Option<&'a str>,
     pub style: &'a str,
     pub disabled: bool,
 }
@@ -15,8 +18,11 @@ impl<'a> RootModuleConfig<'a> for JobsConfig<'a> {
     fn new() -> Self {
         JobsConfig {
             threshold: 1,
+            symbol_threshold: 1,
+            number_threshold: 2,
             format: "[$symbol$number]($style) ",
             symbol: "✦",
+            stopped_symbol: None,
             style: "bold blue",
             disabled: false,
         }
@@ -5,9 +5,13 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct KotlinConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
     pub kotlin_binary: &'a str,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -15,9 +19,13 @@ impl<'a> RootModuleConfig<'a> for KotlinConfig<'a> {
     fn new() -> Self {
         KotlinConfig {
             format: "via [$symbol$version]($style) ",
+            version_format: None,
             symbol: "🅺 ",
             style: "bold blue",
             kotlin_binary: "kotlin",
+            detect_extensions: vec!["kt", "kts"],
+            detect_files: vec![],
+            detect_folders: vec![],
             disabled: false,
         }
     }
@@ -1,7 +1,7 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
+use indexmap::IndexMap;
 
 use starship_module_config_derive::ModuleConfig;
-use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig)]
 pub struct KubernetesConfig<'a> {
@@ -9,7 +9,10 @@ pub struct KubernetesConfig<'a> {
     pub format: &'a str,
     pub style: &'a str,
     pub disabled: bool,
-    pub context_aliases: HashMap<String, &'a str>,
+    pub context_aliases: IndexMap<String, &'a str>,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
 }
 
 impl<'a> RootModuleConfig<'a> for KubernetesConfig<'a> {
@@ -19,7 +22,10 @@ impl<'a> RootModuleConfig<'a> for KubernetesConfig<'a> {
             format: "[$symbol$context( \\($namespace\\))]($style) in ",
             style: "cyan bold",
             disabled: true,
-            context_aliases: HashMap::new(),
+            context_aliases: IndexMap::new(),
+            detect_extensions: vec![],
+            detect_files: vec![],
+            detect_folders: vec![],
         }
     }
 }
@@ -5,9 +5,13 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct LuaConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
     pub lua_binary: &'a str,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -15,9 +19,13 @@ impl<'a> RootModuleConfig<'a> for LuaConfig<'a> {
     fn new() -> Self {
         LuaConfig {
             format: "via [$symbol$version]($style) ",
+            version_format: None,
             symbol: "🌙 ",
             style: "bold blue",
             lua_binary: "lua",
+            detect_extensions: vec!["lua"],
+            detect_files: vec![".lua-version"],
+            detect_folders: vec!["lua"],
             disabled: false,
         }
     }
@@ -1,55 +1,96 @@
+#[cfg(feature = "languages")]
+pub mod asdf;
+#[cfg(feature = "cloud")]
 pub mod aws;
+#[cfg(feature = "battery")]
 pub mod battery;
 pub mod character;
+#[cfg(feature = "languages")]
 pub mod cmake;
 pub mod cmd_duration;
+#[cfg(feature = "languages")]
 pub mod conda;
+#[cfg(feature = "languages")]
 pub mod crystal;
 pub mod custom;
+#[cfg(feature = "languages")]
 pub mod dart;
 pub mod directory;
+#[cfg(feature = "cloud")]
 pub mod docker_context;
+#[cfg(feature = "languages")]
 pub mod dotnet;
+#[cfg(feature = "languages")]
 pub mod elixir;
+#[cfg(feature = "languages")]
 pub mod elm;
 pub mod env_var;
+#[cfg(feature = "languages")]
 pub mod erlang;
+#[cfg(feature = "cloud")]
 pub mod gcloud;
 pub mod git_branch;
 pub mod git_commit;
 pub mod git_state;
 pub mod git_status;
+#[cfg(feature = "languages")]
 pub mod go;
+#[cfg(feature = "cloud")]
 pub mod helm;
+#[cfg(feature = "hg")]
 pub mod hg_branch;
 pub mod hostname;
+#[cfg(feature = "languages")]
 pub mod java;
 pub mod jobs;
+#[cfg(feature = "languages")]
 pub mod julia;
+#[cfg(feature = "languages")]
 pub mod kotlin;
+#[cfg(feature = "cloud")]
 pub mod kubernetes;
+#[cfg(feature = "languages")]
 pub mod lua;
 pub mod memory_usage;
+#[cfg(feature = "languages")]
 pub mod nim;
 pub mod nix_shell;
+#[cfg(feature = "languages")]
 pub mod nodejs;
+#[cfg(feature = "languages")]
 pub mod ocaml;
+#[cfg(feature = "cloud")]
 pub mod openstack;
+#[cfg(feature = "languages")]
 pub mod package;
+#[cfg(feature = "languages")]
 pub mod perl;
+#[cfg(feature = "languages")]
 pub mod php;
+pub mod plugin;
+#[cfg(feature = "cloud")]
+pub mod podman;
+#[cfg(feature = "languages")]
 pub mod purescript;
+#[cfg(feature = "languages")]
 pub mod python;
+#[cfg(feature = "languages")]
 pub mod ruby;
+#[cfg(feature = "languages")]
 pub mod rust;
 pub mod shlvl;
+#[cfg(feature = "cloud")]
 pub mod singularity;
 mod starship_root;
 pub mod status;
+#[cfg(feature = "languages")]
 pub mod swift;
+#[cfg(feature = "cloud")]
 pub mod terraform;
 pub mod time;
+pub mod tmux;
 pub mod username;
+#[cfg(feature = "languages")]
 pub mod zig;
 
 pub use starship_root::*;
@@ -1,12 +1,19 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
 use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig)]
 pub struct NodejsConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub package_manager_symbols: HashMap<String, &'a str>,
+    pub use_pinned_version: bool,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
     pub not_capable_style: &'a str,
 }
@@ -15,8 +22,21 @@ impl<'a> RootModuleConfig<'a> for NodejsConfig<'a> {
     fn new() -> Self {
         NodejsConfig {
             format: "via [$symbol$version]($style) ",
+            version_format: None,
             symbol: "⬢ ",
             style: "bold green",
+            package_manager_symbols: HashMap::new(),
+            use_pinned_version: false,
+            detect_extensions: vec!["js", "mjs", "cjs", "ts"],
+            detect_files: vec![
+                "package.json",
+                ".node-version",
+                ".nvmrc",
+                "!deno.json",
+                "!deno.jsonc",
+                "!deno.lock",
+            ],
+            detect_folders: vec!["node_modules", "!esy.lock"],
             disabled: false,
             not_capable_style: "bold red",
         }
@@ -8,6 +8,8 @@ pub struct PackageConfig<'a> {
     pub symbol: &'a str,
     pub style: &'a str,
     pub display_private: bool,
+    pub search_upwards: bool,
+    pub version_metadata: &'a str,
     pub disabled: bool,
 }
 
@@ -18,6 +20,8 @@ impl<'a> RootModuleConfig<'a> for PackageConfig<'a> {
             symbol: "📦 ",
             style: "208 bold",
             display_private: false,
+            search_upwards: false,
+            version_metadata: "full",
             disabled: false,
         }
     }
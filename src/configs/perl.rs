@@ -7,6 +7,10 @@ pub struct PerlConfig<'a> {
     pub symbol: &'a str,
     pub style: &'a str,
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -16,6 +20,18 @@ impl<'a> RootModuleConfig<'a> for PerlConfig<'a> {
             symbol: "🐪 ",
             style: "149 bold",
             format: "via [$symbol$version]($style) ",
+            version_format: None,
+            detect_extensions: vec!["pl", "pm", "pod"],
+            detect_files: vec![
+                "Makefile.PL",
+                "Build.PL",
+                "cpanfile",
+                "cpanfile.snapshot",
+                "META.json",
+                "META.yml",
+                ".perl-version",
+            ],
+            detect_folders: vec![],
             disabled: false,
         }
     }
@@ -6,7 +6,12 @@ use starship_module_config_derive::ModuleConfig;
 pub struct PhpConfig<'a> {
     pub symbol: &'a str,
     pub style: &'a str,
+    pub not_capable_style: &'a str,
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -15,7 +20,12 @@ impl<'a> RootModuleConfig<'a> for PhpConfig<'a> {
         PhpConfig {
             symbol: "🐘 ",
             style: "147 bold",
+            not_capable_style: "bold red",
             format: "via [$symbol$version]($style) ",
+            version_format: None,
+            detect_extensions: vec!["php"],
+            detect_files: vec!["composer.json", ".php-version"],
+            detect_folders: vec![],
             disabled: false,
         }
     }
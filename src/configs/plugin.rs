@@ -0,0 +1,22 @@
+use crate::config::{ModuleConfig, RootModuleConfig};
+
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig)]
+pub struct PluginConfig<'a> {
+    pub command: Option<&'a str>,
+    pub style: &'a str,
+    pub description: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for PluginConfig<'a> {
+    fn new() -> Self {
+        PluginConfig {
+            command: None,
+            style: "",
+            description: "<plugin config>",
+            disabled: false,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use crate::config::{ModuleConfig, RootModuleConfig};
+
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig)]
+pub struct PodmanConfig<'a> {
+    pub symbol: &'a str,
+    pub style: &'a str,
+    pub format: &'a str,
+    pub only_with_files: bool,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
+    pub disabled: bool,
+}
+
+impl<'a> RootModuleConfig<'a> for PodmanConfig<'a> {
+    fn new() -> Self {
+        PodmanConfig {
+            symbol: "🦭 ",
+            style: "purple bold",
+            format: "via [$symbol$connection]($style) ",
+            only_with_files: true,
+            detect_extensions: vec![],
+            detect_files: vec![
+                "Containerfile",
+                "Dockerfile",
+                "docker-compose.yml",
+                "docker-compose.yaml",
+                "compose.yml",
+                "compose.yaml",
+            ],
+            detect_folders: vec![],
+            disabled: false,
+        }
+    }
+}
@@ -9,8 +9,14 @@ pub struct PythonConfig<'a> {
     pub python_binary: VecOr<&'a str>,
     pub scan_for_pyfiles: bool,
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub style: &'a str,
     pub symbol: &'a str,
+    pub not_capable_style: &'a str,
+    pub use_pinned_version: bool,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -22,8 +28,22 @@ impl<'a> RootModuleConfig<'a> for PythonConfig<'a> {
             python_binary: VecOr(vec!["python", "python3", "python2"]),
             scan_for_pyfiles: true,
             format: "via [${symbol}${pyenv_prefix}${version}( \\($virtualenv\\))]($style) ",
+            version_format: None,
             style: "yellow bold",
             symbol: "🐍 ",
+            not_capable_style: "bold red",
+            use_pinned_version: false,
+            detect_extensions: vec!["py"],
+            detect_files: vec![
+                "requirements.txt",
+                ".python-version",
+                "pyproject.toml",
+                "Pipfile",
+                "tox.ini",
+                "setup.py",
+                "__init__.py",
+            ],
+            detect_folders: vec![],
             disabled: false,
         }
     }
@@ -1,12 +1,20 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
 use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig)]
 pub struct RustConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub channel_styles: HashMap<String, &'a str>,
+    pub not_capable_style: &'a str,
+    pub use_pinned_version: bool,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -14,8 +22,15 @@ impl<'a> RootModuleConfig<'a> for RustConfig<'a> {
     fn new() -> Self {
         RustConfig {
             format: "via [$symbol$version]($style) ",
+            version_format: None,
             symbol: "🦀 ",
             style: "bold red",
+            channel_styles: HashMap::new(),
+            not_capable_style: "bold yellow",
+            use_pinned_version: false,
+            detect_extensions: vec!["rs"],
+            detect_files: vec!["Cargo.toml"],
+            detect_folders: vec![],
             disabled: false,
         }
     }
@@ -1,12 +1,26 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
 
 use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig)]
 pub struct StarshipRootConfig<'a> {
     pub format: &'a str,
+    pub transient_format: &'a str,
+    pub right_format: &'a str,
     pub scan_timeout: u64,
+    pub plugin_timeout: u64,
+    pub scan_upwards: bool,
+    pub follow_symlinks: bool,
+    pub slow_fs_skip_modules: Vec<&'a str>,
+    pub slow_fs_scan_timeout: u64,
+    pub slow_fs_mounts: HashMap<String, bool>,
     pub add_newline: bool,
+    pub version_format: &'a str,
+    pub palette: &'a str,
+    pub palette_query_timeout: u64,
+    pub symbol_set: &'a str,
+    pub powerline_separator: &'a str,
 }
 
 // List of default prompt order
@@ -16,50 +30,88 @@ pub const PROMPT_ORDER: &[&str] = &[
     "username",
     "hostname",
     "shlvl",
+    #[cfg(feature = "cloud")]
     "singularity",
+    #[cfg(feature = "cloud")]
     "kubernetes",
     "directory",
     "git_branch",
     "git_commit",
     "git_state",
     "git_status",
+    #[cfg(feature = "hg")]
     "hg_branch",
+    #[cfg(feature = "cloud")]
     "docker_context",
+    #[cfg(feature = "cloud")]
+    "podman",
+    #[cfg(feature = "languages")]
     "package",
+    #[cfg(feature = "languages")]
+    "asdf",
     // ↓ Toolchain version modules ↓
     // (Let's keep these sorted alphabetically)
+    #[cfg(feature = "languages")]
     "cmake",
+    #[cfg(feature = "languages")]
     "dart",
+    #[cfg(feature = "languages")]
     "dotnet",
+    #[cfg(feature = "languages")]
     "elixir",
+    #[cfg(feature = "languages")]
     "elm",
+    #[cfg(feature = "languages")]
     "erlang",
+    #[cfg(feature = "languages")]
     "golang",
+    #[cfg(feature = "cloud")]
     "helm",
+    #[cfg(feature = "languages")]
     "java",
+    #[cfg(feature = "languages")]
     "julia",
+    #[cfg(feature = "languages")]
     "kotlin",
+    #[cfg(feature = "languages")]
     "lua",
+    #[cfg(feature = "languages")]
     "nim",
+    #[cfg(feature = "languages")]
     "nodejs",
+    #[cfg(feature = "languages")]
     "ocaml",
+    #[cfg(feature = "languages")]
     "perl",
+    #[cfg(feature = "languages")]
     "php",
+    #[cfg(feature = "languages")]
     "purescript",
+    #[cfg(feature = "languages")]
     "python",
+    #[cfg(feature = "languages")]
     "ruby",
+    #[cfg(feature = "languages")]
     "rust",
+    #[cfg(feature = "languages")]
     "swift",
+    #[cfg(feature = "cloud")]
     "terraform",
+    #[cfg(feature = "languages")]
     "zig",
     // ↑ Toolchain version modules ↑
     "nix_shell",
+    #[cfg(feature = "languages")]
     "conda",
     "memory_usage",
+    #[cfg(feature = "cloud")]
     "aws",
+    #[cfg(feature = "cloud")]
     "gcloud",
+    #[cfg(feature = "cloud")]
     "openstack",
     "env_var",
+    #[cfg(feature = "languages")]
     "crystal",
     "custom",
     "cmd_duration",
@@ -76,8 +128,21 @@ impl<'a> RootModuleConfig<'a> for StarshipRootConfig<'a> {
     fn new() -> Self {
         StarshipRootConfig {
             format: "$all",
+            transient_format: "$character",
+            right_format: "",
             scan_timeout: 30,
+            plugin_timeout: 500,
+            scan_upwards: false,
+            follow_symlinks: true,
+            slow_fs_skip_modules: vec!["git_status", "package"],
+            slow_fs_scan_timeout: 5,
+            slow_fs_mounts: HashMap::new(),
             add_newline: true,
+            version_format: "v${raw}",
+            palette: "",
+            palette_query_timeout: 100,
+            symbol_set: "",
+            powerline_separator: "",
         }
     }
 }
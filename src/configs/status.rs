@@ -1,4 +1,5 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
+use indexmap::IndexMap;
 
 use starship_module_config_derive::ModuleConfig;
 
@@ -13,6 +14,12 @@ pub struct StatusConfig<'a> {
     pub style: &'a str,
     pub map_symbol: bool,
     pub recognize_signal_code: bool,
+    pub show_signal_name: bool,
+    pub classes: IndexMap<String, StatusClassConfig<'a>>,
+    pub pipestatus: bool,
+    pub pipestatus_separator: &'a str,
+    pub pipestatus_format: &'a str,
+    pub pipestatus_segment_format: &'a str,
     pub disabled: bool,
 }
 
@@ -28,7 +35,31 @@ impl<'a> RootModuleConfig<'a> for StatusConfig<'a> {
             style: "bold red",
             map_symbol: false,
             recognize_signal_code: true,
+            show_signal_name: false,
+            classes: IndexMap::new(),
+            pipestatus: false,
+            pipestatus_separator: "|",
+            pipestatus_format: "\\[$pipestatus\\] => [$symbol$status]($style) ",
+            pipestatus_segment_format: "[$status]($style)",
             disabled: true,
         }
     }
 }
+
+/// Symbol and style overrides for a class of exit codes
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct StatusClassConfig<'a> {
+    pub symbol: Option<&'a str>,
+    pub style: Option<&'a str>,
+}
+
+impl<'a> ModuleConfig<'a> for StatusClassConfig<'a> {
+    fn from_config(config: &'a toml::Value) -> Option<Self> {
+        let config = config.as_table()?;
+
+        Some(StatusClassConfig {
+            symbol: config.get("symbol").and_then(toml::Value::as_str),
+            style: config.get("style").and_then(toml::Value::as_str),
+        })
+    }
+}
@@ -5,8 +5,13 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct SwiftConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub not_capable_style: &'a str,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -14,8 +19,13 @@ impl<'a> RootModuleConfig<'a> for SwiftConfig<'a> {
     fn new() -> Self {
         SwiftConfig {
             format: "via [$symbol$version]($style) ",
+            version_format: None,
             symbol: "🐦 ",
             style: "bold 202",
+            not_capable_style: "bold red",
+            detect_extensions: vec!["swift"],
+            detect_files: vec![],
+            detect_folders: vec![],
             disabled: false,
         }
     }
@@ -5,8 +5,15 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct TerraformConfig<'a> {
     pub format: &'a str,
+    pub version_format: Option<&'a str>,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub not_capable_style: &'a str,
+    pub version_from_binary: bool,
+    pub use_pinned_version: bool,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -14,8 +21,15 @@ impl<'a> RootModuleConfig<'a> for TerraformConfig<'a> {
     fn new() -> Self {
         TerraformConfig {
             format: "via [$symbol$workspace]($style) ",
+            version_format: None,
             symbol: "💠 ",
             style: "bold 105",
+            not_capable_style: "bold red",
+            version_from_binary: false,
+            use_pinned_version: false,
+            detect_extensions: vec!["tf", "hcl"],
+            detect_files: vec![],
+            detect_folders: vec![".terraform"],
             disabled: false,
         }
     }
@@ -0,0 +1,16 @@
+use crate::config::{ModuleConfig, RootModuleConfig};
+
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig)]
+pub struct TmuxConfig<'a> {
+    pub format: &'a str,
+}
+
+impl<'a> RootModuleConfig<'a> for TmuxConfig<'a> {
+    fn new() -> Self {
+        TmuxConfig {
+            format: "$hostname$kubernetes$battery",
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::config::{ModuleConfig, RootModuleConfig};
+use indexmap::IndexMap;
 
 use starship_module_config_derive::ModuleConfig;
 
@@ -7,7 +8,11 @@ pub struct UsernameConfig<'a> {
     pub format: &'a str,
     pub style_root: &'a str,
     pub style_user: &'a str,
+    pub user_styles: IndexMap<String, &'a str>,
     pub show_always: bool,
+    pub show_for_users: Vec<&'a str>,
+    pub hide_for_users: Vec<&'a str>,
+    pub remote_detection: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -17,7 +22,11 @@ impl<'a> RootModuleConfig<'a> for UsernameConfig<'a> {
             format: "[$user]($style) in ",
             style_root: "red bold",
             style_user: "yellow bold",
+            user_styles: IndexMap::new(),
             show_always: false,
+            show_for_users: vec![],
+            hide_for_users: vec![],
+            remote_detection: vec!["env"],
             disabled: false,
         }
     }
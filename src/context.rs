@@ -2,15 +2,19 @@ use crate::config::StarshipConfig;
 use crate::module::Module;
 
 use crate::modules;
+use crate::palette::{self, Palette};
+use crate::trace;
 use clap::ArgMatches;
 use git2::{ErrorCode::UnbornBranch, Repository, RepositoryState};
 use once_cell::sync::OnceCell;
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::env;
 use std::ffi::OsString;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::string::String;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 /// Context contains data or common methods that may be used by multiple modules.
@@ -26,9 +30,24 @@ pub struct Context<'a> {
     /// A struct containing directory contents in a lookup-optimised format.
     dir_contents: OnceCell<DirContents>,
 
+    /// The repo root up to which files and folders are detected, if `scan_upwards` is enabled.
+    scan_root: OnceCell<Option<PathBuf>>,
+
+    /// Whether the current directory is on a slow (e.g. network) filesystem.
+    slow_fs: OnceCell<bool>,
+
+    /// Whether detection and git discovery follow symlinks, see `follow_symlinks`.
+    follow_symlinks: OnceCell<bool>,
+
+    /// The colors that style strings can use by name, see `palette`.
+    palette: OnceCell<Arc<Palette>>,
+
     /// Properties to provide to modules.
     pub properties: HashMap<&'a str, String>,
 
+    /// The exit codes of every command in the last pipeline, if provided by the shell
+    pub pipestatus: Option<Vec<String>>,
+
     /// Private field to store Git information for modules who need it
     repo: OnceCell<Repo>,
 
@@ -37,6 +56,9 @@ pub struct Context<'a> {
 
     /// A HashMap of environment variable mocks
     pub env: HashMap<&'a str, String>,
+
+    /// The width of the terminal, in columns
+    pub width: usize,
 }
 
 impl<'a> Context<'a> {
@@ -74,19 +96,54 @@ impl<'a> Context<'a> {
             .map(|(a, b)| (*a, b.vals.first().cloned().unwrap().into_string().unwrap()))
             .collect();
 
+        Context::new_with_properties(config, dir, Context::get_shell(), properties)
+    }
+
+    /// Create a new instance of Context from explicit inputs, to embed starship in other tools
+    ///
+    /// The properties are the ones given to `starship prompt` by the shell, by the name of
+    /// their argument, e.g. `status_code`, `cmd_duration`, `pipestatus`, `jobs`, `keymap`
+    /// or `terminal_width`.
+    pub fn new_with_properties<T>(
+        config: StarshipConfig,
+        dir: T,
+        shell: Shell,
+        properties: HashMap<&'a str, String>,
+    ) -> Context<'a>
+    where
+        T: Into<PathBuf>,
+    {
+        // Pipestatus is a whitespace-separated list of exit codes, expand it
+        let pipestatus = properties.get("pipestatus").map(|pipestatus| {
+            pipestatus
+                .split_ascii_whitespace()
+                .map(str::to_string)
+                .collect()
+        });
+
         // TODO: Currently gets the physical directory. Get the logical directory.
         let current_dir = Context::expand_tilde(dir.into());
 
-        let shell = Context::get_shell();
+        let width = properties
+            .get("terminal_width")
+            .and_then(|w| w.parse::<usize>().ok())
+            .or_else(|| term_size::dimensions().map(|(w, _)| w))
+            .unwrap_or(80);
 
         Context {
             config,
             properties,
+            pipestatus,
             current_dir,
             dir_contents: OnceCell::new(),
+            scan_root: OnceCell::new(),
+            slow_fs: OnceCell::new(),
+            follow_symlinks: OnceCell::new(),
+            palette: OnceCell::new(),
             repo: OnceCell::new(),
             shell,
             env: HashMap::new(),
+            width,
         }
     }
 
@@ -126,6 +183,8 @@ impl<'a> Context<'a> {
     }
 
     /// Check if `disabled` option of the module is true in configuration file.
+    ///
+    /// Modules in `slow_fs_skip_modules` are also disabled on slow filesystems.
     pub fn is_module_disabled_in_config(&self, name: &str) -> bool {
         let config = self.config.get_module_config(name);
 
@@ -133,6 +192,39 @@ impl<'a> Context<'a> {
         let disabled = config.and_then(|table| table.as_table()?.get("disabled")?.as_bool());
 
         disabled == Some(true)
+            || (self.is_slow_fs()
+                && self
+                    .config
+                    .get_root_config()
+                    .slow_fs_skip_modules
+                    .contains(&name))
+    }
+
+    /// Whether the current directory is on a slow filesystem, like a network share
+    ///
+    /// Directories can be marked as slow or not with `slow_fs_mounts`, where the longest
+    /// matching path wins. Otherwise, network filesystems are detected from the mount table.
+    pub fn is_slow_fs(&self) -> bool {
+        *self.slow_fs.get_or_init(|| {
+            let root_config = self.config.get_root_config();
+            let configured = root_config
+                .slow_fs_mounts
+                .iter()
+                .filter(|(path, _)| {
+                    self.current_dir
+                        .starts_with(Context::expand_tilde(PathBuf::from(path)))
+                })
+                .max_by_key(|(path, _)| path.len())
+                .map(|(_, is_slow)| *is_slow);
+
+            configured.unwrap_or_else(|| {
+                let is_slow = is_network_fs(&self.current_dir);
+                if is_slow {
+                    log::debug!("{:?} is on a network filesystem", self.current_dir);
+                }
+                is_slow
+            })
+        })
     }
 
     /// Return whether the specified custom module has a `disabled` option set to true.
@@ -144,17 +236,83 @@ impl<'a> Context<'a> {
         Some(disabled == Some(true))
     }
 
+    /// Whether the output is colored, as set by `--color`
+    ///
+    /// With `--color=auto`, colors are disabled by a non-empty `NO_COLOR` or by `TERM=dumb`, unless
+    /// `CLICOLOR_FORCE` is set to something else than `0`. The output of `starship prompt` is
+    /// read by the shell rather than shown, so it isn't checked for a terminal.
+    pub fn use_color(&self) -> bool {
+        match self.properties.get("color").map(String::as_str) {
+            Some("always") => true,
+            Some("never") => false,
+            _ => {
+                let is_set = |name| matches!(self.get_env(name), Some(value) if !value.is_empty());
+                if is_set("NO_COLOR") {
+                    false
+                } else if matches!(self.get_env("CLICOLOR_FORCE"), Some(value) if value != "0") {
+                    true
+                } else {
+                    self.get_env("TERM").as_deref() != Some("dumb")
+                }
+            }
+        }
+    }
+
+    /// The palette selected by `palette`, whose colors style strings can use by name
+    pub fn palette(&self) -> Arc<Palette> {
+        self.palette
+            .get_or_init(|| Arc::new(palette::load(self)))
+            .clone()
+    }
+
     // returns a new ScanDir struct with reference to current dir_files of context
     // see ScanDir for methods
     pub fn try_begin_scan(&'a self) -> Option<ScanDir<'a>> {
         Some(ScanDir {
             dir_contents: self.dir_contents().ok()?,
+            upward_dirs: self.upward_scan_dirs(),
+            follow_symlinks: self.follows_symlinks(),
             files: &[],
             folders: &[],
             extensions: &[],
         })
     }
 
+    /// The parent directories of the current directory up to the repo root, in which
+    /// files and folders are also detected if `scan_upwards` is enabled
+    fn upward_scan_dirs(&self) -> Vec<&Path> {
+        let scan_root = self.scan_root.get_or_init(|| {
+            if !self.config.get_root_config().scan_upwards {
+                return None;
+            }
+            self.get_repo().ok()?.root.clone()
+        });
+
+        let follow_symlinks = self.follows_symlinks();
+        let mut is_below_symlink = false;
+        match scan_root {
+            Some(root) => self
+                .current_dir
+                .ancestors()
+                .take_while(|dir| {
+                    // Don't leave a symlinked directory if symlinks aren't followed
+                    let is_parent_of_symlink = is_below_symlink;
+                    is_below_symlink = !follow_symlinks && is_symlink(dir);
+                    dir.starts_with(root) && !is_parent_of_symlink
+                })
+                .skip(1)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Whether detection and git discovery follow symlinks
+    pub fn follows_symlinks(&self) -> bool {
+        *self
+            .follow_symlinks
+            .get_or_init(|| self.config.get_root_config().follow_symlinks)
+    }
+
     /// Will lazily get repo root and branch when a module requests it.
     pub fn get_repo(&self) -> Result<&Repo, std::io::Error> {
         self.repo
@@ -162,7 +320,7 @@ impl<'a> Context<'a> {
                 let repository = if env::var("GIT_DIR").is_ok() {
                     Repository::open_from_env().ok()
                 } else {
-                    Repository::discover(&self.current_dir).ok()
+                    discover_repository(&self.current_dir, self.follows_symlinks())
                 };
                 let branch = repository
                     .as_ref()
@@ -185,8 +343,17 @@ impl<'a> Context<'a> {
 
     pub fn dir_contents(&self) -> Result<&DirContents, std::io::Error> {
         self.dir_contents.get_or_try_init(|| {
-            let timeout = Duration::from_millis(self.config.get_root_config().scan_timeout);
-            DirContents::from_path_with_timeout(&self.current_dir, timeout)
+            let root_config = self.config.get_root_config();
+            let timeout = if self.is_slow_fs() {
+                root_config
+                    .scan_timeout
+                    .min(root_config.slow_fs_scan_timeout)
+            } else {
+                root_config.scan_timeout
+            };
+            let timeout = Duration::from_millis(timeout);
+            let _span = trace::span("scan", || self.current_dir.to_string_lossy());
+            DirContents::from_path_with_timeout(&self.current_dir, timeout, self.follows_symlinks())
         })
     }
 
@@ -205,6 +372,19 @@ impl<'a> Context<'a> {
     pub fn get_cmd_duration(&self) -> Option<u128> {
         self.properties.get("cmd_duration")?.parse::<u128>().ok()
     }
+
+    /// The command line of the last command, if the shell passed it through
+    ///
+    /// PowerShell passes it in `STARSHIP_LAST_COMMAND`, since Windows PowerShell mangles
+    /// quotes in the arguments of native commands.
+    pub fn get_last_command(&self) -> Option<String> {
+        self.properties
+            .get("last_command")
+            .cloned()
+            .or_else(|| self.get_env("STARSHIP_LAST_COMMAND"))
+            .map(|command| command.trim().to_string())
+            .filter(|command| !command.is_empty())
+    }
 }
 
 #[derive(Debug)]
@@ -222,10 +402,15 @@ pub struct DirContents {
 impl DirContents {
     #[cfg(test)]
     fn from_path(base: &PathBuf) -> Result<Self, std::io::Error> {
-        Self::from_path_with_timeout(base, Duration::from_secs(30))
+        Self::from_path_with_timeout(base, Duration::from_secs(30), true)
     }
 
-    fn from_path_with_timeout(base: &Path, timeout: Duration) -> Result<Self, std::io::Error> {
+    /// Reads the entries of a directory, ignoring symlinks unless `follow_symlinks` is set
+    fn from_path_with_timeout(
+        base: &Path,
+        timeout: Duration,
+        follow_symlinks: bool,
+    ) -> Result<Self, std::io::Error> {
         let start = Instant::now();
 
         let mut folders: HashSet<PathBuf> = HashSet::new();
@@ -240,9 +425,19 @@ impl DirContents {
                 || start.elapsed() < timeout
             })
             .filter_map(|(_, entry)| entry.ok())
+            .filter(|entry| {
+                follow_symlinks
+                    || !matches!(entry.file_type(), Ok(file_type) if file_type.is_symlink())
+            })
             .for_each(|entry| {
                 let path = PathBuf::from(entry.path().strip_prefix(base).unwrap());
-                if entry.path().is_dir() {
+                // The file type usually comes with the directory entry, so only symlinks need
+                // another stat() to know whether they point to a folder
+                let is_dir = match entry.file_type() {
+                    Ok(file_type) if !file_type.is_symlink() => file_type.is_dir(),
+                    _ => entry.path().is_dir(),
+                };
+                if is_dir {
                     folders.insert(path);
                 } else {
                     if !path.to_string_lossy().starts_with('.') {
@@ -277,16 +472,30 @@ impl DirContents {
         self.files.contains(Path::new(path))
     }
 
+    /// Whether a file is named `name`, which can be a glob pattern like `*.csproj`
     pub fn has_file_name(&self, name: &str) -> bool {
-        self.file_names.contains(name)
+        if is_glob(name) {
+            self.file_names
+                .iter()
+                .any(|file_name| glob_match(name, file_name))
+        } else {
+            self.file_names.contains(name)
+        }
     }
 
     pub fn has_any_file_name(&self, names: &[&str]) -> bool {
         names.iter().any(|name| self.has_file_name(name))
     }
 
+    /// Whether there is a folder at `path`, which can be a glob pattern like `*.xcodeproj`
     pub fn has_folder(&self, path: &str) -> bool {
-        self.folders.contains(Path::new(path))
+        if is_glob(path) {
+            self.folders
+                .iter()
+                .any(|folder| glob_match(path, &folder.to_string_lossy()))
+        } else {
+            self.folders.contains(Path::new(path))
+        }
     }
 
     pub fn has_any_folder(&self, paths: &[&str]) -> bool {
@@ -302,6 +511,152 @@ impl DirContents {
     }
 }
 
+/// Filesystem types of network shares and mounts that are slow to scan, like WSL's Windows drives
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "9p", "drvfs", "afs", "ncpfs", "davfs", "sshfs",
+];
+
+/// Whether a path is on a network filesystem, according to the mount table
+#[cfg(target_os = "linux")]
+fn is_network_fs(path: &Path) -> bool {
+    match fs::read_to_string("/proc/mounts") {
+        Ok(mounts) => matches!(
+            get_mount_fs_type(&mounts, path),
+            Some(fs_type) if is_network_fs_type(fs_type)
+        ),
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_network_fs(_path: &Path) -> bool {
+    false
+}
+
+fn is_network_fs_type(fs_type: &str) -> bool {
+    // FUSE filesystems are listed as e.g. `fuse.sshfs`
+    let fs_type = fs_type.strip_prefix("fuse.").unwrap_or(fs_type);
+    NETWORK_FS_TYPES.contains(&fs_type)
+}
+
+/// Finds the filesystem type of the mount a path is on, in a mount table like `/proc/mounts`
+fn get_mount_fs_type<'a>(mounts: &'a str, path: &Path) -> Option<&'a str> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            // Spaces in mount points are escaped as `\040`
+            let mount_point = PathBuf::from(fields.next()?.replace("\\040", " "));
+            let fs_type = fields.next()?;
+            Some((mount_point, fs_type))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.as_os_str().len())
+        .map(|(_, fs_type)| fs_type)
+}
+
+/// Whether a detected file or folder name is a glob pattern
+fn is_glob(name: &str) -> bool {
+    name.contains(&['*', '?'][..])
+}
+
+/// Matches a name against a glob pattern, where `*` matches any number of characters
+/// and `?` matches exactly one
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // The position of the last `*` in the pattern, and of the name when it was reached
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` match one more character
+                Some((star, star_n)) => {
+                    p = star + 1;
+                    n = star_n + 1;
+                    backtrack = Some((star, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Splits scan criteria into the ones to match and the ones that exclude a directory,
+/// which start with a `!`
+fn split_exclusions<'a>(criteria: &[&'a str]) -> (Vec<&'a str>, Vec<&'a str>) {
+    let mut included = Vec::new();
+    let mut excluded = Vec::new();
+    for criterion in criteria {
+        match criterion.strip_prefix('!') {
+            Some(criterion) => excluded.push(criterion),
+            None => included.push(*criterion),
+        }
+    }
+    (included, excluded)
+}
+
+/// Whether `dir` contains a file (or folder) named `name`, which can be a glob pattern
+fn dir_has_entry(dir: &Path, name: &str, is_folder: bool, follow_symlinks: bool) -> bool {
+    let is_entry = |path: &Path| {
+        let metadata = if follow_symlinks {
+            fs::metadata(path)
+        } else {
+            fs::symlink_metadata(path)
+        };
+        match metadata {
+            Ok(metadata) if is_folder => metadata.is_dir(),
+            Ok(metadata) => metadata.is_file(),
+            Err(_) => false,
+        }
+    };
+
+    if !is_glob(name) {
+        return is_entry(&dir.join(name));
+    }
+
+    match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(Result::ok).any(|entry| {
+            glob_match(name, &entry.file_name().to_string_lossy()) && is_entry(&entry.path())
+        }),
+        Err(_) => false,
+    }
+}
+
+fn is_symlink(path: &Path) -> bool {
+    matches!(fs::symlink_metadata(path), Ok(metadata) if metadata.file_type().is_symlink())
+}
+
+/// Finds the repository the directory is in, without leaving a symlinked directory through
+/// its parents if `follow_symlinks` is disabled
+fn discover_repository(dir: &Path, follow_symlinks: bool) -> Option<Repository> {
+    if follow_symlinks {
+        return Repository::discover(dir).ok();
+    }
+
+    for dir in dir.ancestors() {
+        if fs::symlink_metadata(dir.join(".git")).is_ok() {
+            return Repository::open(dir).ok();
+        }
+        if is_symlink(dir) {
+            return None;
+        }
+    }
+    None
+}
+
 pub struct Repo {
     /// If `current_dir` is a git repository or is contained within one,
     /// this is the current branch name of that repo.
@@ -328,6 +683,8 @@ pub struct Remote {
 // of X language, criteria can be set via the builder pattern
 pub struct ScanDir<'a> {
     dir_contents: &'a DirContents,
+    upward_dirs: Vec<&'a Path>,
+    follow_symlinks: bool,
     files: &'a [&'a str],
     folders: &'a [&'a str],
     extensions: &'a [&'a str],
@@ -351,13 +708,81 @@ impl<'a> ScanDir<'a> {
 
     /// based on the current PathBuf check to see
     /// if any of this criteria match or exist and returning a boolean
+    ///
+    /// Files and folders are also looked for in the parent directories up to the repo root
+    /// if `scan_upwards` is enabled.
+    ///
+    /// Criteria starting with a `!` exclude the directory instead, e.g. with files
+    /// `["package.json", "!deno.json"]` a directory with a `deno.json` never matches.
     pub fn is_match(&self) -> bool {
-        self.dir_contents.has_any_extension(self.extensions)
-            || self.dir_contents.has_any_folder(self.folders)
-            || self.dir_contents.has_any_file_name(self.files)
+        let (files, excluded_files) = split_exclusions(self.files);
+        let (folders, excluded_folders) = split_exclusions(self.folders);
+        let (extensions, excluded_extensions) = split_exclusions(self.extensions);
+
+        if self
+            .find_any(&excluded_files, &excluded_folders, &excluded_extensions)
+            .is_some()
+        {
+            return false;
+        }
+        match self.find_any(&files, &folders, &extensions) {
+            Some(reason) => {
+                DETECTION.with(|detection| detection.replace(Some(reason)));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Describes the first criterion found, e.g. `file package.json`
+    fn find_any(&self, files: &[&str], folders: &[&str], extensions: &[&str]) -> Option<String> {
+        if let Some(extension) = extensions
+            .iter()
+            .find(|extension| self.dir_contents.has_extension(extension))
+        {
+            return Some(format!("extension {}", extension));
+        }
+        if let Some(folder) = folders
+            .iter()
+            .find(|folder| self.dir_contents.has_folder(folder))
+        {
+            return Some(format!("folder {}", folder));
+        }
+        if let Some(file) = files
+            .iter()
+            .find(|file| self.dir_contents.has_file_name(file))
+        {
+            return Some(format!("file {}", file));
+        }
+
+        self.upward_dirs.iter().find_map(|dir| {
+            if let Some(file) = files
+                .iter()
+                .find(|file| dir_has_entry(dir, file, false, self.follow_symlinks))
+            {
+                return Some(format!("file {} in {}", file, dir.display()));
+            }
+            folders
+                .iter()
+                .find(|folder| dir_has_entry(dir, folder, true, self.follow_symlinks))
+                .map(|folder| format!("folder {} in {}", folder, dir.display()))
+        })
     }
 }
 
+thread_local! {
+    // What the last matching scan on this thread found, for `starship explain`
+    static DETECTION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Takes what the last matching scan on this thread found, e.g. `extension rs`
+///
+/// A module is computed on a single thread, so taking this before and after computing it
+/// tells why it was shown.
+pub fn take_detection() -> Option<String> {
+    DETECTION.with(RefCell::take)
+}
+
 fn get_current_branch(repository: &Repository) -> Option<String> {
     let head = match repository.head() {
         Ok(reference) => reference,
@@ -435,6 +860,58 @@ mod tests {
         Ok(dir)
     }
 
+    #[test]
+    fn test_context_from_properties() {
+        let mut properties = HashMap::new();
+        properties.insert("pipestatus", "0 1".to_string());
+        properties.insert("terminal_width", "120".to_string());
+        let config = StarshipConfig { config: None };
+        let context = Context::new_with_properties(config, "/tmp", Shell::Zsh, properties);
+
+        assert_eq!(context.current_dir, PathBuf::from("/tmp"));
+        assert_eq!(context.shell, Shell::Zsh);
+        assert_eq!(context.width, 120);
+        assert_eq!(
+            context.pipestatus,
+            Some(vec!["0".to_string(), "1".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_use_color() {
+        let use_color = |color: Option<&str>, env: &[(&'static str, &str)]| {
+            let mut properties = HashMap::new();
+            if let Some(color) = color {
+                properties.insert("color", color.to_string());
+            }
+            let config = StarshipConfig { config: None };
+            let mut context = Context::new_with_properties(config, "/", Shell::Zsh, properties);
+            for (name, value) in env {
+                context.env.insert(name, value.to_string());
+            }
+            context.use_color()
+        };
+
+        assert!(use_color(None, &[]));
+        assert!(use_color(Some("auto"), &[("NO_COLOR", "")]));
+        assert!(!use_color(Some("auto"), &[("NO_COLOR", "1")]));
+        assert!(!use_color(None, &[("TERM", "dumb")]));
+        assert!(use_color(
+            None,
+            &[("TERM", "dumb"), ("CLICOLOR_FORCE", "1")]
+        ));
+        assert!(!use_color(
+            None,
+            &[("TERM", "dumb"), ("CLICOLOR_FORCE", "0")]
+        ));
+        assert!(!use_color(
+            None,
+            &[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]
+        ));
+        assert!(use_color(Some("always"), &[("NO_COLOR", "1")]));
+        assert!(!use_color(Some("never"), &[("CLICOLOR_FORCE", "1")]));
+    }
+
     #[test]
     fn test_scan_dir() -> Result<(), Box<dyn std::error::Error>> {
         let empty = testdir(&[])?;
@@ -443,6 +920,8 @@ mod tests {
         assert_eq!(
             ScanDir {
                 dir_contents: &empty_dc,
+                upward_dirs: Vec::new(),
+                follow_symlinks: true,
                 files: &["package.json"],
                 extensions: &["js"],
                 folders: &["node_modules"],
@@ -457,6 +936,8 @@ mod tests {
         assert_eq!(
             ScanDir {
                 dir_contents: &rust_dc,
+                upward_dirs: Vec::new(),
+                follow_symlinks: true,
                 files: &["package.json"],
                 extensions: &["js"],
                 folders: &["node_modules"],
@@ -471,6 +952,8 @@ mod tests {
         assert_eq!(
             ScanDir {
                 dir_contents: &java_dc,
+                upward_dirs: Vec::new(),
+                follow_symlinks: true,
                 files: &["package.json"],
                 extensions: &["js"],
                 folders: &["node_modules"],
@@ -485,6 +968,8 @@ mod tests {
         assert_eq!(
             ScanDir {
                 dir_contents: &node_dc,
+                upward_dirs: Vec::new(),
+                follow_symlinks: true,
                 files: &["package.json"],
                 extensions: &["js"],
                 folders: &["node_modules"],
@@ -496,4 +981,252 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_get_mount_fs_type() {
+        let mounts = "/dev/sda1 / ext4 rw,relatime 0 0\n\
+                      server:/export /home/user/share nfs4 rw 0 0\n\
+                      drvfs /mnt/c 9p rw 0 0\n\
+                      user@host:/ /home/user/my\\040remote fuse.sshfs rw 0 0\n";
+
+        let fs_type = |path: &str| get_mount_fs_type(mounts, Path::new(path));
+        assert_eq!(fs_type("/home/user"), Some("ext4"));
+        assert_eq!(fs_type("/home/user/share/project"), Some("nfs4"));
+        assert_eq!(fs_type("/home/user/shared"), Some("ext4"));
+        assert_eq!(fs_type("/mnt/c/Users"), Some("9p"));
+        assert_eq!(fs_type("/home/user/my remote/src"), Some("fuse.sshfs"));
+
+        assert!(is_network_fs_type("nfs4"));
+        assert!(is_network_fs_type("fuse.sshfs"));
+        assert!(!is_network_fs_type("ext4"));
+        assert!(!is_network_fs_type("fuse.portal"));
+    }
+
+    #[test]
+    fn test_slow_fs_mounts() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = testdir(&[])?;
+        let project = dir.path().join("project");
+        fs::create_dir(&project)?;
+
+        let context = |mounts: &[(&Path, bool)]| {
+            let mounts: toml::value::Table = mounts
+                .iter()
+                .map(|(path, is_slow)| (path.to_string_lossy().into_owned(), (*is_slow).into()))
+                .collect();
+            let mut config = toml::toml! {
+                slow_fs_skip_modules = ["git_status", "package"]
+            };
+            if let toml::Value::Table(config) = &mut config {
+                config.insert("slow_fs_mounts".to_string(), mounts.into());
+            }
+            let mut context = Context::new_with_dir(ArgMatches::default(), &project);
+            context.config = StarshipConfig {
+                config: Some(config),
+            };
+            context
+        };
+
+        let slow = context(&[(dir.path(), true)]);
+        assert!(slow.is_slow_fs());
+        assert!(slow.is_module_disabled_in_config("package"));
+        assert!(!slow.is_module_disabled_in_config("nodejs"));
+
+        let not_slow = context(&[(dir.path(), true), (&project, false)]);
+        assert!(!not_slow.is_slow_fs());
+        assert!(!not_slow.is_module_disabled_in_config("package"));
+        dir.close()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.csproj", "app.csproj"));
+        assert!(glob_match("*.csproj", ".csproj"));
+        assert!(!glob_match("*.csproj", "app.csproj.user"));
+        assert!(glob_match("docker-compose*.yml", "docker-compose.yml"));
+        assert!(glob_match(
+            "docker-compose*.yml",
+            "docker-compose.override.yml"
+        ));
+        assert!(!glob_match("docker-compose*.yml", "docker-compose.yaml"));
+        assert!(glob_match("?akefile", "Rakefile"));
+        assert!(!glob_match("?akefile", "akefile"));
+        assert!(glob_match("*a*b*", "xaxxbx"));
+        assert!(!glob_match("*a*b", "xaxxbx"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn test_scan_dir_globs() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = testdir(&["App.csproj", "docker-compose.override.yml"])?;
+        fs::create_dir(dir.path().join("App.xcodeproj"))?;
+        let dc = DirContents::from_path(&PathBuf::from(dir.path()))?;
+
+        assert!(dc.has_file_name("*.csproj"));
+        assert!(dc.has_file_name("docker-compose*.yml"));
+        assert!(!dc.has_file_name("*.fsproj"));
+        assert!(!dc.has_file_name("*.xcodeproj"));
+        assert!(dc.has_folder("*.xcodeproj"));
+        assert!(!dc.has_folder("*.csproj"));
+        dir.close()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_dir_exclusions() -> Result<(), Box<dyn std::error::Error>> {
+        let deno = testdir(&["main.ts", "deno.json"])?;
+        let deno_dc = DirContents::from_path(&PathBuf::from(deno.path()))?;
+        let node = testdir(&["main.ts", "package.json"])?;
+        let node_dc = DirContents::from_path(&PathBuf::from(node.path()))?;
+
+        let scan = |dir_contents| ScanDir {
+            dir_contents,
+            upward_dirs: Vec::new(),
+            follow_symlinks: true,
+            files: &["package.json", "!deno.json"],
+            extensions: &["ts", "!mjs"],
+            folders: &["!esy.lock"],
+        };
+        assert!(!scan(&deno_dc).is_match());
+        assert!(scan(&node_dc).is_match());
+
+        let only_exclusions = ScanDir {
+            dir_contents: &node_dc,
+            upward_dirs: Vec::new(),
+            follow_symlinks: true,
+            files: &["!deno.json"],
+            extensions: &[],
+            folders: &[],
+        };
+        assert!(!only_exclusions.is_match());
+        deno.close()?;
+        node.close()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_dir_detection() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = testdir(&["package.json", "node_modules/.keep"])?;
+        let dc = DirContents::from_path(&PathBuf::from(dir.path()))?;
+        let scan = |files, folders| ScanDir {
+            dir_contents: &dc,
+            upward_dirs: Vec::new(),
+            follow_symlinks: true,
+            files,
+            extensions: &["js"],
+            folders,
+        };
+
+        take_detection();
+        assert!(scan(&["package.json"], &["node_modules"]).is_match());
+        assert_eq!(take_detection(), Some("folder node_modules".to_string()));
+        assert!(scan(&["package.json"], &[]).is_match());
+        assert_eq!(take_detection(), Some("file package.json".to_string()));
+        assert!(!scan(&[], &[]).is_match());
+        assert_eq!(take_detection(), None);
+        dir.close()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_upwards() -> Result<(), Box<dyn std::error::Error>> {
+        let project = testdir(&["package.json", "src/components/button.js"])?;
+        fs::create_dir(project.path().join("node_modules"))?;
+        std::process::Command::new("git")
+            .args(&["init", "--quiet"])
+            .current_dir(project.path())
+            .output()?;
+        let components = project.path().join("src").join("components");
+
+        let is_match = |scan_upwards: bool, files: &[&str], folders: &[&str]| {
+            let mut context = Context::new_with_dir(ArgMatches::default(), &components);
+            context.config = StarshipConfig {
+                config: Some(toml::toml! {
+                    scan_upwards = scan_upwards
+                }),
+            };
+            let is_match = context
+                .try_begin_scan()
+                .unwrap()
+                .set_files(files)
+                .set_folders(folders)
+                .is_match();
+            is_match
+        };
+
+        assert!(!is_match(false, &["package.json"], &[]));
+        assert!(is_match(true, &["package.json"], &[]));
+        assert!(is_match(true, &[], &["node_modules"]));
+        assert!(!is_match(true, &["Cargo.toml"], &["target"]));
+        assert!(is_match(true, &["package.*"], &[]));
+        assert!(is_match(true, &[], &["node_*"]));
+        assert!(!is_match(true, &["node_*"], &["package.*"]));
+        assert!(!is_match(true, &["button.js", "!package.json"], &[]));
+        project.close()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_dir_contents_follows_symlinks() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = testdir(&["src/main.rs", "Cargo.toml"])?;
+        std::os::unix::fs::symlink(dir.path().join("src"), dir.path().join("linked_src"))?;
+        std::os::unix::fs::symlink(
+            dir.path().join("Cargo.toml"),
+            dir.path().join("linked.toml"),
+        )?;
+        let dc = DirContents::from_path(&PathBuf::from(dir.path()))?;
+
+        assert!(dc.has_folder("src"));
+        assert!(dc.has_folder("linked_src"));
+        assert!(dc.has_file("Cargo.toml"));
+        assert!(dc.has_file("linked.toml"));
+        assert!(dc.has_extension("toml"));
+        assert!(!dc.has_file("src/main.rs"));
+
+        let dc = DirContents::from_path_with_timeout(dir.path(), Duration::from_secs(30), false)?;
+        assert!(dc.has_folder("src"));
+        assert!(!dc.has_folder("linked_src"));
+        assert!(dc.has_file("Cargo.toml"));
+        assert!(!dc.has_file("linked.toml"));
+        dir.close()?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_git_discovery_without_following_symlinks() -> Result<(), Box<dyn std::error::Error>> {
+        let repo = testdir(&["sub/file"])?;
+        std::process::Command::new("git")
+            .args(&["init", "--quiet"])
+            .current_dir(repo.path())
+            .output()?;
+        let outside = testdir(&[])?;
+        let link = outside.path().join("link");
+        std::os::unix::fs::symlink(repo.path().join("sub"), &link)?;
+
+        let root = |follow_symlinks: bool| {
+            let mut context = Context::new_with_dir(ArgMatches::default(), &link);
+            context.config = StarshipConfig {
+                config: Some(toml::toml! {
+                    follow_symlinks = follow_symlinks
+                }),
+            };
+            let root = context.get_repo().unwrap().root.clone();
+            root
+        };
+
+        assert!(root(true).is_some());
+        assert_eq!(root(false), None);
+        repo.close()?;
+        outside.close()?;
+
+        Ok(())
+    }
 }
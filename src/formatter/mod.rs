@@ -1,6 +1,8 @@
 pub mod model;
 mod parser;
 pub mod string_formatter;
+mod version;
 
 pub use model::{StyleVariableHolder, VariableHolder};
-pub use string_formatter::StringFormatter;
+pub use string_formatter::{StringFormatter, StringFormatterError};
+pub use version::VersionFormatter;
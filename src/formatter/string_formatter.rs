@@ -197,6 +197,29 @@ impl<'a> StringFormatter<'a> {
         self
     }
 
+    /// The variables of the format string in the order they first appear in, unlike
+    /// `get_variables` which sorts them by name
+    pub fn get_variables_in_order(&self) -> Vec<String> {
+        fn collect<'a>(format: &[FormatElement<'a>], variables: &mut Vec<String>) {
+            for element in format {
+                match element {
+                    FormatElement::Variable(name) => {
+                        if !variables.iter().any(|variable| variable == name) {
+                            variables.push(name.to_string());
+                        }
+                    }
+                    FormatElement::TextGroup(textgroup) => collect(&textgroup.format, variables),
+                    FormatElement::Conditional(format) => collect(format, variables),
+                    FormatElement::Text(_) => {}
+                }
+            }
+        }
+
+        let mut variables = Vec::new();
+        collect(&self.format, &mut variables);
+        variables
+    }
+
     /// Parse the format string and consume self.
     ///
     /// This method will throw an Error in the following conditions:
@@ -394,6 +417,13 @@ mod tests {
         None
     }
 
+    #[test]
+    fn test_variables_in_order() {
+        const FORMAT_STR: &str = "$b[$c( $a)]($style)$b";
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap();
+        assert_eq!(formatter.get_variables_in_order(), vec!["b", "c", "a"]);
+    }
+
     #[test]
     fn test_default_style() {
         const FORMAT_STR: &str = "text";
@@ -0,0 +1,104 @@
+use super::string_formatter::StringFormatterError;
+use super::StringFormatter;
+use crate::context::Context;
+
+/// Formats versions of the language modules with a `version_format` like `v${major}.${minor}`
+///
+/// The available variables are `raw` (the version as reported by the tool) and, if the version
+/// has them, its `major`, `minor` and `patch` components.
+pub struct VersionFormatter<'a> {
+    formatter: StringFormatter<'a>,
+}
+
+impl<'a> VersionFormatter<'a> {
+    /// Creates an instance of a VersionFormatter from a format string
+    ///
+    /// Like the StringFormatter, this will throw an error when the string isn't parseable.
+    pub fn new(version_format: &'a str) -> Result<Self, StringFormatterError> {
+        let formatter = StringFormatter::new(version_format)?;
+
+        Ok(Self { formatter })
+    }
+
+    /// Formats a version structure into a readable string
+    pub fn format_version(self, version: &str) -> Result<String, StringFormatterError> {
+        let components: Vec<&str> = version.trim_start_matches('v').split('.').collect();
+
+        let formatted = self
+            .formatter
+            .map(|variable| match variable {
+                "raw" => Some(Ok(version)),
+                "major" => components.first().copied().map(Ok),
+                "minor" => components.get(1).copied().map(Ok),
+                "patch" => components.get(2).copied().map(Ok),
+                _ => None,
+            })
+            .parse(None);
+
+        formatted.map(|segments| {
+            segments
+                .iter()
+                .map(|segment| segment.value.as_str())
+                .collect::<String>()
+        })
+    }
+
+    /// Formats the version of a module with its `version_format`
+    ///
+    /// Falls back to the `version_format` of the root config if the module doesn't set one.
+    pub fn format_module_version(
+        context: &Context,
+        module_name: &str,
+        version: &str,
+        version_format: Option<&str>,
+    ) -> Option<String> {
+        let root_config = context.config.get_root_config();
+        let version_format = version_format.unwrap_or(root_config.version_format);
+
+        match VersionFormatter::new(version_format)
+            .and_then(|formatter| formatter.format_version(version))
+        {
+            Ok(formatted) => Some(formatted),
+            Err(error) => {
+                log::warn!("Error formatting `{}` version:\n{}", module_name, error);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERSION: &str = "3.2.1";
+
+    #[test]
+    fn test_default_is_ok() {
+        let result = VersionFormatter::new("v${major}.${minor}.${patch}")
+            .unwrap()
+            .format_version(VERSION);
+        assert_eq!(result.unwrap(), "v3.2.1");
+    }
+
+    #[test]
+    fn test_trim_patch() {
+        let result = VersionFormatter::new("v${major}.${minor}")
+            .unwrap()
+            .format_version(VERSION);
+        assert_eq!(result.unwrap(), "v3.2");
+    }
+
+    #[test]
+    fn test_raw_and_missing_components() {
+        let result = VersionFormatter::new("${raw}(-${patch})")
+            .unwrap()
+            .format_version("1.10");
+        assert_eq!(result.unwrap(), "1.10");
+    }
+
+    #[test]
+    fn test_invalid_format() {
+        assert!(VersionFormatter::new("v${major").is_err());
+    }
+}
@@ -117,10 +117,14 @@ pub fn init_stub(shell_name: &str) -> io::Result<()> {
              * More background can be found in these pull requests:
              * https://github.com/starship/starship/pull/241
              * https://github.com/starship/starship/pull/278
+             *
+             * The DEBUG trap isn't visible in a sourced script, so the
+             * existing trap is read here, for the init script to preserve it.
              */
             let script = {
                 format!(
-                    r#"if [ "${{BASH_VERSINFO[0]}}" -gt 4 ] || ([ "${{BASH_VERSINFO[0]}}" -eq 4 ] && [ "${{BASH_VERSINFO[1]}}" -ge 1 ])
+                    r#"STARSHIP_DEBUG_TRAP="$(trap -p DEBUG)"
+if [ "${{BASH_VERSINFO[0]}}" -gt 4 ] || ([ "${{BASH_VERSINFO[0]}}" -eq 4 ] && [ "${{BASH_VERSINFO[1]}}" -ge 1 ])
 then
 source <("{0}" init bash --print-full-init)
 else
@@ -245,3 +249,383 @@ const FISH_INIT: &str = include_str!("starship.fish");
 const PWSH_INIT: &str = include_str!("starship.ps1");
 
 const ION_INIT: &str = include_str!("starship.ion");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+
+    /// A stand-in for starship, which prints the arguments of `starship prompt`, one per line,
+    /// followed by the last command
+    const FAKE_STARSHIP_PS1: &str = "if ($args[0] -eq 'prompt') {
+        $args[1..($args.Count - 1)]
+        \"STARSHIP_LAST_COMMAND=$env:STARSHIP_LAST_COMMAND\"
+    }\n";
+
+    /// A stand-in for starship, which prints how many prompts it computed, followed by the
+    /// arguments of `starship prompt`, one per line
+    #[cfg(unix)]
+    const FAKE_STARSHIP_SH: &str = "#!/bin/sh
+case \"$1\" in
+    time) echo 1000 ;;
+    prompt)
+        shift
+        echo x >> \"$0.count\"
+        echo \"prompt $(wc -l < \"$0.count\" | tr -d ' ')\"
+        printf '%s\\n' \"$@\"
+        ;;
+esac
+";
+
+    /// Run `commands` in bash after the init script, with a fake starship
+    ///
+    /// PROMPT_COMMAND and the DEBUG trap of the init script run the same way as in an
+    /// interactive shell when `commands` calls `starship_precmd` in place of drawing a prompt.
+    #[cfg(unix)]
+    fn run_bash(before_init: &str, commands: &str) -> io::Result<String> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir()?;
+        let starship = dir.path().join("starship");
+        fs::write(&starship, FAKE_STARSHIP_SH)?;
+        fs::set_permissions(&starship, fs::Permissions::from_mode(0o755))?;
+        let init = dir.path().join("init.bash");
+        let script = BASH_INIT.replace("::STARSHIP::", &format!("\"{}\"", starship.display()));
+        fs::write(&init, script)?;
+
+        let output = Command::new("bash")
+            .args(&["--norc", "--noprofile", "-c"])
+            .arg(format!(
+                "{}\nsource \"{}\"\n{}",
+                before_init,
+                init.display(),
+                commands
+            ))
+            .output()?;
+        dir.close()?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Run `lines` in an interactive bash after the init script, with a fake starship
+    ///
+    /// Returns what bash drew on stderr, which includes the prompts and so the arguments of
+    /// each `prompt`. The last argument of a prompt runs into what's drawn after it, like the
+    /// next prompt or the `exit` at the end of the input.
+    #[cfg(unix)]
+    fn run_interactive_bash(lines: &str) -> io::Result<String> {
+        use std::io::Write;
+        use std::os::unix::fs::PermissionsExt;
+        use std::process::Stdio;
+
+        let dir = tempfile::tempdir()?;
+        let starship = dir.path().join("starship");
+        fs::write(&starship, FAKE_STARSHIP_SH)?;
+        fs::set_permissions(&starship, fs::Permissions::from_mode(0o755))?;
+        let init = dir.path().join("init.bash");
+        let script = BASH_INIT.replace("::STARSHIP::", &format!("\"{}\"", starship.display()));
+        fs::write(&init, script)?;
+
+        let mut bash = Command::new("bash")
+            .args(&["--norc", "--noprofile", "--noediting", "-i"])
+            .env("HISTFILE", "/dev/null")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = bash.stdin.take() {
+            write!(stdin, "source \"{}\"\n{}\n", init.display(), lines)?;
+        }
+        let output = bash.wait_with_output()?;
+        dir.close()?;
+        Ok(String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn bash_last_command_is_whole_line() -> io::Result<()> {
+        let output = run_interactive_bash(
+            "echo one | cat
+true && false
+
+HISTCONTROL=ignorespace
+ echo hidden | cat",
+        )?;
+        let next_prompt = regex::Regex::new(r"(prompt \d+|exit)$").unwrap();
+        let last_commands: Vec<String> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("--last-command="))
+            .map(|line| next_prompt.replace(line, "").into_owned())
+            .collect();
+        // The first prompt is the one drawn after the init script
+        assert_eq!(
+            last_commands[1..],
+            [
+                "echo one | cat",
+                "true && false",
+                "",
+                "HISTCONTROL=ignorespace",
+                // Lines left out of the history only give their first command
+                "echo hidden",
+            ],
+            "{}",
+            output
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn bash_preserved_debug_trap_sees_last_argument() -> io::Result<()> {
+        let output = run_bash(
+            r#"trap 'LAST_ARG_IN_TRAP=$_' DEBUG
+STARSHIP_DEBUG_TRAP="$(trap -p DEBUG)""#,
+            r#"starship_precmd
+: previous_argument
+echo "$LAST_ARG_IN_TRAP $_""#,
+        )?;
+        assert_eq!(output, "previous_argument previous_argument\n");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn bash_preexec_keeps_previous_last_argument() -> io::Result<()> {
+        // Calls the preexec functions from the DEBUG trap, the way bash-preexec does
+        let bash_preexec = r#"__bp_imported=defined
+__bp_preexec_invoke_exec() {
+    __bp_last_argument_prev_command=$1
+    [[ "$BASH_COMMAND" == starship_precmd ]] && return
+    local preexec_function
+    for preexec_function in "${preexec_functions[@]}"; do
+        "$preexec_function" "$BASH_COMMAND"
+    done
+}
+trap '__bp_preexec_invoke_exec "$_"' DEBUG"#;
+        let output = run_bash(
+            bash_preexec,
+            r#"preexec_functions=(record_last_argument "${preexec_functions[@]}")
+record_last_argument() { :; }
+starship_precmd
+: previous_argument
+echo "${preexec_functions[*]}"
+starship_precmd
+echo "$PS1""#,
+        )?;
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "record_last_argument starship_preexec_bp");
+        assert!(lines.contains(&"--cmd-duration=0"), "{}", output);
+        assert!(
+            lines.contains(&"--last-command=: previous_argument"),
+            "{}",
+            output
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn bash_preexec_gives_command_line() -> io::Result<()> {
+        // bash-preexec gives the preexec functions the command line from the history
+        let bash_preexec = r#"__bp_imported=defined
+__bp_preexec_invoke_exec() {
+    __bp_last_argument_prev_command=$1
+    [[ "$BASH_COMMAND" == starship_precmd ]] && return
+    local preexec_function
+    for preexec_function in "${preexec_functions[@]}"; do
+        "$preexec_function" "echo one | cat"
+    done
+}
+trap '__bp_preexec_invoke_exec "$_"' DEBUG"#;
+        let output = run_bash(
+            bash_preexec,
+            r#"starship_precmd
+echo one | cat
+starship_precmd
+echo "$PS1""#,
+        )?;
+        assert!(
+            output
+                .lines()
+                .any(|line| line == "--last-command=echo one | cat"),
+            "{}",
+            output
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn bash_reuse_prompt_on_empty_line() -> io::Result<()> {
+        let output = run_bash(
+            "",
+            r#"STARSHIP_REUSE_PROMPT=1
+starship_precmd
+starship_precmd
+echo "$PS1""#,
+        )?;
+        assert_eq!(output.lines().next(), Some("prompt 1"), "{}", output);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn bash_reuse_prompt_after_command() -> io::Result<()> {
+        let output = run_bash(
+            "",
+            r#"STARSHIP_REUSE_PROMPT=1
+starship_precmd
+: command
+starship_precmd
+echo "$PS1""#,
+        )?;
+        assert_eq!(output.lines().next(), Some("prompt 2"), "{}", output);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn bash_reuse_prompt_after_resize() -> io::Result<()> {
+        // The preserved PROMPT_COMMAND is run for every prompt, without running a command
+        let output = run_bash(
+            "COLUMNS=80 PROMPT_COMMAND='COLUMNS=$((COLUMNS + 1))'",
+            r#"STARSHIP_REUSE_PROMPT=1
+starship_precmd
+starship_precmd
+echo "$PS1""#,
+        )?;
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "prompt 2", "{}", output);
+        assert!(lines.contains(&"--terminal-width=82"), "{}", output);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn bash_no_reuse_by_default() -> io::Result<()> {
+        let output = run_bash(
+            "",
+            r#"starship_precmd
+starship_precmd
+echo "$PS1""#,
+        )?;
+        assert_eq!(output.lines().next(), Some("prompt 2"), "{}", output);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn bash_right_prompt_opt_in() -> io::Result<()> {
+        let output = run_bash(
+            "",
+            r#"starship_precmd
+echo "$PS1"
+STARSHIP_BASH_RIGHT_PROMPT=1
+starship_precmd
+echo "$PS1""#,
+        )?;
+        let prompts: Vec<&str> = output.split("prompt ").collect();
+        assert!(!prompts[1].contains("--with-right"), "{}", output);
+        assert!(prompts[2].contains("\n--with-right\n"), "{}", output);
+        Ok(())
+    }
+
+    /// Run `commands` in PowerShell after the init script, with a fake starship
+    ///
+    /// Returns the output, which includes the arguments of each `prompt`.
+    fn run_pwsh(commands: &str) -> io::Result<String> {
+        let dir = tempfile::tempdir()?;
+        let starship = dir.path().join("starship.ps1");
+        fs::write(&starship, FAKE_STARSHIP_PS1)?;
+        let init = dir.path().join("init.ps1");
+        let script = PWSH_INIT.replace("::STARSHIP::", &format!("\"{}\"", starship.display()));
+        fs::write(&init, script)?;
+        let test = dir.path().join("test.ps1");
+        let helpers = "function Add-TestHistory($commandLine) {
+            $now = Get-Date
+            [PSCustomObject]@{
+                CommandLine = $commandLine
+                ExecutionStatus = 'Completed'
+                StartExecutionTime = $now.AddSeconds(-3)
+                EndExecutionTime = $now
+            } | Add-History
+        }";
+        fs::write(
+            &test,
+            format!(". \"{}\"\n{}\n{}\n", init.display(), helpers, commands),
+        )?;
+
+        let output = Command::new("pwsh")
+            .args(&["-NoProfile", "-NonInteractive", "-File"])
+            .arg(&test)
+            .output()?;
+        dir.close()?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    #[test]
+    #[ignore]
+    fn pwsh_native_command_status() -> io::Result<()> {
+        let output = run_pwsh(
+            "Add-TestHistory 'pwsh -NoProfile -Command exit 3'
+            $global:LASTEXITCODE = 3
+            Write-Error '' -ErrorAction Ignore
+            prompt",
+        )?;
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines.contains(&"--status=3"), "{}", output);
+        assert!(
+            !lines.iter().any(|line| line.starts_with("--pipestatus")),
+            "{}",
+            output
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn pwsh_cmdlet_failure_ignores_stale_exit_code() -> io::Result<()> {
+        let output = run_pwsh(
+            "Add-TestHistory 'Get-Item missing -ErrorAction Ignore'
+            $global:LASTEXITCODE = 3
+            Write-Error '' -ErrorAction Ignore
+            prompt",
+        )?;
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines.contains(&"--status=1"), "{}", output);
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn pwsh_success_after_native_failure() -> io::Result<()> {
+        let output = run_pwsh(
+            "Add-TestHistory 'Get-Item .'
+            $global:LASTEXITCODE = 3
+            prompt",
+        )?;
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines.contains(&"--status=0"), "{}", output);
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn pwsh_last_command_with_quotes() -> io::Result<()> {
+        let output = run_pwsh(
+            "Add-TestHistory 'git commit -m \"a \"\"quoted\"\" message\"'
+            prompt
+            Add-TestHistory 'Get-Item .'
+            $env:STARSHIP_LAST_COMMAND -eq $null",
+        )?;
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(
+            lines.contains(&r#"STARSHIP_LAST_COMMAND=git commit -m "a ""quoted"" message""#),
+            "{}",
+            output
+        );
+        // The last command isn't left in the environment of other commands
+        assert_eq!(lines.last(), Some(&"True"), "{}", output);
+        Ok(())
+    }
+}
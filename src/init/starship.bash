@@ -13,6 +13,7 @@
 # for the entire command, and not just a portion of it.
 
 # Will be run before *every* command (even ones in pipes!)
+# The command line is given as the second argument by bash-preexec.
 starship_preexec() {
     # Save previous command's last argument, otherwise it will be set to "starship_preexec"
     local PREV_LAST_ARG=$1
@@ -21,70 +22,141 @@ starship_preexec() {
     if [ "$STARSHIP_PREEXEC_READY" = "true" ]; then
         STARSHIP_PREEXEC_READY=false
         STARSHIP_START_TIME=$(::STARSHIP:: time)
+        if [[ -n "${2-}" ]]; then
+            STARSHIP_LAST_COMMAND=$2 STARSHIP_COMMAND_RAN=1
+        # The trap also runs for PROMPT_COMMAND when Enter is pressed on an empty line
+        elif [[ "$BASH_COMMAND" == "starship_precmd" ]]; then
+            STARSHIP_LAST_COMMAND=
+        else
+            _starship_set_last_command
+            STARSHIP_COMMAND_RAN=1
+        fi
     fi
 
     : "$PREV_LAST_ARG"
 }
 
+# Sets the command line being run from the history. $BASH_COMMAND is only its first simple
+# command, so it's the fallback when the line wasn't added to the history (e.g. with HISTCONTROL),
+# which is when the last entry isn't the one numbered $HISTCMD at the prompt.
+_starship_set_last_command() {
+    local number line
+    if [[ -o history ]]; then
+        read -r number line <<< "$(HISTTIMEFORMAT= builtin history 1)"
+    fi
+    if [[ -n "$number" && "$number" == "${STARSHIP_HISTCMD-}" ]]; then
+        STARSHIP_LAST_COMMAND=$line
+    else
+        STARSHIP_LAST_COMMAND=$BASH_COMMAND
+    fi
+}
+
 # Will be run before the prompt is drawn
 starship_precmd() {
     # Save the status, because commands in this pipeline will change $?
-    STARSHIP_CMD_STATUS=$?
+    STARSHIP_CMD_STATUS=$? STARSHIP_PIPE_STATUS=(${PIPESTATUS[@]})
+    # The number of the history entry of the next command line
+    STARSHIP_HISTCMD=$HISTCMD
 
     local NUM_JOBS
     # Evaluate the number of jobs before running the preseved prompt command, so that tools
     # like z/autojump, which background certain jobs, do not cause spurious background jobs
     # to be displayed by starship. Also avoids forking to run `wc`, slightly improving perf
     NUM_JOBS=$(n=0; while read line; do [[ $line ]] && n=$((n+1));done <<< $(jobs -p) ; echo $n)
+    NUM_STOPPED_JOBS=$(n=0; while read line; do [[ $line ]] && n=$((n+1));done <<< $(jobs -sp) ; echo $n)
 
     # Run the bash precmd function, if it's set. If not set, evaluates to no-op
     "${starship_precmd_user_func-:}"
 
-    eval "$_PRESERVED_PROMPT_COMMAND"
+    # Run the PROMPT_COMMAND that was set before starship, each entry seeing the status of the
+    # last command as it would without starship (e.g. for the iTerm2 shell integration)
+    local cmd
+    for cmd in "${_PRESERVED_PROMPT_COMMAND[@]}"; do
+        _starship_set_return "$STARSHIP_CMD_STATUS"
+        eval "$cmd"
+    done
+
+    # If no command was run since the last prompt, reuse it when STARSHIP_REUSE_PROMPT is set
+    local prompt_key="$PWD $COLUMNS $STARSHIP_CMD_STATUS"
+    if [[ -n "${STARSHIP_REUSE_PROMPT-}" && -z "${STARSHIP_COMMAND_RAN-}" && "${STARSHIP_PROMPT_KEY-}" == "$prompt_key" ]]; then
+        PS1="$STARSHIP_LAST_PS1"
+        unset STARSHIP_START_TIME
+        STARSHIP_PREEXEC_READY=true
+        return
+    fi
 
     # Prepare the timer data, if needed.
     if [[ $STARSHIP_START_TIME ]]; then
         STARSHIP_END_TIME=$(::STARSHIP:: time)
         STARSHIP_DURATION=$((STARSHIP_END_TIME - STARSHIP_START_TIME))
-        PS1="$(::STARSHIP:: prompt --status=$STARSHIP_CMD_STATUS --jobs="$NUM_JOBS" --cmd-duration=$STARSHIP_DURATION)"
+        PS1="$(::STARSHIP:: prompt ${STARSHIP_BASH_RIGHT_PROMPT:+--with-right} --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="${STARSHIP_PIPE_STATUS[*]}" --jobs="$NUM_JOBS" --stopped-jobs="$NUM_STOPPED_JOBS" --cmd-duration=$STARSHIP_DURATION --last-command="$STARSHIP_LAST_COMMAND")"
         unset STARSHIP_START_TIME
     else
-        PS1="$(::STARSHIP:: prompt --status=$STARSHIP_CMD_STATUS --jobs="$NUM_JOBS")"
+        PS1="$(::STARSHIP:: prompt ${STARSHIP_BASH_RIGHT_PROMPT:+--with-right} --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="${STARSHIP_PIPE_STATUS[*]}" --jobs="$NUM_JOBS" --stopped-jobs="$NUM_STOPPED_JOBS")"
     fi
+    STARSHIP_LAST_PS1="$PS1" STARSHIP_PROMPT_KEY="$prompt_key"
+    unset STARSHIP_COMMAND_RAN
     STARSHIP_PREEXEC_READY=true  # Signal that we can safely restart the timer
 }
 
+_starship_set_return() {
+    return "${1:-0}"
+}
+
+# Runs the DEBUG trap that was set before starship, and then starship_preexec
+starship_preexec_all() {
+    local PREV_LAST_ARG=$1
+    if [[ -n "$_PRESERVED_DEBUG_TRAP" ]]; then
+        : "$PREV_LAST_ARG"
+        eval -- "$_PRESERVED_DEBUG_TRAP"
+    fi
+    starship_preexec "$PREV_LAST_ARG"
+}
+
+# Runs starship_preexec for bash-preexec, which passes the command line instead of the last
+# argument of the previous command. It captures that argument before running any command.
+starship_preexec_bp() {
+    starship_preexec "${__bp_last_argument_prev_command-}" "$1"
+}
+
 # If the user appears to be using https://github.com/rcaloras/bash-preexec,
 # then hook our functions into their framework.
 if [[ "${__bp_imported:-}" == "defined" || $preexec_functions || $precmd_functions ]]; then
-    # bash-preexec needs a single function--wrap the args into a closure and pass
-    starship_preexec_all(){ starship_preexec "$_"; }
-    preexec_functions+=(starship_preexec_all)
-    precmd_functions+=(starship_precmd)
+    # bash-preexec runs the DEBUG trap and PROMPT_COMMAND that were set before it,
+    # so starship only adds its functions, once
+    if [[ " ${preexec_functions[*]} " != *" starship_preexec_bp "* ]]; then
+        preexec_functions+=(starship_preexec_bp)
+    fi
+    if [[ " ${precmd_functions[*]} " != *" starship_precmd "* ]]; then
+        precmd_functions+=(starship_precmd)
+    fi
 else
-    # We want to avoid destroying an existing DEBUG hook. If we detect one, create
-    # a new function that runs both the existing function AND our function, then
-    # re-trap DEBUG to use this new function. This prevents a trap clobber.
-    dbg_trap="$(trap -p DEBUG | cut -d' ' -f3 | tr -d \')"
+    # We want to avoid destroying an existing DEBUG trap. If we detect one, preserve it
+    # so that starship_preexec_all runs both the existing trap AND our function.
+    # `trap -p` prints `trap -- 'command' DEBUG`, with the command quoted for the shell.
+    # The trap isn't visible in this sourced script, so `starship init bash` reads it before.
+    dbg_trap="${STARSHIP_DEBUG_TRAP-$(trap -p DEBUG)}"
+    unset STARSHIP_DEBUG_TRAP
+    dbg_trap="${dbg_trap#trap -- }"
+    eval "dbg_trap=${dbg_trap% DEBUG}"
     if [[ -z "$dbg_trap" ]]; then
         trap 'starship_preexec "$_"' DEBUG
     elif [[ "$dbg_trap" != 'starship_preexec "$_"' && "$dbg_trap" != 'starship_preexec_all "$_"' ]]; then
-        starship_preexec_all() {
-            local PREV_LAST_ARG=$1 ; $dbg_trap; starship_preexec; : "$PREV_LAST_ARG";
-        }
+        _PRESERVED_DEBUG_TRAP="$dbg_trap"
         trap 'starship_preexec_all "$_"' DEBUG
     fi
+    unset dbg_trap
 
     # Finally, prepare the precmd function and set up the start time. We will avoid to
     # add multiple instances of the starship function and keep other user functions if any.
-    if [[ -z "$PROMPT_COMMAND" ]]; then
-        PROMPT_COMMAND="starship_precmd"
-    elif [[ "$PROMPT_COMMAND" != *"starship_precmd"* ]]; then
+    # PROMPT_COMMAND may also be an array since bash 5.1, where every entry is run.
+    if [[ "${PROMPT_COMMAND[*]}" != *"starship_precmd"* ]]; then
         # Appending to PROMPT_COMMAND breaks exit status ($?) checking.
         # Prepending to PROMPT_COMMAND breaks "command duration" module.
         # So, we are preserving the existing PROMPT_COMMAND
         # which will be executed later in the starship_precmd function
-        _PRESERVED_PROMPT_COMMAND="$PROMPT_COMMAND"
+        _PRESERVED_PROMPT_COMMAND=("${PROMPT_COMMAND[@]}")
+        unset PROMPT_COMMAND
         PROMPT_COMMAND="starship_precmd"
     fi
 fi
@@ -1,4 +1,6 @@
 function fish_prompt
+    # Save the pipestatus first, since any command will overwrite it
+    set -l starship_pipestatus $pipestatus
     switch "$fish_key_bindings"
         case fish_hybrid_key_bindings fish_vi_key_bindings
             set keymap "$fish_bind_mode"
@@ -8,7 +10,53 @@ function fish_prompt
     set -l exit_code $status
     # Account for changes in variable name between v2.7 and v3.0
     set -l starship_duration "$CMD_DURATION$cmd_duration"
-    ::STARSHIP:: prompt --status=$exit_code --keymap=$keymap --cmd-duration=$starship_duration --jobs=(count (jobs -p))
+    # Collapse the prompt of the command about to run, see enable_transience
+    set -l transient
+    if test "$STARSHIP_TRANSIENT" = 1
+        set -g STARSHIP_TRANSIENT 0
+        set transient --transient
+    end
+    set -l prompt_args $transient --terminal-width="$COLUMNS" --status=$exit_code --pipestatus="$starship_pipestatus" --keymap=$keymap --cmd-duration=$starship_duration --last-command="$history[1]" --jobs=(count (jobs -p))
+    if not set -q STARSHIP_REUSE_PROMPT; or test -n "$transient"
+        ::STARSHIP:: prompt $prompt_args
+        return
+    end
+    # Reuse the last prompt if no command was run since, and nothing it depends on changed.
+    # Unlike in bash and zsh, fish_prompt also draws the prompt when the keymap changes.
+    set -l prompt_key "$PWD $COLUMNS $exit_code $keymap"
+    if set -q STARSHIP_COMMAND_RAN; or test "$prompt_key" != "$STARSHIP_PROMPT_KEY"
+        set -g STARSHIP_LAST_PROMPT (::STARSHIP:: prompt $prompt_args)
+        set -g STARSHIP_PROMPT_KEY $prompt_key
+        set -e STARSHIP_COMMAND_RAN
+    end
+    string join \n -- $STARSHIP_LAST_PROMPT
+end
+
+# fish_preexec isn't emitted when Enter is pressed on an empty line
+function starship_command_ran --on-event fish_preexec
+    set -g STARSHIP_COMMAND_RAN 1
+end
+
+# Repaints the prompt as the `transient_format` before running the command line
+function starship_transient_execute
+    # Leave the prompt of an unfinished command alone, since a new line is only added to it
+    set -l command_line (commandline)
+    if test -z "$command_line"; or commandline --is-valid
+        set -g STARSHIP_TRANSIENT 1
+        commandline -f repaint
+    end
+    commandline -f execute
+end
+
+# Replaces the prompts of the commands that were run with the `transient_format`
+function enable_transience
+    bind \r starship_transient_execute
+    bind -M insert \r starship_transient_execute
+end
+
+function disable_transience
+    bind \r execute
+    bind -M insert \r execute
 end
 
 # disable virtualenv prompt, it breaks starship
@@ -10,6 +10,7 @@ function global:prompt {
 
     $env:PWD = $PWD
     $current_directory = (Convert-Path -LiteralPath $PWD)
+    $terminal_width = $Host.UI.RawUI.WindowSize.Width
     
     # Whe start from the premise that the command executed correctly, which covers also the fresh console.
     $lastExitCodeForPrompt = 0
@@ -17,24 +18,55 @@ function global:prompt {
     # Save old output encoding and set it to UTF-8
     $origOutputEncoding = [Console]::OutputEncoding
     [Console]::OutputEncoding = [System.Text.Encoding]::UTF8
+    # The most recent error, to tell whether the last command line added one
+    $lastError = if ($global:Error.Count -gt 0) { $global:Error[0] } else { $null }
     if ($lastCmd = Get-History -Count 1) {
         # In case we have a False on the Dollar hook, we know there's an error.
         if (-not $origDollarQuestion) {
-            # We retrieve the InvocationInfo from the most recent error.
-            $lastCmdletError = try { Get-Error |  Where-Object { $_ -ne $null } | Select-Object -expand InvocationInfo } catch { $null }
-            # We check if the las command executed matches the line that caused the last error , in which case we know
-            # it was an internal Powershell command, otherwise, there MUST be an error code.
-            $lastExitCodeForPrompt = if ($null -ne $lastCmdletError -and $lastCmd.CommandLine -eq $lastCmdletError.Line) { 1 } else { $origLastExitCode }
+            # A cmdlet that failed added an error since the last prompt, unless it was run with
+            # -ErrorAction Ignore. $Error is capped, so errors are compared rather than counted.
+            $isNewError = $null -ne $lastError -and -not [object]::ReferenceEquals($lastError, $global:_starshipLastError)
+            # Native commands only add an error with $PSNativeCommandUseErrorActionPreference,
+            # and then the exit code is still in $LASTEXITCODE
+            $isNativeError = $isNewError -and $lastError.Exception.GetType().Name -eq 'NativeCommandExitException'
+            # Only native commands set $LASTEXITCODE, so it's stale unless the last command of the
+            # command line ran one. The first command found by that name is the one which ran.
+            $lastCommandAst = [System.Management.Automation.Language.Parser]::ParseInput($lastCmd.CommandLine, [ref]$null, [ref]$null).FindAll({ $args[0] -is [System.Management.Automation.Language.CommandAst] }, $true) | Select-Object -Last 1
+            $lastCommandName = if ($lastCommandAst) { $lastCommandAst.GetCommandName() }
+            $isNativeCommand = $lastCommandName -and (Get-Command -Name $lastCommandName -ErrorAction Ignore | Select-Object -First 1).CommandType -eq 'Application'
+            $lastExitCodeForPrompt = if ($isNewError -and -not $isNativeError) {
+                1
+            } elseif ($isNativeCommand -and $origLastExitCode) {
+                # A native command failed, with its exit code in $LASTEXITCODE
+                $origLastExitCode
+            } else {
+                # A cmdlet failed without an error, e.g. with -ErrorAction Ignore
+                1
+            }
         }
+    }
 
-        $duration = [math]::Round(($lastCmd.EndExecutionTime - $lastCmd.StartExecutionTime).TotalMilliseconds)
-        # & ensures the path is interpreted as something to execute
-        $out = @(&::STARSHIP:: prompt "--path=$current_directory" --status=$lastExitCodeForPrompt --jobs=$jobs --cmd-duration=$duration)
-    } else {
-        $out = @(&::STARSHIP:: prompt "--path=$current_directory" --status=$lastExitCodeForPrompt --jobs=$jobs)
+    # PowerShell has no pipeline status, only the status of the last command of the command line
+    $arguments = @("--path=$current_directory", "--status=$lastExitCodeForPrompt", "--jobs=$jobs", "--terminal-width=$terminal_width")
+    if ($lastCmd) {
+        # Windows PowerShell doesn't escape quotes in the arguments of native commands, so the
+        # command line is passed in the environment instead
+        $env:STARSHIP_LAST_COMMAND = $lastCmd.CommandLine
+        # The history has when the command started and ended, so the duration doesn't include
+        # the time taken by the prompt itself. An empty command line isn't added to the
+        # history, so the duration is only shown at the first prompt after the command.
+        if ($lastCmd.Id -ne $global:_starshipLastHistoryId) {
+            $duration = [math]::Round(($lastCmd.EndExecutionTime - $lastCmd.StartExecutionTime).TotalMilliseconds)
+            $arguments += "--cmd-duration=$duration"
+            $global:_starshipLastHistoryId = $lastCmd.Id
+        }
     }
+    # & ensures the path is interpreted as something to execute
+    $out = @(&::STARSHIP:: prompt @arguments)
+    Remove-Item -Path Env:STARSHIP_LAST_COMMAND -ErrorAction Ignore
     # Restore old output encoding
     [Console]::OutputEncoding = $origOutputEncoding
+    $global:_starshipLastError = $lastError
 
     # Convert stdout (array of lines) to expected return type string
     # `n is an escaped newline
@@ -68,5 +100,10 @@ $ENV:VIRTUAL_ENV_DISABLE_PROMPT=1
 
 $ENV:STARSHIP_SHELL = "powershell"
 
+# The errors from before starship started aren't from the commands of the prompts
+$global:_starshipLastError = if ($global:Error.Count -gt 0) { $global:Error[0] } else { $null }
+# Likewise for the commands of the history, whose duration is shown once
+$global:_starshipLastHistoryId = (Get-History -Count 1).Id
+
 # Set up the session key that will be used to store logs
 $ENV:STARSHIP_SESSION_KEY = -join ((48..57) + (65..90) + (97..122) | Get-Random -Count 16 | ForEach-Object { [char]$_ })
@@ -1,5 +1,48 @@
-// Lib is present to allow for benchmarking
+//! Starship renders its prompt as a library too, so that other tools like terminals or shells
+//! can embed it instead of running `starship prompt`.
+//!
+//! A prompt is rendered in three steps:
+//!
+//! 1. Load the configuration, with [`StarshipConfig::initialize`] for the user's
+//!    `starship.toml`, or from a TOML value of your own.
+//! 2. Build a [`Context`] for a directory with [`Context::new_with_properties`], giving it
+//!    what the shell would otherwise pass on to `starship prompt`.
+//! 3. Render the whole prompt with [`print::get_prompt`], a single module with
+//!    [`print::get_module`], or compute the [`Module`]s and their styled [`Segment`]s with
+//!    [`print::compute_modules`] to draw them yourself.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use starship::config::StarshipConfig;
+//! use starship::context::{Context, Shell};
+//!
+//! // The user's config would be `StarshipConfig::initialize()`
+//! let config = StarshipConfig {
+//!     config: Some(toml::toml! {
+//!         format = "$character"
+//!         [character]
+//!         error_symbol = "[x](bold red)"
+//!     }),
+//! };
+//! let mut properties = HashMap::new();
+//! properties.insert("status_code", "1".to_string());
+//! properties.insert("terminal_width", "120".to_string());
+//!
+//! let dir = std::env::temp_dir();
+//! let context = Context::new_with_properties(config, dir, Shell::Unknown, properties);
+//! let modules = starship::print::compute_modules(&context);
+//! assert_eq!(modules[0].get_name(), "character");
+//! assert_eq!(modules[0].segments[0].value, "x");
+//! ```
+//!
+//! [`StarshipConfig::initialize`]: config::StarshipConfig::initialize
+//! [`Context`]: context::Context
+//! [`Context::new_with_properties`]: context::Context::new_with_properties
+//! [`Module`]: module::Module
+//! [`Segment`]: segment::Segment
+
 pub mod bug_report;
+pub mod completions;
 pub mod config;
 pub mod configs;
 pub mod configure;
@@ -8,9 +51,13 @@ pub mod formatter;
 pub mod init;
 pub mod logger;
 pub mod module;
+mod module_cache;
 mod modules;
+mod palette;
 pub mod print;
-mod segment;
+pub mod segment;
+mod symbol_set;
+pub mod trace;
 mod utils;
 
 #[cfg(test)]
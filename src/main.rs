@@ -21,6 +21,19 @@ fn main() {
         .help("The status code of the previously run command")
         .takes_value(true);
 
+    let pipestatus_arg = Arg::with_name("pipestatus")
+        .long("pipestatus")
+        .value_name("PIPESTATUS")
+        .help("Status codes from a command pipeline, separated by whitespace")
+        .takes_value(true);
+
+    let last_command_arg = Arg::with_name("last_command")
+        .long("last-command")
+        .value_name("LAST_COMMAND")
+        .help("The command line of the last command that was run")
+        .takes_value(true)
+        .allow_hyphen_values(true);
+
     let path_arg = Arg::with_name("path")
         .short("p")
         .long("path")
@@ -57,10 +70,74 @@ fn main() {
         .help("The number of currently running jobs")
         .takes_value(true);
 
+    let stopped_jobs_arg = Arg::with_name("stopped_jobs")
+        .long("stopped-jobs")
+        .value_name("STOPPED_JOBS")
+        // bash/zsh only
+        .help("How many of the jobs are stopped")
+        .takes_value(true);
+
+    let terminal_width_arg = Arg::with_name("terminal_width")
+        .short("w")
+        .long("terminal-width")
+        .value_name("TERMINAL_WIDTH")
+        .help("The width of the current interactive terminal.")
+        .takes_value(true);
+
+    let trace_file_arg = Arg::with_name("trace_file")
+        .long("trace-file")
+        .value_name("FILE")
+        .help("Write a Chrome trace of the time spent on the prompt to a file")
+        .takes_value(true);
+
+    let output_format_arg = Arg::with_name("output_format")
+        .long("format")
+        .value_name("FORMAT")
+        .help("Print the prompt as ANSI text for the shell, as HTML, or its modules as JSON")
+        .possible_values(&["ansi", "html", "json"])
+        .default_value("ansi")
+        .takes_value(true);
+
+    let color_arg = Arg::with_name("color")
+        .long("color")
+        .value_name("WHEN")
+        .help("Whether to color the output, by default unless NO_COLOR is set or TERM is dumb")
+        .possible_values(&["always", "auto", "never"])
+        .default_value("auto")
+        .takes_value(true);
+
+    let async_arg = Arg::with_name("async")
+        .long("async")
+        .value_name("PHASE")
+        .help("Print the placeholder or the final prompt of the async mode of zsh")
+        .possible_values(&["placeholder", "final"])
+        .takes_value(true);
+
+    let transient_arg = Arg::with_name("transient")
+        .long("transient")
+        .help("Print the prompt of `transient_format`, which replaces the prompt of a run command");
+
+    let right_arg = Arg::with_name("right")
+        .long("right")
+        .help("Print the prompt of `right_format`, to draw on the right of the terminal")
+        .conflicts_with("transient");
+
+    let with_right_arg = Arg::with_name("with_right")
+        .long("with-right")
+        // bash only
+        .help("Draw the prompt of `right_format` on the last line of the prompt")
+        .conflicts_with_all(&["transient", "right"]);
+
     let init_scripts_arg = Arg::with_name("print_full_init")
         .long("print-full-init")
         .help("Print the main initialization script (as opposed to the init stub)");
 
+    let shells: Vec<&str> = Shell::variants()
+        .iter()
+        .chain(completions::EXTRA_SHELLS.iter())
+        .copied()
+        .collect();
+
     let mut app = App::new("starship")
         .about("The cross-shell prompt for astronauts. ☄🌌️")
         // pull the version number from Cargo.toml
@@ -79,10 +156,21 @@ fn main() {
             SubCommand::with_name("prompt")
                 .about("Prints the full starship prompt")
                 .arg(&status_code_arg)
+                .arg(&pipestatus_arg)
+                .arg(&last_command_arg)
                 .arg(&path_arg)
                 .arg(&cmd_duration_arg)
                 .arg(&keymap_arg)
-                .arg(&jobs_arg),
+                .arg(&jobs_arg)
+                .arg(&stopped_jobs_arg)
+                .arg(&terminal_width_arg)
+                .arg(&trace_file_arg)
+                .arg(&output_format_arg)
+                .arg(&color_arg)
+                .arg(&async_arg)
+                .arg(&transient_arg)
+                .arg(&right_arg)
+                .arg(&with_right_arg),
         )
         .subcommand(
             SubCommand::with_name("module")
@@ -100,10 +188,15 @@ fn main() {
                         .help("List out all supported modules"),
                 )
                 .arg(&status_code_arg)
+                .arg(&pipestatus_arg)
+                .arg(&last_command_arg)
                 .arg(&path_arg)
                 .arg(&cmd_duration_arg)
                 .arg(&keymap_arg)
-                .arg(&jobs_arg),
+                .arg(&jobs_arg)
+                .arg(&stopped_jobs_arg)
+                .arg(&terminal_width_arg)
+                .arg(&color_arg),
         )
         .subcommand(
             SubCommand::with_name("config")
@@ -143,7 +236,29 @@ fn main() {
                 .settings(&[AppSettings::Hidden]),
         )
         .subcommand(
-            SubCommand::with_name("explain").about("Explains the currently showing modules"),
+            SubCommand::with_name("explain")
+                .about("Explains the currently showing modules")
+                .arg(
+                    Arg::with_name("output_format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help("Print the explanation as text, or as JSON for other programs")
+                        .possible_values(&["text", "json"])
+                        .default_value("text")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("tmux")
+                .about("Prints modules styled for the tmux status line")
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help("The modules to print, instead of the `format` in [tmux]")
+                        .takes_value(true),
+                )
+                .arg(&path_arg),
         )
         .subcommand(SubCommand::with_name("timings").about("Prints timings of all active modules"))
         .subcommand(
@@ -152,7 +267,7 @@ fn main() {
                 .arg(
                     Arg::with_name("shell")
                         .takes_value(true)
-                        .possible_values(&Shell::variants())
+                        .possible_values(&shells)
                         .help("the shell to generate completions for")
                         .value_name("SHELL")
                         .required(true)
@@ -215,14 +330,17 @@ fn main() {
         }
         ("explain", Some(sub_m)) => print::explain(sub_m.clone()),
         ("timings", Some(sub_m)) => print::timings(sub_m.clone()),
+        ("tmux", Some(sub_m)) => print::tmux(sub_m.clone()),
         ("completions", Some(sub_m)) => {
-            let shell: Shell = sub_m
-                .value_of("shell")
-                .expect("Shell name missing.")
-                .parse()
-                .expect("Invalid shell");
-
-            app.gen_completions_to("starship", shell, &mut io::stdout().lock());
+            let shell = sub_m.value_of("shell").expect("Shell name missing.");
+            match shell {
+                "nushell" => print!("{}", completions::nushell(&app)),
+                "fig" => print!("{}", completions::fig(&app)),
+                shell => {
+                    let shell: Shell = shell.parse().expect("Invalid shell");
+                    app.gen_completions_to("starship", shell, &mut io::stdout().lock());
+                }
+            }
         }
         ("session", _) => println!(
             "{}",
@@ -9,58 +9,96 @@ use std::time::Duration;
 // Keep these ordered alphabetically.
 // Default ordering is handled in configs/starship_root.rs
 pub const ALL_MODULES: &[&str] = &[
+    #[cfg(feature = "languages")]
+    "asdf",
+    #[cfg(feature = "cloud")]
     "aws",
     #[cfg(feature = "battery")]
     "battery",
     "character",
+    #[cfg(feature = "languages")]
     "cmake",
     "cmd_duration",
+    #[cfg(feature = "languages")]
     "conda",
+    #[cfg(feature = "languages")]
     "dart",
     "directory",
+    #[cfg(feature = "cloud")]
     "docker_context",
+    #[cfg(feature = "languages")]
     "dotnet",
+    #[cfg(feature = "languages")]
     "elixir",
+    #[cfg(feature = "languages")]
     "elm",
+    #[cfg(feature = "languages")]
     "erlang",
     "env_var",
+    #[cfg(feature = "cloud")]
     "gcloud",
     "git_branch",
     "git_commit",
     "git_state",
     "git_status",
+    #[cfg(feature = "languages")]
     "golang",
+    #[cfg(feature = "cloud")]
     "helm",
+    #[cfg(feature = "hg")]
     "hg_branch",
     "hostname",
+    #[cfg(feature = "languages")]
     "java",
     "jobs",
+    #[cfg(feature = "languages")]
     "julia",
+    #[cfg(feature = "languages")]
     "kotlin",
+    #[cfg(feature = "cloud")]
     "kubernetes",
     "line_break",
+    #[cfg(feature = "languages")]
     "lua",
     "memory_usage",
+    #[cfg(feature = "languages")]
     "nim",
     "nix_shell",
+    #[cfg(feature = "languages")]
     "nodejs",
+    #[cfg(feature = "languages")]
     "ocaml",
+    #[cfg(feature = "cloud")]
     "openstack",
+    #[cfg(feature = "languages")]
     "package",
+    #[cfg(feature = "languages")]
     "perl",
+    #[cfg(feature = "cloud")]
+    "podman",
+    #[cfg(feature = "languages")]
     "purescript",
+    #[cfg(feature = "languages")]
     "python",
+    #[cfg(feature = "languages")]
     "ruby",
+    #[cfg(feature = "languages")]
     "crystal",
+    #[cfg(feature = "languages")]
     "rust",
+    #[cfg(feature = "languages")]
     "php",
+    #[cfg(feature = "languages")]
     "swift",
+    #[cfg(feature = "cloud")]
     "terraform",
     "shlvl",
+    #[cfg(feature = "cloud")]
     "singularity",
     "status",
     "time",
     "username",
+    #[cfg(feature = "languages")]
     "zig",
 ];
 
@@ -81,6 +119,9 @@ pub struct Module<'a> {
 
     /// the time it took to compute this module
     pub duration: Duration,
+
+    /// What the module detected in the directory to be shown, e.g. `file package.json`
+    pub detected_by: Option<String>,
 }
 
 impl<'a> Module<'a> {
@@ -92,6 +133,7 @@ impl<'a> Module<'a> {
             description: desc.to_string(),
             segments: Vec::new(),
             duration: Duration::default(),
+            detected_by: None,
         }
     }
 
@@ -178,6 +220,7 @@ mod tests {
             description: desc.to_string(),
             segments: Vec::new(),
             duration: Duration::default(),
+            detected_by: None,
         };
 
         assert!(module.is_empty());
@@ -193,6 +236,7 @@ mod tests {
             description: desc.to_string(),
             segments: vec![Segment::new(None, "")],
             duration: Duration::default(),
+            detected_by: None,
         };
 
         assert!(module.is_empty());
@@ -208,6 +252,7 @@ mod tests {
             description: desc.to_string(),
             segments: vec![Segment::new(None, "\n")],
             duration: Duration::default(),
+            detected_by: None,
         };
 
         assert!(!module.is_empty());
@@ -223,6 +268,7 @@ mod tests {
             description: desc.to_string(),
             segments: vec![Segment::new(None, " ")],
             duration: Duration::default(),
+            detected_by: None,
         };
 
         assert!(!module.is_empty());
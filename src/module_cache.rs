@@ -0,0 +1,186 @@
+use ansi_term::Style;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::parse_style_string;
+use crate::context::Context;
+use crate::print::color_name;
+use crate::segment::Segment;
+use crate::utils;
+
+/// Modules that took longer than this at the last prompt are slow, and the placeholder prompt
+/// shows their cached segments instead of computing them again
+const SLOW_MODULE: Duration = Duration::from_millis(10);
+
+/// The segments of the modules of the last prompt of a session, and how long they took
+///
+/// This is used by the async mode of zsh, which first prints a placeholder prompt where slow
+/// modules show what they showed at the last prompt, and then redraws the prompt once it has
+/// been fully computed in the background. The segments are only shown again in the directory
+/// they were computed in, as most slow modules show something about the directory.
+#[derive(Default, Serialize, Deserialize)]
+pub struct ModuleCache {
+    #[serde(default)]
+    dir: PathBuf,
+    modules: HashMap<String, CachedModule>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedModule {
+    duration_ms: u64,
+    segments: Vec<CachedSegment>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedSegment {
+    text: String,
+    style: Option<String>,
+}
+
+impl ModuleCache {
+    /// The cache file of the session, if the shell set a session key
+    pub fn path(context: &Context) -> Option<PathBuf> {
+        let session = context.get_env("STARSHIP_SESSION_KEY")?;
+        let cache_dir = context
+            .get_env_os("STARSHIP_CACHE")
+            .map(PathBuf::from)
+            .or_else(|| dirs_next::home_dir().map(|home| home.join(".cache/starship")))?;
+        Some(cache_dir.join(format!("modules_{}.json", session)))
+    }
+
+    /// The cache of the session for a directory, empty if it was saved in another directory
+    pub fn load(path: &Path, dir: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|cached| serde_json::from_slice::<Self>(&cached).ok())
+            .filter(|cache| cache.dir == dir)
+            .unwrap_or_else(|| Self {
+                dir: dir.to_path_buf(),
+                modules: HashMap::new(),
+            })
+    }
+
+    pub fn save(&self, path: &Path) {
+        // The file of a new session is a good time to remove those of sessions that are over
+        if let (false, Some(cache_dir)) = (path.exists(), path.parent()) {
+            utils::remove_stale_session_files(cache_dir);
+        }
+        let saved = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(path, serde_json::to_vec(self)?));
+        if let Err(error) = saved {
+            log::debug!("Unable to cache the modules of the prompt: {}", error);
+        }
+    }
+
+    /// Records the segments of a variable of the root `format`
+    pub fn insert(&mut self, module: &str, duration: Duration, segments: &[Segment]) {
+        let segments = segments
+            .iter()
+            .map(|segment| CachedSegment {
+                text: segment.value.clone(),
+                style: segment.style.map(style_string),
+            })
+            .collect();
+        let cached = CachedModule {
+            duration_ms: duration.as_millis() as u64,
+            segments,
+        };
+        self.modules.insert(module.to_string(), cached);
+    }
+
+    /// The cached segments of a variable of the root `format`, if it was slow at the last prompt
+    pub fn get_slow(&self, module: &str) -> Option<Vec<Segment>> {
+        let cached = self.modules.get(module)?;
+        if Duration::from_millis(cached.duration_ms) < SLOW_MODULE {
+            return None;
+        }
+        let segments = cached
+            .segments
+            .iter()
+            .map(|segment| {
+                let style = segment.style.as_deref().and_then(parse_style_string);
+                Segment::new(style, segment.text.as_str())
+            })
+            .collect();
+        Some(segments)
+    }
+}
+
+/// The style string of a style, e.g. `fg:red bg:208 bold`
+fn style_string(style: Style) -> String {
+    let colors = [("fg:", style.foreground), ("bg:", style.background)];
+    let colors = colors
+        .iter()
+        .filter_map(|(prefix, color)| Some(format!("{}{}", prefix, color_name((*color)?))));
+    let flags = [
+        (style.is_bold, "bold"),
+        (style.is_italic, "italic"),
+        (style.is_underline, "underline"),
+        (style.is_dimmed, "dimmed"),
+    ];
+    let flags = flags
+        .iter()
+        .filter(|(is_set, _)| *is_set)
+        .map(|(_, flag)| flag.to_string());
+    colors.chain(flags).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ansi_term::Color;
+
+    #[test]
+    fn slow_modules_are_cached() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("cache/modules_test.json");
+        let style = Color::Fixed(208)
+            .on(Color::RGB(0, 136, 255))
+            .bold()
+            .italic();
+        let segments = vec![Segment::new(Some(style), "main"), Segment::new(None, " ")];
+
+        let mut cache = ModuleCache::default();
+        cache.insert("git_status", Duration::from_millis(250), &segments);
+        cache.insert("directory", Duration::from_millis(1), &segments);
+        cache.save(&path);
+
+        let cache = ModuleCache::load(&path, Path::new(""));
+        let cached = cache.get_slow("git_status").expect("git_status was slow");
+        let cached: Vec<(Option<Style>, &str)> = cached
+            .iter()
+            .map(|segment| (segment.style, segment.value.as_str()))
+            .collect();
+        assert_eq!(cached, vec![(Some(style), "main"), (None, " ")]);
+        assert!(cache.get_slow("directory").is_none());
+        assert!(cache.get_slow("rust").is_none());
+        dir.close()
+    }
+
+    #[test]
+    fn cache_is_kept_per_directory() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("modules_test.json");
+        let segments = vec![Segment::new(None, "main")];
+
+        let mut cache = ModuleCache::load(&path, Path::new("/src/repo"));
+        cache.insert("git_branch", Duration::from_millis(250), &segments);
+        cache.save(&path);
+
+        let cache = ModuleCache::load(&path, Path::new("/src/repo"));
+        assert!(cache.get_slow("git_branch").is_some());
+        let cache = ModuleCache::load(&path, Path::new("/tmp"));
+        assert!(cache.get_slow("git_branch").is_none());
+
+        // Once saved in another directory, the cache is of that directory only
+        cache.save(&path);
+        let cache = ModuleCache::load(&path, Path::new("/src/repo"));
+        assert!(cache.get_slow("git_branch").is_none());
+        dir.close()
+    }
+}
@@ -0,0 +1,200 @@
+use super::utils::pinned_version::{find_tool_versions, parse_tool_versions};
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::asdf::AsdfConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with the tool versions pinned by asdf
+///
+/// Will display the pinned versions if a `.tool-versions` file is found in the current
+/// directory or one of its parents. Only the nearest file is read.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("asdf");
+    let config = AsdfConfig::try_load(module.config);
+
+    let tool_versions = find_tool_versions(context)?;
+
+    let versions = parse_tool_versions(&tool_versions)
+        .into_iter()
+        .filter(|(tool, _)| config.tools.is_empty() || config.tools.contains(tool))
+        .map(|(tool, version)| format_tool_version(config.tool_format, tool, version))
+        .collect::<Option<Vec<String>>>()?;
+    if versions.is_empty() {
+        return None;
+    }
+    let versions = versions.join(config.separator);
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "versions" => Some(Ok(versions.as_str())),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `asdf`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+fn format_tool_version(tool_format: &str, tool: &str, version: &str) -> Option<String> {
+    let formatted = StringFormatter::new(tool_format).and_then(|formatter| {
+        formatter
+            .map(|variable| match variable {
+                "tool" => Some(Ok(tool)),
+                "version" => Some(Ok(version)),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    match formatted {
+        Ok(segments) => Some(
+            segments
+                .iter()
+                .map(|segment| segment.value.as_str())
+                .collect(),
+        ),
+        Err(error) => {
+            log::warn!("Error in module `asdf` tool_format:\n{}", error);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs;
+    use std::io;
+
+    #[test]
+    fn folder_without_tool_versions() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("asdf")
+            .path(dir.path())
+            .config(toml::toml! {
+                [asdf]
+                disabled = false
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn tool_versions_in_parent_folder() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join(".tool-versions"),
+            "# pins\nnodejs 14.2.0 12.18.0\npython 3.8.5 # project default\n",
+        )?;
+        let sub_dir = dir.path().join("src");
+        fs::create_dir(&sub_dir)?;
+
+        let actual = ModuleRenderer::new("asdf")
+            .path(&sub_dir)
+            .config(toml::toml! {
+                [asdf]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "with {} ",
+            Color::Blue.bold().paint("🧰 nodejs 14.2.0, python 3.8.5")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn nearest_tool_versions_with_allowlist() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".tool-versions"), "terraform 0.13.5\n")?;
+        let sub_dir = dir.path().join("app");
+        fs::create_dir(&sub_dir)?;
+        fs::write(
+            sub_dir.join(".tool-versions"),
+            "nodejs 14.2.0\npython 3.8.5\nruby 2.7.2\n",
+        )?;
+
+        let actual = ModuleRenderer::new("asdf")
+            .path(&sub_dir)
+            .config(toml::toml! {
+                [asdf]
+                tool_format = "$tool@$version"
+                separator = " "
+                tools = ["ruby", "nodejs", "terraform"]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "with {} ",
+            Color::Blue.bold().paint("🧰 nodejs@14.2.0 ruby@2.7.2")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn no_allowed_tools_pinned() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".tool-versions"), "terraform 0.13.5\n")?;
+
+        let actual = ModuleRenderer::new("asdf")
+            .path(dir.path())
+            .config(toml::toml! {
+                [asdf]
+                tools = ["nodejs"]
+                disabled = false
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn custom_tool_versions_file_name() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".asdf-versions"), "golang 1.15.6\n")?;
+
+        let actual = ModuleRenderer::new("asdf")
+            .path(dir.path())
+            .env("ASDF_DEFAULT_TOOL_VERSIONS_FILENAME", ".asdf-versions")
+            .config(toml::toml! {
+                [asdf]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "with {} ",
+            Color::Blue.bold().paint("🧰 golang 1.15.6")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+}
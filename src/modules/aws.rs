@@ -1,26 +1,35 @@
 use std::collections::HashMap;
-use std::fs::File;
+use std::ffi::OsStr;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use chrono::DateTime;
+use ini::Ini;
+
 use super::{Context, Module, RootModuleConfig};
 
-use crate::configs::aws::AwsConfig;
+use crate::configs::aws::{AwsAliasConfig, AwsConfig};
 use crate::formatter::StringFormatter;
+use crate::utils::render_time;
 
 type Profile = String;
 type Region = String;
 
-fn get_aws_region_from_config(context: &Context, aws_profile: Option<&str>) -> Option<Region> {
-    let config_location = context
+fn get_config_file_path(context: &Context) -> Option<PathBuf> {
+    context
         .get_env("AWS_CONFIG_FILE")
         .and_then(|path| PathBuf::from_str(&path).ok())
         .or_else(|| {
             let mut home = dirs_next::home_dir()?;
             home.push(".aws/config");
             Some(home)
-        })?;
+        })
+}
+
+fn get_aws_region_from_config(context: &Context, aws_profile: Option<&str>) -> Option<Region> {
+    let config_location = get_config_file_path(context)?;
 
     let file = File::open(&config_location).ok()?;
     let reader = BufReader::new(file);
@@ -65,10 +74,173 @@ fn get_aws_profile_and_region(context: &Context) -> (Option<Profile>, Option<Reg
     }
 }
 
-fn alias_region(region: String, aliases: &HashMap<String, &str>) -> String {
-    match aliases.get(&region) {
-        None => region,
-        Some(alias) => (*alias).to_string(),
+/// The name of the section of a profile in `~/.aws/config`
+fn get_profile_section_name(aws_profile: Option<&str>) -> String {
+    match aws_profile {
+        Some(aws_profile) => format!("profile {}", aws_profile),
+        None => String::from("default"),
+    }
+}
+
+/// Get the expiration time of the current credentials, as a unix timestamp
+///
+/// Credential helpers like aws-vault export the expiration time, otherwise it
+/// is read from the credentials file, where it is written by tools like
+/// saml2aws (`x_security_token_expires`) or by credential caching (`expiration`).
+/// For SSO profiles, it is the expiration of the access token cached by `aws sso login`.
+fn get_credentials_expiration(context: &Context, aws_profile: Option<&str>) -> Option<i64> {
+    let expiration = context
+        .get_env("AWS_SESSION_EXPIRATION")
+        .or_else(|| context.get_env("AWS_CREDENTIAL_EXPIRATION"))
+        .or_else(|| {
+            let credentials_location = context
+                .get_env("AWS_SHARED_CREDENTIALS_FILE")
+                .and_then(|path| PathBuf::from_str(&path).ok())
+                .or_else(|| {
+                    let mut home = dirs_next::home_dir()?;
+                    home.push(".aws/credentials");
+                    Some(home)
+                })?;
+            let credentials = Ini::load_from_file(credentials_location).ok()?;
+            let section = credentials.section(Some(aws_profile.unwrap_or("default")))?;
+
+            section
+                .get("expiration")
+                .or_else(|| section.get("x_security_token_expires"))
+                .map(str::to_owned)
+        })
+        .or_else(|| {
+            let start_url = get_sso_start_url(context, aws_profile)?;
+            let mut cache_dir = dirs_next::home_dir()?;
+            cache_dir.push(".aws/sso/cache");
+            get_sso_expiration(&cache_dir, &start_url)
+        })?;
+
+    // Older versions of the AWS CLI write SSO expirations like `2021-01-01T00:00:00UTC`
+    let expiration = expiration.trim();
+    let expiration = match expiration.strip_suffix("UTC") {
+        Some(expiration) => format!("{}Z", expiration),
+        None => expiration.to_string(),
+    };
+    match DateTime::parse_from_rfc3339(&expiration) {
+        Ok(expiration) => Some(expiration.timestamp()),
+        Err(error) => {
+            log::warn!(
+                "Unable to parse AWS credentials expiration `{}`: {}",
+                expiration,
+                error
+            );
+            None
+        }
+    }
+}
+
+/// The start URL of the SSO session of a profile, given either by its `sso_start_url`
+/// or by the `[sso-session ...]` section its `sso_session` refers to
+fn get_sso_start_url(context: &Context, aws_profile: Option<&str>) -> Option<String> {
+    let profile_config = Ini::load_from_file(get_config_file_path(context)?).ok()?;
+    let section = profile_config.section(Some(get_profile_section_name(aws_profile)))?;
+    if let Some(start_url) = section.get("sso_start_url") {
+        return Some(start_url.to_string());
+    }
+
+    let session = section.get("sso_session")?;
+    profile_config
+        .section(Some(format!("sso-session {}", session)))?
+        .get("sso_start_url")
+        .map(str::to_owned)
+}
+
+/// Get the expiration of the SSO access token of a start URL, from the JSON files in the
+/// cache of the AWS CLI
+fn get_sso_expiration(cache_dir: &Path, start_url: &str) -> Option<String> {
+    fs::read_dir(cache_dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension() == Some(OsStr::new("json")))
+        .find_map(|path| {
+            let cached: serde_json::Value = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+            // The cache also holds the registration of the CLI as a client, without a start URL
+            if cached.get("startUrl")?.as_str()? != start_url {
+                return None;
+            }
+            cached.get("expiresAt")?.as_str().map(str::to_owned)
+        })
+}
+
+/// Whether there are credentials for a profile, even without a profile or region to show
+///
+/// Besides static keys, credentials are also given by aws-vault, which exports them along
+/// with `AWS_VAULT`, or by a `credential_process` in the profile's config.
+fn has_credentials(context: &Context, aws_profile: Option<&str>) -> bool {
+    let from_env = ["AWS_VAULT", "AWS_ACCESS_KEY_ID", "AWS_ROLE_ARN"]
+        .iter()
+        .any(|name| context.get_env(name).is_some());
+    let from_process = || {
+        let profile_config = Ini::load_from_file(get_config_file_path(context)?).ok()?;
+        let section = profile_config.section(Some(get_profile_section_name(aws_profile)))?;
+        Some(section.contains_key("credential_process"))
+    };
+    from_env || from_process() == Some(true)
+}
+
+/// The name of the assumed role and the profile it was assumed from, if any
+///
+/// The role is either given by `AWS_ROLE_ARN` for web identity credentials, or by
+/// the `role_arn` and `source_profile` of the profile in `~/.aws/config`, which
+/// is also where aws-vault reads them from.
+fn get_assumed_role(
+    context: &Context,
+    aws_profile: Option<&str>,
+) -> (Option<String>, Option<String>) {
+    if let Some(role_arn) = context.get_env("AWS_ROLE_ARN") {
+        return (get_role_name(&role_arn), None);
+    }
+
+    let profile_config = get_config_file_path(context)
+        .and_then(|config_location| Ini::load_from_file(config_location).ok());
+    let profile_config = match &profile_config {
+        Some(profile_config) => profile_config,
+        None => return (None, None),
+    };
+    let section = match profile_config.section(Some(get_profile_section_name(aws_profile))) {
+        Some(section) => section,
+        None => return (None, None),
+    };
+
+    let role = section.get("role_arn").and_then(get_role_name);
+    let source_profile = role
+        .as_ref()
+        .and_then(|_| section.get("source_profile"))
+        .map(str::to_owned);
+    (role, source_profile)
+}
+
+/// Get the role name from an ARN like `arn:aws:iam::123456789012:role/path/name`
+fn get_role_name(role_arn: &str) -> Option<String> {
+    let resource = role_arn.trim().rsplit(':').next()?;
+    let role_path = resource.strip_prefix("role/")?;
+    let role_name = role_path.rsplit('/').next()?;
+
+    if role_name.is_empty() {
+        return None;
+    }
+    Some(role_name.to_string())
+}
+
+/// Apply the alias of a profile or region, returning the name to display and the
+/// style override, if any
+fn alias_name<'a>(
+    name: String,
+    aliases: &HashMap<String, AwsAliasConfig<'a>>,
+) -> (String, Option<&'a str>) {
+    match aliases.get(&name) {
+        None => (name, None),
+        Some(alias) => (
+            alias.name.map_or(name, |alias_name| alias_name.to_string()),
+            alias.style,
+        ),
     }
 }
 
@@ -77,14 +249,44 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let config: AwsConfig = AwsConfig::try_load(module.config);
 
     let (aws_profile, aws_region) = get_aws_profile_and_region(context);
-    if aws_profile.is_none() && aws_region.is_none() {
+    if aws_profile.is_none()
+        && aws_region.is_none()
+        && !has_credentials(context, aws_profile.as_deref())
+    {
         return None;
     }
 
-    let mapped_region = if let Some(aws_region) = aws_region {
-        Some(alias_region(aws_region, &config.region_aliases))
-    } else {
-        None
+    let remaining = get_credentials_expiration(context, aws_profile.as_deref())
+        .map(|expiration| expiration - chrono::Local::now().timestamp());
+    let expiration = remaining.map(|remaining| {
+        if remaining > 0 {
+            render_time(remaining as u128 * 1000, false)
+        } else {
+            config.expiration_symbol.to_string()
+        }
+    });
+
+    let (role, source_profile) = get_assumed_role(context, aws_profile.as_deref());
+
+    let (mapped_profile, profile_style) = match aws_profile {
+        Some(aws_profile) => {
+            let (profile, style) = alias_name(aws_profile, &config.profile_aliases);
+            (Some(profile), style)
+        }
+        None => (None, None),
+    };
+    let (mapped_region, region_style) = match aws_region {
+        Some(aws_region) => {
+            let (region, style) = alias_name(aws_region, &config.region_aliases);
+            (Some(region), style)
+        }
+        None => (None, None),
+    };
+    // Credentials about to expire need attention first, then profile styles win, as
+    // accounts are usually what needs to stand out
+    let style = match remaining {
+        Some(remaining) if remaining <= config.expiration_threshold => config.expiration_style,
+        _ => profile_style.or(region_style).unwrap_or(config.style),
     };
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
@@ -94,12 +296,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => Some(Ok(style)),
                 _ => None,
             })
             .map(|variable| match variable {
-                "profile" => aws_profile.as_ref().map(Ok),
+                "profile" => mapped_profile.as_ref().map(Ok),
                 "region" => mapped_region.as_ref().map(Ok),
+                "expiration" => expiration.as_ref().map(Ok),
+                "role" => role.as_ref().map(Ok),
+                "source_profile" => source_profile.as_ref().map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -391,4 +596,270 @@ region = us-east-2
         assert_eq!(expected, actual);
         Ok(())
     }
+
+    #[test]
+    fn expiration_from_env() -> io::Result<()> {
+        let expiration = chrono::Utc::now() + chrono::Duration::seconds(1800);
+        let actual = ModuleRenderer::new("aws")
+            .env("AWS_PROFILE", "astronauts")
+            .env("AWS_REGION", "ap-northeast-2")
+            .env("AWS_SESSION_EXPIRATION", expiration.to_rfc3339().as_str())
+            .collect();
+
+        // The expiration may be a second off, depending on when the module is rendered
+        let possible_values = ["30m", "29m59s", "29m58s"];
+        let possible_values = possible_values.iter().map(|duration| {
+            Some(format!(
+                "on {} ",
+                Color::Yellow
+                    .bold()
+                    .paint(format!("☁️  astronauts(ap-northeast-2)[{}]", duration))
+            ))
+        });
+
+        assert!(
+            possible_values.clone().any(|expected| expected == actual),
+            "expected {:?} to be one of {:?}",
+            actual,
+            possible_values.collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn expired_credentials_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let credentials_path = dir.path().join("credentials");
+        let mut file = File::create(&credentials_path)?;
+        file.write_all(
+            "[astronauts]
+aws_access_key_id=dummy
+aws_secret_access_key=dummy
+x_security_token_expires=2020-01-01T00:00:00Z
+"
+            .as_bytes(),
+        )?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("AWS_PROFILE", "astronauts")
+            .env("AWS_REGION", "ap-northeast-2")
+            .env(
+                "AWS_SHARED_CREDENTIALS_FILE",
+                credentials_path.to_string_lossy().as_ref(),
+            )
+            .config(toml::toml! {
+                [aws]
+                expiration_symbol = "expired"
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Red
+                .bold()
+                .paint("☁️  astronauts(ap-northeast-2)[expired]")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn expiration_under_threshold() -> io::Result<()> {
+        let expiration = chrono::Utc::now() + chrono::Duration::seconds(1800);
+        let render = |threshold: i64| {
+            ModuleRenderer::new("aws")
+                .env("AWS_PROFILE", "astronauts")
+                .env("AWS_SESSION_EXPIRATION", expiration.to_rfc3339().as_str())
+                .config(toml::toml! {
+                    [aws]
+                    format = "on [$symbol$profile]($style) "
+                    expiration_threshold = threshold
+                    expiration_style = "bold purple"
+                })
+                .collect()
+        };
+
+        let expected =
+            |style: ansi_term::Style| Some(format!("on {} ", style.paint("☁️  astronauts")));
+        assert_eq!(render(3600), expected(Color::Purple.bold()));
+        assert_eq!(render(600), expected(Color::Yellow.bold()));
+        Ok(())
+    }
+
+    #[test]
+    fn expiration_from_sso_cache() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config_path = dir.path().join("config");
+        let mut file = File::create(&config_path)?;
+        file.write_all(
+            "[profile astronauts]
+sso_session = company
+sso_account_id = 123456789012
+
+[sso-session company]
+sso_start_url = https://company.awsapps.com/start
+"
+            .as_bytes(),
+        )?;
+        file.sync_all()?;
+
+        let cache_dir = dir.path().join("cache");
+        std::fs::create_dir(&cache_dir)?;
+        std::fs::write(
+            cache_dir.join("botocore-client-id-us-east-1.json"),
+            r#"{"clientId": "id", "expiresAt": "2030-01-01T00:00:00Z"}"#,
+        )?;
+        std::fs::write(
+            cache_dir.join("0123456789abcdef.json"),
+            r#"{"startUrl": "https://company.awsapps.com/start", "accessToken": "token", "expiresAt": "2021-06-01T12:00:00UTC"}"#,
+        )?;
+
+        let mut context =
+            crate::context::Context::new_with_dir(clap::ArgMatches::default(), dir.path());
+        context
+            .env
+            .insert("AWS_CONFIG_FILE", config_path.to_string_lossy().to_string());
+        let start_url = super::get_sso_start_url(&context, Some("astronauts"));
+        assert_eq!(
+            start_url.as_deref(),
+            Some("https://company.awsapps.com/start")
+        );
+        assert_eq!(
+            super::get_sso_expiration(&cache_dir, &start_url.unwrap()).as_deref(),
+            Some("2021-06-01T12:00:00UTC")
+        );
+        assert_eq!(
+            super::get_sso_expiration(&cache_dir, "https://other.awsapps.com/start"),
+            None
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn profile_set_with_alias() -> io::Result<()> {
+        let actual = ModuleRenderer::new("aws")
+            .env("AWS_PROFILE", "AdministratorAccess-123456789012")
+            .env("AWS_REGION", "us-east-1")
+            .config(toml::toml! {
+                [aws.profile_aliases]
+                AdministratorAccess-123456789012 = "admin"
+                [aws.region_aliases]
+                us-east-1 = "va"
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Yellow.bold().paint("☁️  admin(va)")
+        ));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn profile_alias_with_style() -> io::Result<()> {
+        let actual = ModuleRenderer::new("aws")
+            .env("AWS_PROFILE", "production")
+            .env("AWS_REGION", "us-east-1")
+            .config(toml::toml! {
+                [aws.profile_aliases]
+                production = { name = "prod", style = "bold red" }
+                [aws.region_aliases]
+                us-east-1 = { style = "bold blue" }
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Red.bold().paint("☁️  prod(us-east-1)")
+        ));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn credential_process_without_region() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config_path = dir.path().join("config");
+        std::fs::write(
+            &config_path,
+            "[default]\ncredential_process = /opt/bin/get-credentials\n",
+        )?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("AWS_CONFIG_FILE", config_path.to_string_lossy().as_ref())
+            .collect();
+        let expected = Some(format!("on {} ", Color::Yellow.bold().paint("☁️  ")));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn role_name_from_arn() {
+        assert_eq!(
+            super::get_role_name("arn:aws:iam::123456789012:role/admin"),
+            Some(String::from("admin"))
+        );
+        assert_eq!(
+            super::get_role_name("arn:aws:iam::123456789012:role/team/deploy"),
+            Some(String::from("deploy"))
+        );
+        assert_eq!(
+            super::get_role_name("arn:aws:iam::123456789012:user/astronaut"),
+            None
+        );
+    }
+
+    #[test]
+    fn role_set_from_env() -> io::Result<()> {
+        let actual = ModuleRenderer::new("aws")
+            .env("AWS_REGION", "ap-northeast-2")
+            .env("AWS_ROLE_ARN", "arn:aws:iam::123456789012:role/ci-deploy")
+            .config(toml::toml! {
+                [aws]
+                format = "on [$symbol$role(\\($region\\))]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Yellow.bold().paint("☁️  ci-deploy(ap-northeast-2)")
+        ));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn role_set_from_config() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config_path = dir.path().join("config");
+        let mut file = File::create(&config_path)?;
+        file.write_all(
+            "[profile astronauts]
+region = us-east-2
+role_arn = arn:aws:iam::123456789012:role/admin
+source_profile = personal
+"
+            .as_bytes(),
+        )?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("AWS_PROFILE", "astronauts")
+            .env("AWS_CONFIG_FILE", config_path.to_string_lossy().as_ref())
+            .config(toml::toml! {
+                [aws]
+                format = "on [$symbol$role from $source_profile]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Yellow.bold().paint("☁️  admin from personal")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
 }
@@ -10,13 +10,17 @@ use crate::formatter::StringFormatter;
 ///   (green arrow by default)
 /// - If the exit-code was anything else, it will be formatted with
 ///   `error_symbol` (red arrow by default)
+/// - If the exit-code is listed in `exit_code_symbols`, it will be formatted
+///   with the symbol given there instead
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     enum ShellEditMode {
         Normal,
+        Visual,
+        Replace,
+        ReplaceOne,
         Insert,
     }
     const ASSUMED_MODE: ShellEditMode = ShellEditMode::Insert;
-    // TODO: extend config to more modes
 
     let mut module = context.new_module("character");
     let config: CharacterConfig = CharacterConfig::try_load(module.config);
@@ -33,20 +37,26 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     // Unfortunately, this is also the name of the non-vi default mode.
     // We do some environment detection in src/init.rs to translate.
     // The result: in non-vi fish, keymap is always reported as "insert"
+    // zsh has no keymaps for replace and visual modes, so the init script reports them as
+    // "vireplace" and "vivisual"
     let mode = match (&context.shell, keymap.as_str()) {
         (Shell::Fish, "default") | (Shell::Zsh, "vicmd") => ShellEditMode::Normal,
+        (Shell::Fish, "visual") | (Shell::Zsh, "vivisual") => ShellEditMode::Visual,
+        (Shell::Fish, "replace") | (Shell::Zsh, "vireplace") => ShellEditMode::Replace,
+        (Shell::Fish, "replace_one") => ShellEditMode::ReplaceOne,
         _ => ASSUMED_MODE,
     };
 
     let symbol = match mode {
         ShellEditMode::Normal => config.vicmd_symbol,
-        ShellEditMode::Insert => {
-            if exit_success {
-                config.success_symbol
-            } else {
-                config.error_symbol
-            }
-        }
+        ShellEditMode::Visual => config.vimcmd_visual_symbol,
+        ShellEditMode::Replace => config.vimcmd_replace_symbol,
+        ShellEditMode::ReplaceOne => config.vimcmd_replace_one_symbol,
+        ShellEditMode::Insert => match config.exit_code_symbols.get(exit_code.trim()) {
+            Some(symbol) => symbol,
+            None if exit_success => config.success_symbol,
+            None => config.error_symbol,
+        },
     };
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
@@ -200,10 +210,97 @@ mod test {
         // fish keymap is other
         let actual = ModuleRenderer::new("character")
             .shell(Shell::Fish)
-            .keymap("visual")
+            .keymap("insert")
             .collect();
         assert_eq!(expected_other, actual);
 
         Ok(())
     }
+
+    #[test]
+    fn fish_vi_sub_modes() -> io::Result<()> {
+        let expected_visual = Some(format!("{} ", Color::Yellow.bold().paint("❮")));
+        let expected_replace = Some(format!("{} ", Color::Purple.bold().paint("❮")));
+        let expected_replace_one = Some(format!("{} ", Color::Purple.bold().paint("r")));
+
+        let actual = ModuleRenderer::new("character")
+            .shell(Shell::Fish)
+            .keymap("visual")
+            .collect();
+        assert_eq!(expected_visual, actual);
+
+        let actual = ModuleRenderer::new("character")
+            .shell(Shell::Fish)
+            .keymap("replace")
+            .collect();
+        assert_eq!(expected_replace, actual);
+
+        let actual = ModuleRenderer::new("character")
+            .config(toml::toml! {
+                [character]
+                vimcmd_replace_one_symbol = "[r](bold purple)"
+            })
+            .shell(Shell::Fish)
+            .keymap("replace_one")
+            .collect();
+        assert_eq!(expected_replace_one, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn zsh_replace_mode() -> io::Result<()> {
+        let expected = Some(format!("{} ", Color::Purple.bold().paint("R")));
+
+        let actual = ModuleRenderer::new("character")
+            .config(toml::toml! {
+                [character]
+                vimcmd_replace_symbol = "[R](bold purple)"
+            })
+            .shell(Shell::Zsh)
+            .keymap("vireplace")
+            .collect();
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn zsh_visual_mode() -> io::Result<()> {
+        let expected = Some(format!("{} ", Color::Yellow.bold().paint("❮")));
+
+        let actual = ModuleRenderer::new("character")
+            .shell(Shell::Zsh)
+            .keymap("vivisual")
+            .collect();
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn exit_code_symbol() -> io::Result<()> {
+        let expected_interrupted = Some(format!("{} ", Color::Yellow.bold().paint("✗")));
+        let expected_error = Some(format!("{} ", Color::Red.bold().paint("❯")));
+
+        let actual = ModuleRenderer::new("character")
+            .config(toml::toml! {
+                [character.exit_code_symbols]
+                130 = "[✗](bold yellow)"
+            })
+            .status(130)
+            .collect();
+        assert_eq!(expected_interrupted, actual);
+
+        let actual = ModuleRenderer::new("character")
+            .config(toml::toml! {
+                [character.exit_code_symbols]
+                130 = "[✗](bold yellow)"
+            })
+            .status(1)
+            .collect();
+        assert_eq!(expected_error, actual);
+
+        Ok(())
+    }
 }
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::cmake::CMakeConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current CMake version
@@ -9,17 +9,20 @@ use crate::utils;
 /// Will display the CMake version if any of the following criteria are met:
 ///     - The current directory contains a `CMakeLists.txt` or `CMakeCache.txt`
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("cmake");
+    let config = CMakeConfig::try_load(module.config);
+
     let is_cmake_project = context
         .try_begin_scan()?
-        .set_files(&["CMakeLists.txt", "CMakeCache.txt"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_cmake_project {
         return None;
     }
 
-    let mut module = context.new_module("cmake");
-    let config = CMakeConfig::try_load(module.config);
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
@@ -31,10 +34,17 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => utils::exec_cmd("cmake", &["--version"])
-                    .map(|output| format_cmake_version(&output.stdout))
-                    .flatten()
-                    .map(Ok),
+                "version" => {
+                    let cmake_version =
+                        parse_cmake_version(&utils::exec_cmd("cmake", &["--version"])?.stdout)?;
+                    VersionFormatter::format_module_version(
+                        context,
+                        module.get_name(),
+                        &cmake_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
                 _ => None,
             })
             .parse(None)
@@ -51,9 +61,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn format_cmake_version(cmake_version: &str) -> Option<String> {
+fn parse_cmake_version(cmake_version: &str) -> Option<String> {
     let version = cmake_version.split_whitespace().nth(2)?;
-    Some(format!("v{}", version))
+    Some(version.to_string())
 }
 
 #[cfg(test)]
@@ -2,6 +2,9 @@ use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::cmd_duration::CmdDurationConfig;
 use crate::formatter::StringFormatter;
+#[cfg(any(feature = "notifications", test))]
+use crate::utils;
+use crate::utils::{render_time, render_time_spaced};
 
 /// Outputs the time it took the last command to execute
 ///
@@ -20,6 +23,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 
     let elapsed = context.get_cmd_duration()?;
+
+    if let Some(command) = context.get_last_command() {
+        if is_excluded_command(&command, &config.excluded_commands) {
+            log::trace!("Not showing duration of excluded command: {}", command);
+            return None;
+        }
+    }
     let config_min = config.min_time as u128;
 
     if elapsed < config_min {
@@ -33,7 +43,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "duration" => Some(Ok(render_time(elapsed, config.show_milliseconds))),
+                "duration" => Some(Ok(render_duration(elapsed, &config))),
                 _ => None,
             })
             .parse(None)
@@ -47,43 +57,57 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         }
     });
 
-    Some(undistract_me(module, &config, elapsed))
+    Some(undistract_me(module, &config, context, elapsed))
 }
 
-// Render the time into a nice human-readable string
-fn render_time(raw_millis: u128, show_millis: bool) -> String {
-    // Calculate a simple breakdown into days/hours/minutes/seconds/milliseconds
-    let (millis, raw_seconds) = (raw_millis % 1000, raw_millis / 1000);
-    let (seconds, raw_minutes) = (raw_seconds % 60, raw_seconds / 60);
-    let (minutes, raw_hours) = (raw_minutes % 60, raw_minutes / 60);
-    let (hours, days) = (raw_hours % 24, raw_hours / 24);
-
-    let components = [days, hours, minutes, seconds];
-    let suffixes = ["d", "h", "m", "s"];
-
-    let mut rendered_components: Vec<String> = components
-        .iter()
-        .zip(&suffixes)
-        .map(render_time_component)
-        .collect();
-    if show_millis || raw_millis < 1000 {
-        rendered_components.push(render_time_component((&millis, &"ms")));
-    }
-    rendered_components.join("")
+/// Whether the command line starts with one of the excluded commands
+///
+/// Each excluded command is compared word by word against the start of the
+/// command line, so `git rebase` excludes `git rebase -i` but not `git log`.
+/// The program itself is compared by name, so `vim` also excludes `/usr/bin/vim`.
+fn is_excluded_command(command: &str, excluded_commands: &[&str]) -> bool {
+    let mut words = command.split_whitespace();
+    let program = match words.next() {
+        Some(program) => program.rsplit('/').next().unwrap_or(program),
+        None => return false,
+    };
+    let command_words: Vec<&str> = std::iter::once(program).chain(words).collect();
+
+    excluded_commands.iter().any(|excluded| {
+        let excluded_words: Vec<&str> = excluded.split_whitespace().collect();
+        !excluded_words.is_empty() && command_words.starts_with(&excluded_words)
+    })
 }
 
-/// Render a single component of the time string, giving an empty string if component is zero
-fn render_time_component((component, suffix): (&u128, &&str)) -> String {
-    match component {
-        0 => String::new(),
-        n => format!("{}{}", n, suffix),
+/// Render the duration according to the configured `duration_format`
+fn render_duration(raw_millis: u128, config: &CmdDurationConfig) -> String {
+    let show_millis = config.show_milliseconds
+        || (config.show_milliseconds_below > 0
+            && raw_millis < config.show_milliseconds_below as u128);
+
+    match config.duration_format {
+        "compact" => render_time(raw_millis, show_millis),
+        "spaced" => render_time_spaced(raw_millis, show_millis),
+        "milliseconds" => format!("{}ms", raw_millis),
+        "seconds" => {
+            let precision = config.precision.max(0) as usize;
+            format!("{:.*}s", precision, raw_millis as f64 / 1000.0)
+        }
+        format => {
+            log::warn!(
+                "Unknown duration_format in [cmd_duration]: {}, falling back to \"compact\"",
+                format
+            );
+            render_time(raw_millis, show_millis)
+        }
     }
 }
 
-#[cfg(not(feature = "notify-rust"))]
+#[cfg(not(feature = "notifications"))]
 fn undistract_me<'a, 'b>(
     module: Module<'a>,
     config: &'b CmdDurationConfig,
+    _context: &'a Context,
     _elapsed: u128,
 ) -> Module<'a> {
     if config.show_notifications {
@@ -93,16 +117,22 @@ fn undistract_me<'a, 'b>(
     module
 }
 
-#[cfg(feature = "notify-rust")]
+#[cfg(feature = "notifications")]
 fn undistract_me<'a, 'b>(
     module: Module<'a>,
     config: &'b CmdDurationConfig,
+    context: &'a Context,
     elapsed: u128,
 ) -> Module<'a> {
     use ansi_term::{unstyle, ANSIStrings};
     use notify_rust::{Notification, Timeout};
 
-    if config.show_notifications && config.min_time_to_notify as u128 <= elapsed {
+    if should_notify(config, context, elapsed) {
+        let timeout = match config.notification_timeout {
+            timeout if timeout >= 0 => Timeout::Milliseconds(timeout as u32),
+            _ => Timeout::Default,
+        };
+
         let body = format!(
             "Command execution {}",
             unstyle(&ANSIStrings(&module.ansi_strings()))
@@ -113,7 +143,7 @@ fn undistract_me<'a, 'b>(
             .summary("Command finished")
             .body(&body)
             .icon("utilities-terminal")
-            .timeout(Timeout::Milliseconds(750));
+            .timeout(timeout);
 
         if let Err(err) = notification.show() {
             log::trace!("Cannot show notification: {}", err);
@@ -123,6 +153,43 @@ fn undistract_me<'a, 'b>(
     module
 }
 
+/// Whether the command took long enough to notify about, and the terminal isn't focused
+#[cfg(any(feature = "notifications", test))]
+fn should_notify(config: &CmdDurationConfig, context: &Context, elapsed: u128) -> bool {
+    if !config.show_notifications || (config.min_time_to_notify as u128) > elapsed {
+        return false;
+    }
+
+    // zsh in async mode prints the prompt of each command twice, notify with the final one
+    if context.properties.get("async").map(String::as_str) == Some("placeholder") {
+        return false;
+    }
+
+    // There is no need to notify the user about a terminal they are looking at
+    if is_terminal_focused(context) == Some(true) {
+        log::trace!("Terminal is focused, not showing notification");
+        return false;
+    }
+
+    true
+}
+
+/// Whether the terminal running the prompt is the focused window
+///
+/// This is only known on X11, where most terminals export their window as `WINDOWID`.
+/// Returns `None` if the focus can't be determined.
+#[cfg(any(feature = "notifications", test))]
+fn is_terminal_focused(context: &Context) -> Option<bool> {
+    let window_id = context.get_env("WINDOWID")?.parse::<u64>().ok()?;
+
+    // e.g. `_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007`
+    let output = utils::exec_cmd("xprop", &["-root", "_NET_ACTIVE_WINDOW"])?;
+    let active_window = output.stdout.split('#').nth(1)?.split(',').next()?.trim();
+    let active_window = u64::from_str_radix(active_window.trim_start_matches("0x"), 16).ok()?;
+
+    Some(active_window == window_id)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,6 +218,12 @@ mod tests {
         assert_eq!(render_time(86_400_000_u128, true), "1d")
     }
 
+    #[test]
+    fn test_spaced() {
+        assert_eq!(render_time_spaced(123_456_u128, false), "2m 3s");
+        assert_eq!(render_time_spaced(3_723_456_u128, true), "1h 2m 3s 456ms")
+    }
+
     #[test]
     fn config_blank_duration_1s() -> io::Result<()> {
         let actual = ModuleRenderer::new("cmd_duration")
@@ -203,6 +276,124 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_is_excluded_command() {
+        let excluded = ["vim", "git rebase"];
+        assert!(is_excluded_command("vim src/main.rs", &excluded));
+        assert!(is_excluded_command("/usr/bin/vim", &excluded));
+        assert!(is_excluded_command("git rebase -i HEAD~3", &excluded));
+        assert!(!is_excluded_command("git log", &excluded));
+        assert!(!is_excluded_command("nvim", &excluded));
+    }
+
+    #[test]
+    fn config_excluded_command() -> io::Result<()> {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                excluded_commands = ["ssh", "less"]
+            })
+            .cmd_duration(5000)
+            .last_command("ssh example.com")
+            .collect();
+
+        let expected = None;
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn config_excluded_command_from_env() -> io::Result<()> {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                excluded_commands = ["ssh"]
+            })
+            .env("STARSHIP_LAST_COMMAND", "ssh \"example.com\"")
+            .cmd_duration(5000)
+            .collect();
+
+        assert_eq!(None, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn config_not_excluded_command() -> io::Result<()> {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                excluded_commands = ["ssh", "less"]
+            })
+            .cmd_duration(5000)
+            .last_command("cargo build")
+            .collect();
+
+        let expected = Some(format!("took {} ", Color::Yellow.bold().paint("5s")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn config_show_milliseconds_below() -> io::Result<()> {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                show_milliseconds_below = 10_000
+            })
+            .cmd_duration(5123)
+            .collect();
+
+        let expected = Some(format!("took {} ", Color::Yellow.bold().paint("5s123ms")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn config_duration_format_milliseconds() -> io::Result<()> {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                duration_format = "milliseconds"
+            })
+            .cmd_duration(62_345)
+            .collect();
+
+        let expected = Some(format!("took {} ", Color::Yellow.bold().paint("62345ms")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn config_duration_format_seconds() -> io::Result<()> {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                duration_format = "seconds"
+                precision = 2
+            })
+            .cmd_duration(62_347)
+            .collect();
+
+        let expected = Some(format!("took {} ", Color::Yellow.bold().paint("62.35s")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn config_duration_format_spaced() -> io::Result<()> {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                duration_format = "spaced"
+            })
+            .cmd_duration(123_456)
+            .collect();
+
+        let expected = Some(format!("took {} ", Color::Yellow.bold().paint("2m 3s")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
     #[test]
     fn config_1s_duration_prefix_underwent() -> io::Result<()> {
         let actual = ModuleRenderer::new("cmd_duration")
@@ -232,4 +423,59 @@ mod tests {
         assert_eq!(expected, actual);
         Ok(())
     }
+
+    fn notification_context(window_id: Option<&str>) -> Context<'static> {
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), "/");
+        if let Some(window_id) = window_id {
+            context.env.insert("WINDOWID", window_id.to_string());
+        }
+        context
+    }
+
+    fn notification_config() -> CmdDurationConfig<'static> {
+        let mut config = CmdDurationConfig::new();
+        config.show_notifications = true;
+        config
+    }
+
+    #[test]
+    fn notify_when_terminal_unfocused() {
+        // The mocked active window is 0x3a00007
+        let context = notification_context(Some("60817416"));
+        assert_eq!(is_terminal_focused(&context), Some(false));
+        assert!(should_notify(&notification_config(), &context, 60_000));
+    }
+
+    #[test]
+    fn no_notification_when_terminal_focused() {
+        let context = notification_context(Some("60817415"));
+        assert_eq!(is_terminal_focused(&context), Some(true));
+        assert!(!should_notify(&notification_config(), &context, 60_000));
+    }
+
+    #[test]
+    fn notify_when_focus_unknown() {
+        let context = notification_context(None);
+        assert_eq!(is_terminal_focused(&context), None);
+        assert!(should_notify(&notification_config(), &context, 60_000));
+    }
+
+    #[test]
+    fn no_notification_for_short_commands() {
+        let context = notification_context(Some("60817416"));
+        assert!(!should_notify(&notification_config(), &context, 10_000));
+        assert!(!should_notify(&CmdDurationConfig::new(), &context, 60_000));
+    }
+
+    #[test]
+    fn notify_once_in_async_mode() {
+        let mut context = notification_context(Some("60817416"));
+        context
+            .properties
+            .insert("async", "placeholder".to_string());
+        assert!(!should_notify(&notification_config(), &context, 60_000));
+
+        context.properties.insert("async", "final".to_string());
+        assert!(should_notify(&notification_config(), &context, 60_000));
+    }
 }
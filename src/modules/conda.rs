@@ -1,5 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
+use std::path::Path;
+
 use super::utils::directory::truncate;
 use crate::configs::conda::CondaConfig;
 use crate::formatter::StringFormatter;
@@ -24,6 +26,18 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 
     let conda_env = truncate(conda_env, config.truncation_length);
+    let stack = get_stacked_envs(context, &conda_env);
+    // Only show the stack when there are environments layered underneath
+    let stack_depth = if stack.len() > 1 {
+        Some(stack.len().to_string())
+    } else {
+        None
+    };
+    let stack = if stack.len() > 1 {
+        Some(stack.join(config.stack_separator))
+    } else {
+        None
+    };
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -37,6 +51,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "environment" => Some(Ok(conda_env.as_str())),
+                "stack_depth" => stack_depth.as_deref().map(Ok),
+                "stack" => stack.as_deref().map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -53,6 +69,46 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// The environments activated with `conda activate --stack`, from the bottom to the top
+///
+/// Conda keeps the prefix of each activation level in `$CONDA_PREFIX_<level>` and sets
+/// `$CONDA_STACKED_<level>` when that level was stacked onto the one below it.
+fn get_stacked_envs(context: &Context, conda_env: &str) -> Vec<String> {
+    let mut stack = vec![conda_env.to_string()];
+    let mut level: usize = context
+        .get_env("CONDA_SHLVL")
+        .and_then(|shlvl| shlvl.trim().parse().ok())
+        .unwrap_or(0);
+
+    while level > 1
+        && context
+            .get_env(format!("CONDA_STACKED_{}", level))
+            .as_deref()
+            == Some("true")
+    {
+        level -= 1;
+        match context.get_env(format!("CONDA_PREFIX_{}", level)) {
+            Some(prefix) => stack.push(get_env_name(Path::new(&prefix))),
+            None => break,
+        }
+    }
+
+    stack.reverse();
+    stack
+}
+
+/// Named environments live in the `envs` directory of the conda installation, which is
+/// itself the `base` environment
+fn get_env_name(prefix: &Path) -> String {
+    match (
+        prefix.parent().and_then(Path::file_name),
+        prefix.file_name(),
+    ) {
+        (Some(parent), Some(name)) if parent == "envs" => name.to_string_lossy().to_string(),
+        _ => String::from("base"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::ModuleRenderer;
@@ -100,6 +156,48 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn stacked_envs() -> io::Result<()> {
+        let actual = ModuleRenderer::new("conda")
+            .env("CONDA_DEFAULT_ENV", "tools")
+            .env("CONDA_SHLVL", "3")
+            .env("CONDA_PREFIX_1", "/opt/conda")
+            .env("CONDA_PREFIX_2", "/opt/conda/envs/astronauts")
+            .env("CONDA_STACKED_2", "true")
+            .env("CONDA_STACKED_3", "true")
+            .config(toml::toml! {
+                [conda]
+                format = "via [$symbol$stack( \\($stack_depth\\))]($style) "
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("🅒 base > astronauts > tools (3)")
+        ));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn activated_env_without_stack() -> io::Result<()> {
+        let actual = ModuleRenderer::new("conda")
+            .env("CONDA_DEFAULT_ENV", "tools")
+            .env("CONDA_SHLVL", "2")
+            .env("CONDA_PREFIX_1", "/opt/conda")
+            .config(toml::toml! {
+                [conda]
+                format = "via [$symbol$environment( \\($stack_depth\\))]($style) "
+            })
+            .collect();
+
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("🅒 tools")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
     #[test]
     fn truncate() -> io::Result<()> {
         let actual = ModuleRenderer::new("conda")
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::crystal::CrystalConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Crystal version
@@ -10,19 +10,20 @@ use crate::utils;
 ///     - Current directory contains a `.cr` file
 ///     - Current directory contains a `shard.yml` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("crystal");
+    let config: CrystalConfig = CrystalConfig::try_load(module.config);
+
     let is_crystal_project = context
         .try_begin_scan()?
-        .set_files(&["shard.yml"])
-        .set_extensions(&["cr"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_crystal_project {
         return None;
     }
 
-    let mut module = context.new_module("crystal");
-    let config: CrystalConfig = CrystalConfig::try_load(module.config);
-
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
@@ -34,10 +35,18 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => format_crystal_version(
-                    utils::exec_cmd("crystal", &["--version"])?.stdout.as_str(),
-                )
-                .map(Ok),
+                "version" => {
+                    let crystal_version = parse_crystal_version(
+                        utils::exec_cmd("crystal", &["--version"])?.stdout.as_str(),
+                    )?;
+                    VersionFormatter::format_module_version(
+                        context,
+                        module.get_name(),
+                        &crystal_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
                 _ => None,
             })
             .parse(None)
@@ -54,17 +63,14 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn format_crystal_version(crystal_version: &str) -> Option<String> {
+fn parse_crystal_version(crystal_version: &str) -> Option<String> {
     let version = crystal_version
         // split into ["Crystal", "0.35.1", ...]
         .split_whitespace()
         // return "0.35.1"
         .nth(1)?;
 
-    let mut formatted_version = String::with_capacity(version.len() + 1);
-    formatted_version.push('v');
-    formatted_version.push_str(version);
-    Some(formatted_version)
+    Some(version.to_string())
 }
 
 #[cfg(test)]
@@ -1,7 +1,14 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
 use std::io::Write;
-use std::process::{Command, Output, Stdio};
-use std::time::Instant;
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::time::{Duration, Instant};
 
+use regex::Regex;
+
+use super::utils::condition;
 use super::{Context, Module, RootModuleConfig};
 
 use crate::{configs::custom::CustomConfig, formatter::StringFormatter};
@@ -9,8 +16,9 @@ use crate::{configs::custom::CustomConfig, formatter::StringFormatter};
 /// Creates a custom module with some configuration
 ///
 /// The relevant TOML config will set the files, extensions, and directories needed
-/// for the module to be displayed. If none of them match, and optional "when"
-/// command can be run -- if its result is 0, the module will be shown.
+/// for the module to be displayed. If none of them match, an optional "condition"
+/// is evaluated without spawning a shell, and then an optional "when" command
+/// can be run -- if its result is 0, the module will be shown.
 ///
 /// Finally, the content of the module itself is also set by a command.
 pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
@@ -35,8 +43,20 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
     let mut is_match = scan_dir.is_match();
 
     if !is_match {
-        if let Some(when) = config.when {
-            is_match = exec_when(when, &config.shell.0);
+        if let Some(condition) = config.condition {
+            is_match = match condition::evaluate(condition, context) {
+                Ok(result) => result,
+                Err(error) => {
+                    log::warn!("Error in condition of `custom.{}`: {}", name, error);
+                    false
+                }
+            };
+        }
+
+        if !is_match {
+            if let Some(when) = config.when {
+                is_match = exec_when(when, &config.shell.0);
+            }
         }
 
         if !is_match {
@@ -46,10 +66,34 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
 
     let mut module = Module::new(name, config.description, Some(toml_config));
 
-    let output = exec_command(config.command, &config.shell.0)?;
+    let output = if config.cache_duration > 0 || config.async_refresh {
+        cached_command(name, &config, context)?
+    } else {
+        run_command(config.command.get(), &config.shell.0)?
+    };
+
+    let success = output.status.success();
+    if !success && !config.show_on_failure {
+        return None;
+    }
+    let exit_code = if success {
+        None
+    } else {
+        output.status.code().map(|code| code.to_string())
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    let trimmed = stdout.trim();
+    if !trimmed.is_empty() || !success {
+        let captures = config.regex.and_then(|regex| match Regex::new(regex) {
+            Ok(regex) => regex.captures(trimmed),
+            Err(error) => {
+                log::warn!("Error in regex of `custom.{}`:\n{}", name, error);
+                None
+            }
+        });
 
-    let trimmed = output.trim();
-    if !trimmed.is_empty() {
         let parsed = StringFormatter::new(config.format).and_then(|formatter| {
             formatter
                 .map_meta(|var, _| match var {
@@ -64,7 +108,13 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
                     // This may result in multiple calls to `get_module_version` when a user have
                     // multiple `$version` variables defined in `format`.
                     "output" => Some(Ok(trimmed)),
-                    _ => None,
+                    "exit_code" => exit_code.as_deref().map(Ok),
+                    "stderr" => Some(stderr.trim()).filter(|s| !s.is_empty()).map(Ok),
+                    // Named capture groups of `regex` are available as variables
+                    group => captures
+                        .as_ref()?
+                        .name(group)
+                        .map(|capture| Ok(capture.as_str())),
                 })
                 .parse(None)
         });
@@ -82,6 +132,113 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// Returns the output of the command, from the cache if it was cached less than
+/// `cache_duration` seconds ago
+///
+/// The output is cached in the starship cache directory per session, or per directory
+/// when `cache_scope` is `"directory"`. Failed commands aren't cached.
+///
+/// With `async_refresh`, a stale or missing output is refreshed by a `starship module`
+/// process in the background, and the last cached output (or the `placeholder`) is
+/// returned right away.
+fn cached_command(name: &str, config: &CustomConfig, context: &Context) -> Option<Output> {
+    let command = config.command.get();
+    let scope = match config.cache_scope {
+        "session" => context.get_env("STARSHIP_SESSION_KEY").unwrap_or_default(),
+        "directory" => context.current_dir.to_string_lossy().into_owned(),
+        scope => {
+            log::warn!(
+                "Unknown cache_scope in [custom.{}]: {}, falling back to \"session\"",
+                name,
+                scope
+            );
+            context.get_env("STARSHIP_SESSION_KEY").unwrap_or_default()
+        }
+    };
+
+    let mut hasher = DefaultHasher::new();
+    (scope, command, &config.shell.0).hash(&mut hasher);
+    let cache_dir = context
+        .get_env_os("STARSHIP_CACHE")
+        .map(PathBuf::from)
+        .or_else(|| dirs_next::home_dir().map(|home| home.join(".cache/starship")))?
+        .join("custom");
+    let cache_file = cache_dir.join(format!("{}_{:016x}", name, hasher.finish()));
+
+    let is_fresh = fs::metadata(&cache_file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .filter(|age| *age < Duration::from_secs(config.cache_duration));
+    // Set in the background process that refreshes the cached output
+    let is_refresh = context.get_env("STARSHIP_CUSTOM_REFRESH").as_deref() == Some(name);
+    if is_fresh.is_some() && !is_refresh {
+        if let Ok(stdout) = fs::read(&cache_file) {
+            log::trace!("Using cached output of custom module {:?}", name);
+            return Some(Output {
+                status: ExitStatus::default(),
+                stdout,
+                stderr: Vec::new(),
+            });
+        }
+    }
+
+    if config.async_refresh && !is_refresh {
+        let cached = fs::read(&cache_file).ok();
+        // Rewriting the stale output marks it as fresh, so that the following prompts
+        // don't start more refreshes while this one is running
+        if let Some(cached) = &cached {
+            fs::write(&cache_file, cached).ok();
+        }
+        spawn_refresh(name, context);
+
+        return Some(Output {
+            status: ExitStatus::default(),
+            stdout: cached.unwrap_or_else(|| config.placeholder.as_bytes().to_vec()),
+            stderr: Vec::new(),
+        });
+    }
+
+    let output = run_command(command, &config.shell.0)?;
+    if output.status.success() {
+        if let Err(error) =
+            fs::create_dir_all(&cache_dir).and_then(|_| fs::write(&cache_file, &output.stdout))
+        {
+            log::debug!(
+                "Unable to cache output of custom module {:?}: {}",
+                name,
+                error
+            );
+        }
+    }
+    Some(output)
+}
+
+/// Refreshes the cached output of a custom module in a detached `starship module` process
+fn spawn_refresh(name: &str, context: &Context) {
+    // The tests run in the test binary, which can't print modules
+    if cfg!(test) {
+        return;
+    }
+
+    let spawned = std::env::current_exe().and_then(|starship| {
+        Command::new(starship)
+            .arg("module")
+            .arg(format!("custom.{}", name))
+            .arg("--path")
+            .arg(&context.current_dir)
+            .env("STARSHIP_CUSTOM_REFRESH", name)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    });
+
+    if let Err(error) = spawned {
+        log::debug!("Unable to refresh custom module {:?}: {}", name, error);
+    }
+}
+
 /// Return the invoking shell, using `shell` and fallbacking in order to STARSHIP_SHELL and "sh"
 #[cfg(not(windows))]
 fn get_shell<'a, 'b>(shell_args: &'b [&'a str]) -> (std::borrow::Cow<'a, str>, &'b [&'a str]) {
@@ -203,28 +360,24 @@ fn exec_when(cmd: &str, shell_args: &[&str]) -> bool {
     }
 }
 
-/// Execute the given command, returning its output on success
-fn exec_command(cmd: &str, shell_args: &[&str]) -> Option<String> {
+/// Execute the given command, returning its output even if it failed
+fn run_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
     log::trace!("Running '{}'", cmd);
 
-    if let Some(output) = shell_command(cmd, shell_args) {
-        if !output.status.success() {
-            log::trace!("Non-zero exit code '{:?}'", output.status.code());
-            log::trace!(
-                "stdout: {}",
-                std::str::from_utf8(&output.stdout).unwrap_or("<invalid utf8>")
-            );
-            log::trace!(
-                "stderr: {}",
-                std::str::from_utf8(&output.stderr).unwrap_or("<invalid utf8>")
-            );
-            return None;
-        }
-
-        Some(String::from_utf8_lossy(&output.stdout).into())
-    } else {
-        None
+    let output = shell_command(cmd, shell_args)?;
+    if !output.status.success() {
+        log::trace!("Non-zero exit code '{:?}'", output.status.code());
+        log::trace!(
+            "stdout: {}",
+            std::str::from_utf8(&output.stdout).unwrap_or("<invalid utf8>")
+        );
+        log::trace!(
+            "stderr: {}",
+            std::str::from_utf8(&output.stderr).unwrap_or("<invalid utf8>")
+        );
     }
+
+    Some(output)
 }
 
 /// If the specified shell refers to PowerShell, adds the arguments "-Command -" to the
@@ -243,6 +396,9 @@ fn handle_powershell(command: &mut Command, shell: &str, shell_args: &[&str]) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::StarshipConfig;
+    use std::io;
+    use std::path::Path;
 
     #[cfg(not(windows))]
     const SHELL: &[&str] = &["/bin/sh"];
@@ -256,6 +412,17 @@ mod tests {
 
     const UNKNOWN_COMMAND: &str = "ydelsyiedsieudleylse dyesdesl";
 
+    /// Execute the given command, returning its output on success
+    fn exec_command(cmd: &str, shell_args: &[&str]) -> Option<String> {
+        let output = run_command(cmd, shell_args)?;
+
+        if output.status.success() {
+            Some(String::from_utf8_lossy(&output.stdout).into())
+        } else {
+            None
+        }
+    }
+
     #[test]
     fn when_returns_right_value() {
         assert!(exec_when("echo hello", SHELL));
@@ -313,6 +480,189 @@ mod tests {
         );
     }
 
+    fn render_module(config: toml::Value) -> Option<String> {
+        let dir = tempfile::tempdir().ok()?;
+        render_module_in(config, dir.path(), &[])
+    }
+
+    fn render_module_in(
+        config: toml::Value,
+        dir: &Path,
+        env: &[(&'static str, &Path)],
+    ) -> Option<String> {
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir);
+        context.config = StarshipConfig {
+            config: Some(config),
+        };
+        for (key, value) in env {
+            context
+                .env
+                .insert(key, value.to_string_lossy().into_owned());
+        }
+        module("test", &context).map(|module| module.to_string())
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn regex_captures_are_variables() {
+        let actual = render_module(toml::toml! {
+            [custom.test]
+            command = "echo 'user@example.com (admin)'"
+            when = "true"
+            shell = ["/bin/sh"]
+            regex = r"^(?P<user>[^@]+)@(?P<host>\S+)(?: \((?P<role>\w+)\))?"
+            format = "$user at $host( as $role)( in $team)"
+        });
+        assert_eq!(actual, Some("user at example.com as admin".into()));
+
+        let actual = render_module(toml::toml! {
+            [custom.test]
+            command = "echo nothing"
+            when = "true"
+            shell = ["/bin/sh"]
+            regex = "(?P<user>[^@]+)@"
+            format = "$output( $user)"
+        });
+        assert_eq!(actual, Some("nothing".into()));
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    fn command_can_be_overridden_per_os() {
+        let actual = render_module(toml::toml! {
+            [custom.test]
+            command = { default = "echo default", windows = "echo windows" }
+            when = "true"
+            shell = ["/bin/sh"]
+            format = "$output"
+        });
+        assert_eq!(actual, Some("default".into()));
+
+        let actual = render_module(toml::toml! {
+            [custom.test]
+            command = { linux = "echo unix", macos = "echo unix", windows = "echo windows" }
+            when = "true"
+            shell = ["/bin/sh"]
+            format = "$output"
+        });
+        assert_eq!(actual, Some("unix".into()));
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn command_output_is_cached() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+        let dir = tempfile::tempdir()?;
+        let other_dir = tempfile::tempdir()?;
+        let value = dir.path().join("value");
+        let env = [("STARSHIP_CACHE", cache.path())];
+        let render = |scope: &str, duration: i64, dir: &Path| {
+            let command = format!("cat {}", value.to_string_lossy());
+            render_module_in(
+                toml::toml! {
+                    [custom.test]
+                    command = command
+                    when = "true"
+                    shell = ["/bin/sh"]
+                    format = "$output"
+                    cache_duration = duration
+                    cache_scope = scope
+                },
+                dir,
+                &env,
+            )
+        };
+
+        fs::write(&value, "first")?;
+        assert_eq!(render("session", 60, dir.path()), Some("first".into()));
+        fs::write(&value, "second")?;
+        assert_eq!(render("session", 60, dir.path()), Some("first".into()));
+        assert_eq!(
+            render("session", 60, other_dir.path()),
+            Some("first".into())
+        );
+        assert_eq!(render("session", 0, dir.path()), Some("second".into()));
+
+        assert_eq!(render("directory", 60, dir.path()), Some("second".into()));
+        fs::write(&value, "third")?;
+        assert_eq!(render("directory", 60, dir.path()), Some("second".into()));
+        assert_eq!(
+            render("directory", 60, other_dir.path()),
+            Some("third".into())
+        );
+
+        cache.close()?;
+        other_dir.close()?;
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn async_refresh_renders_cached_output() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+        let dir = tempfile::tempdir()?;
+        let value = dir.path().join("value");
+        let render = |is_refresh: bool| {
+            let command = format!("cat {}", value.to_string_lossy());
+            let mut env = vec![("STARSHIP_CACHE", cache.path())];
+            if is_refresh {
+                env.push(("STARSHIP_CUSTOM_REFRESH", Path::new("test")));
+            }
+            render_module_in(
+                toml::toml! {
+                    [custom.test]
+                    command = command
+                    when = "true"
+                    shell = ["/bin/sh"]
+                    format = "$output"
+                    async_refresh = true
+                    placeholder = "..."
+                },
+                dir.path(),
+                &env,
+            )
+        };
+
+        fs::write(&value, "first")?;
+        assert_eq!(render(false), Some("...".into()));
+        assert_eq!(render(true), Some("first".into()));
+        fs::write(&value, "second")?;
+        assert_eq!(render(false), Some("first".into()));
+        assert_eq!(render(true), Some("second".into()));
+        assert_eq!(render(false), Some("second".into()));
+
+        cache.close()?;
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn failed_command_can_be_shown() {
+        let render = |show_on_failure: bool| {
+            render_module(toml::toml! {
+                [custom.test]
+                command = "echo partial; echo 'not found' >&2; exit 3"
+                when = "true"
+                shell = ["/bin/sh"]
+                format = "$output( \\[$exit_code\\])( $stderr)"
+                show_on_failure = show_on_failure
+            })
+        };
+
+        assert_eq!(render(false), None);
+        assert_eq!(render(true), Some("partial [3] not found".into()));
+
+        let actual = render_module(toml::toml! {
+            [custom.test]
+            command = "echo ok; echo warning >&2"
+            when = "true"
+            shell = ["/bin/sh"]
+            format = "$output( \\[$exit_code\\])( $stderr)"
+            show_on_failure = true
+        });
+        assert_eq!(actual, Some("ok warning".into()));
+    }
+
     #[test]
     fn command_can_fail() {
         assert_eq!(exec_command(FAILING_COMMAND, SHELL), None);
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::dart::DartConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Dart version
@@ -11,11 +11,14 @@ use crate::utils;
 ///     - Current directory contains a `.dart_tool` directory
 ///     - Current directory contains a `pubspec.yaml`/`pubspec.yml` or `pubspec.lock` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("dart");
+    let config: DartConfig = DartConfig::try_load(module.config);
+
     let is_dart_project = context
         .try_begin_scan()?
-        .set_extensions(&["dart"])
-        .set_folders(&[".dart_tool"])
-        .set_files(&["pubspec.yaml", "pubspec.yml", "pubspec.lock"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_dart_project {
@@ -24,9 +27,6 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     let dart_version = utils::exec_cmd("dart", &["--version"])?.stderr;
 
-    let mut module = context.new_module("dart");
-    let config: DartConfig = DartConfig::try_load(module.config);
-
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -38,7 +38,16 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => parse_dart_version(&dart_version).map(Ok),
+                "version" => {
+                    let dart_version = parse_dart_version(&dart_version)?;
+                    VersionFormatter::format_module_version(
+                        context,
+                        module.get_name(),
+                        &dart_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
                 _ => None,
             })
             .parse(None)
@@ -62,7 +71,7 @@ fn parse_dart_version(dart_version: &str) -> Option<String> {
         // return "2.8.4"
         .nth(3)?;
 
-    Some(format!("v{}", version))
+    Some(version.to_string())
 }
 
 #[cfg(test)]
@@ -76,7 +85,7 @@ mod tests {
     #[test]
     fn test_parse_dart_version() {
         let input = "Dart VM version: 2.8.4 (stable)";
-        assert_eq!(parse_dart_version(input), Some("v2.8.4".to_string()));
+        assert_eq!(parse_dart_version(input), Some("2.8.4".to_string()));
     }
 
     #[test]
@@ -15,6 +15,7 @@ use crate::config::RootModuleConfig;
 use crate::configs::directory::DirectoryConfig;
 use crate::context::Shell;
 use crate::formatter::StringFormatter;
+use crate::print::better_width;
 
 const HOME_SYMBOL: &str = "~";
 
@@ -32,7 +33,11 @@ const HOME_SYMBOL: &str = "~";
 /// Paths will undergo user-provided substitutions of substrings
 ///
 /// **Truncation**
-/// Paths will be limited in length to `3` path components by default.
+/// Paths will be limited in length to `3` path components by default, or to a
+/// percentage of the terminal width if `truncation_width_percentage` is set.
+///
+/// **Icons**
+/// Paths matching a user-provided pattern will be prefixed with its icon
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("directory");
     let config: DirectoryConfig = DirectoryConfig::try_load(module.config);
@@ -57,10 +62,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     let substituted_dir = substitute_path(dir_string, &config.substitutions);
 
-    // Truncate the dir string to the maximum number of path components
-    let truncated_dir_string = truncate(substituted_dir, config.truncation_length as usize);
-
-    let prefix = if is_truncated(&truncated_dir_string) {
+    let icon = get_directory_icon(current_dir, &home_dir, &config.icons).unwrap_or("");
+    let truncation_prefix = |truncated_dir_string: &str| {
+        if !is_truncated(truncated_dir_string) {
+            return String::from("");
+        }
         // Substitutions could have changed the prefix, so don't allow them and
         // fish-style path contraction together
         if config.fish_style_pwd_dir_length > 0 && config.substitutions.is_empty() {
@@ -69,27 +75,59 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             to_fish_style(
                 config.fish_style_pwd_dir_length as usize,
                 contracted_home_dir,
-                &truncated_dir_string,
+                truncated_dir_string,
             )
         } else {
             String::from(config.truncation_symbol)
         }
+    };
+
+    // Truncate the dir string to the maximum number of path components, or just
+    // enough for the icon, prefix and path to fit in the requested share of the terminal width
+    let truncated_dir_string = if config.truncation_width_percentage > 0 {
+        let max_width = context.width * config.truncation_width_percentage as usize / 100;
+        let max_width = max_width.saturating_sub(better_width(icon));
+        truncate_to_width(substituted_dir, max_width, truncation_prefix)
     } else {
-        String::from("")
+        truncate(substituted_dir, config.truncation_length as usize)
     };
 
-    let displayed_path = prefix + &truncated_dir_string;
+    let prefix = truncation_prefix(&truncated_dir_string);
+    let displayed_path = String::from(icon) + &prefix + &truncated_dir_string;
     let lock_symbol = String::from(config.read_only);
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    // Split the path around the repo root, so that each part can be styled separately
+    let repo_path_parts = match &repo.root {
+        Some(repo_root)
+            if config.before_repo_root_style.is_some() || config.repo_root_style.is_some() =>
+        {
+            split_repo_path(&displayed_path, current_dir, repo_root)
+        }
+        _ => None,
+    };
+    let format = if repo_path_parts.is_some() {
+        config.repo_root_format
+    } else {
+        config.format
+    };
+    let (before_root_path, repo_root, path) =
+        repo_path_parts.unwrap_or_else(|| (String::new(), String::new(), displayed_path));
+
+    let parsed = StringFormatter::new(format).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
                 "style" => Some(Ok(config.style)),
+                "before_repo_root_style" => {
+                    Some(Ok(config.before_repo_root_style.unwrap_or(config.style)))
+                }
+                "repo_root_style" => Some(Ok(config.repo_root_style.unwrap_or(config.style))),
                 "read_only_style" => Some(Ok(config.read_only_style)),
                 _ => None,
             })
             .map(|variable| match variable {
-                "path" => Some(Ok(&displayed_path)),
+                "path" => Some(Ok(&path)),
+                "before_root_path" => Some(Ok(&before_root_path)),
+                "repo_root" => Some(Ok(&repo_root)),
                 "read_only" => {
                     if is_readonly_dir(&context.current_dir) {
                         Some(Ok(&lock_symbol))
@@ -224,6 +262,30 @@ fn contract_repo_path(full_path: &Path, top_level_path: &Path) -> Option<String>
     None
 }
 
+/// Split a displayed path into the part before the repo root, the repo root
+/// and the part after it
+///
+/// Returns `None` if the repo root is not part of the displayed path, e.g. because
+/// it was truncated or substituted away.
+fn split_repo_path(
+    displayed_path: &str,
+    current_dir: &Path,
+    repo_root: &Path,
+) -> Option<(String, String, String)> {
+    let repo_path = contract_repo_path(current_dir, repo_root)?;
+    let before_root_path = displayed_path.strip_suffix(&repo_path)?;
+    let (repo_root_name, after_root_path) = match repo_path.find('/') {
+        Some(index) => repo_path.split_at(index),
+        None => (repo_path.as_str(), ""),
+    };
+
+    Some((
+        before_root_path.to_string(),
+        repo_root_name.to_string(),
+        after_root_path.to_string(),
+    ))
+}
+
 fn real_path<P: AsRef<Path>>(path: P) -> PathBuf {
     let path = path.as_ref();
     let mut buf = PathBuf::new();
@@ -254,6 +316,47 @@ fn substitute_path(dir_string: String, substitutions: &IndexMap<String, &str>) -
     substituted_dir
 }
 
+/// Truncate a path to the most components that fit within `max_width` columns
+///
+/// The width of the prefix drawn before each truncated path, like the truncation symbol or
+/// the fish-style path, is taken into account. At least the last path component is always kept.
+fn truncate_to_width<F>(dir_string: String, max_width: usize, prefix: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    let length = dir_string.split('/').filter(|c| !c.is_empty()).count();
+
+    (1..=length)
+        .rev()
+        .map(|len| truncate(dir_string.clone(), len))
+        .find(|truncated| better_width(&prefix(truncated)) + better_width(truncated) <= max_width)
+        .unwrap_or_else(|| truncate(dir_string, 1))
+}
+
+/// Find the icon for the first pattern in `icons` that matches the path
+///
+/// A pattern matches when the path is the pattern itself or one of its
+/// subdirectories. A leading `~` in a pattern is expanded to the home directory.
+fn get_directory_icon<'a>(
+    current_dir: &Path,
+    home_dir: &Path,
+    icons: &IndexMap<String, &'a str>,
+) -> Option<&'a str> {
+    icons.iter().find_map(|(pattern, icon)| {
+        let pattern = Path::new(pattern);
+        let pattern = match pattern.strip_prefix(HOME_SYMBOL) {
+            Ok(without_home) => home_dir.join(without_home),
+            Err(_) => pattern.to_path_buf(),
+        };
+
+        if current_dir.starts_with(&pattern) {
+            Some(*icon)
+        } else {
+            None
+        }
+    })
+}
+
 /// Takes part before contracted path and replaces it with fish style path
 ///
 /// Will take the first letter of each directory before the contracted path and
@@ -293,7 +396,7 @@ fn to_fish_style(pwd_dir_length: usize, dir_string: String, truncated_dir_string
 mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
-    use ansi_term::Color;
+    use ansi_term::{ANSIStrings, Color, Style};
     use dirs_next::home_dir;
     #[cfg(not(target_os = "windows"))]
     use std::os::unix::fs::symlink;
@@ -373,6 +476,65 @@ mod tests {
         assert_eq!(output, "/foo/baz");
     }
 
+    fn symbol_prefix(symbol: &str) -> impl Fn(&str) -> String + '_ {
+        move |truncated| {
+            if is_truncated(truncated) {
+                symbol.to_string()
+            } else {
+                String::new()
+            }
+        }
+    }
+
+    #[test]
+    fn truncate_to_width_keeps_fitting_path() {
+        let path = "~/starship/engines/booster";
+        let output = truncate_to_width(path.to_string(), 26, symbol_prefix(""));
+        assert_eq!(output, "~/starship/engines/booster");
+    }
+
+    #[test]
+    fn truncate_to_width_drops_leading_components() {
+        let path = "~/starship/engines/booster";
+        let output = truncate_to_width(path.to_string(), 24, symbol_prefix(""));
+        assert_eq!(output, "starship/engines/booster");
+
+        let output = truncate_to_width(path.to_string(), 24, symbol_prefix("…/"));
+        assert_eq!(output, "engines/booster");
+    }
+
+    #[test]
+    fn truncate_to_width_keeps_last_component() {
+        let path = "/absolute/path/to/rocket";
+        let output = truncate_to_width(path.to_string(), 2, symbol_prefix(""));
+        assert_eq!(output, "rocket");
+    }
+
+    #[test]
+    fn split_path_around_repo_root() {
+        let current_dir = Path::new("/home/astronaut/src/rocket-controls/src/meters");
+        let repo_root = Path::new("/home/astronaut/src/rocket-controls");
+
+        let output = split_repo_path("~/src/rocket-controls/src/meters", current_dir, repo_root);
+        assert_eq!(
+            output,
+            Some((
+                "~/src/".to_string(),
+                "rocket-controls".to_string(),
+                "/src/meters".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn split_path_with_truncated_repo_root() {
+        let current_dir = Path::new("/home/astronaut/src/rocket-controls/src/meters");
+        let repo_root = Path::new("/home/astronaut/src/rocket-controls");
+
+        let output = split_repo_path("src/meters", current_dir, repo_root);
+        assert_eq!(output, None);
+    }
+
     #[test]
     fn fish_style_with_user_home_contracted_path() {
         let path = "~/starship/engines/booster/rocket";
@@ -623,6 +785,67 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn icon_for_matching_directory() -> io::Result<()> {
+        let actual = ModuleRenderer::new("directory")
+            .path("/path/to/music/album")
+            .config(toml::toml! {
+                [directory.icons]
+                "/path/to/music" = "🎵 "
+                "/path" = "📁 "
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint("🎵 to/music/album")
+        ));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn icon_matches_whole_components() -> io::Result<()> {
+        let actual = ModuleRenderer::new("directory")
+            .path("/path/to/musical")
+            .config(toml::toml! {
+                [directory.icons]
+                "/path/to/music" = "🎵 "
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Cyan.bold().paint("/path/to/musical")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn icon_for_directory_in_home() -> io::Result<()> {
+        let (tmp_dir, name) = make_known_tempdir(home_dir().unwrap().as_path())?;
+        let dir = tmp_dir.path().join("starship");
+        fs::create_dir_all(&dir)?;
+
+        let pattern = format!("~/{}", name);
+        let mut icons = toml::value::Table::new();
+        icons.insert(pattern, toml::Value::from("🚀 "));
+        let mut directory = toml::value::Table::new();
+        directory.insert(String::from("icons"), toml::Value::Table(icons));
+        let mut config = toml::value::Table::new();
+        config.insert(String::from("directory"), toml::Value::Table(directory));
+
+        let actual = ModuleRenderer::new("directory")
+            .path(dir)
+            .config(toml::Value::Table(config))
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint(format!("🚀 ~/{}/starship", name))
+        ));
+
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
     #[test]
     fn strange_substitution() -> io::Result<()> {
         let strange_sub = "/\\/;,!";
@@ -740,6 +963,47 @@ mod tests {
         tmp_dir.close()
     }
 
+    #[test]
+    fn truncated_directory_to_terminal_width() -> io::Result<()> {
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                truncation_width_percentage = 50
+            })
+            .width(42)
+            .path("/some/absolute/path/to/a/ship/engine")
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint("path/to/a/ship/engine")
+        ));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_directory_to_terminal_width_with_fish_style_and_icon() -> io::Result<()> {
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                truncation_width_percentage = 50
+                fish_style_pwd_dir_length = 1
+                [directory.icons]
+                "/some" = "🚀 "
+            })
+            .width(50)
+            .path("/some/absolute/path/to/a/ship/engine")
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint("🚀 /s/a/p/t/a/ship/engine")
+        ));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
     #[test]
     fn truncated_directory_config_large() -> io::Result<()> {
         let (tmp_dir, _) = make_known_tempdir(Path::new("/tmp"))?;
@@ -919,6 +1183,38 @@ mod tests {
         tmp_dir.close()
     }
 
+    #[test]
+    #[ignore]
+    fn directory_in_git_repo_with_repo_root_styles() -> io::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let repo_dir = tmp_dir.path().join("above-repo").join("rocket-controls");
+        let dir = repo_dir.join("src/meters/fuel-gauge");
+        fs::create_dir_all(&dir)?;
+        init_repo(&repo_dir).unwrap();
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                truncation_length = 5
+                truncate_to_repo = false
+                before_repo_root_style = "dimmed"
+                repo_root_style = "green"
+            })
+            .path(dir)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            ANSIStrings(&[
+                Style::new().dimmed().paint("above-repo/"),
+                Color::Green.paint("rocket-controls"),
+                Color::Cyan.bold().paint("/src/meters/fuel-gauge"),
+            ])
+        ));
+
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
     #[test]
     #[ignore]
     fn fish_path_directory_in_git_repo_truncate_to_repo_false() -> io::Result<()> {
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use super::{Context, Module, RootModuleConfig};
 
@@ -12,7 +12,9 @@ use crate::utils;
 ///     - There is a file named `$HOME/.docker/config.json`
 ///     - Or a file named `$DOCKER_CONFIG/config.json`
 ///     - The file is JSON and contains a field named `currentContext`
-///     - The value of `currentContext` is not `default`
+///       (or `DOCKER_CONTEXT` is set, or `DOCKER_HOST` points at a remote engine)
+///     - The current directory contains a file from `detect_files` (e.g. a `Dockerfile`),
+///       or `only_with_files` is false
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("docker_context");
     let config: DockerContextConfig = DockerContextConfig::try_load(module.config);
@@ -20,60 +22,282 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     if config.only_with_files
         && !context
             .try_begin_scan()?
-            .set_files(&["docker-compose.yml", "docker-compose.yaml", "Dockerfile"])
+            .set_files(&config.detect_files)
+            .set_extensions(&config.detect_extensions)
+            .set_folders(&config.detect_folders)
             .is_match()
     {
         return None;
     }
-    let docker_config = PathBuf::from(
+    let docker_config_dir = PathBuf::from(
         &context
             .get_env_os("DOCKER_CONFIG")
             .unwrap_or(dirs_next::home_dir()?.join(".docker").into_os_string()),
-    )
-    .join("config.json");
+    );
 
-    if !docker_config.exists() {
+    let current_context = context
+        .get_env("DOCKER_CONTEXT")
+        .or_else(|| get_current_context(&docker_config_dir));
+
+    // Like the docker CLI, `DOCKER_HOST` takes precedence over the context's endpoint
+    let remote_host = context
+        .get_env("DOCKER_HOST")
+        .or_else(|| get_context_host(&docker_config_dir, current_context.as_deref()?))
+        .and_then(|host| get_remote_host(&host));
+
+    if current_context.is_none() && remote_host.is_none() {
         return None;
     }
+    let ctx = current_context.unwrap_or_else(|| "default".to_string());
 
-    let json = utils::read_file(docker_config).ok()?;
-    let parsed_json = serde_json::from_str(&json).ok()?;
-
-    match parsed_json {
-        serde_json::Value::Object(root) => {
-            let current_context = root.get("currentContext")?;
-            match current_context {
-                serde_json::Value::String(ctx) => {
-                    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
-                        formatter
-                            .map_meta(|variable, _| match variable {
-                                "symbol" => Some(config.symbol),
-                                _ => None,
-                            })
-                            .map_style(|variable| match variable {
-                                "style" => Some(Ok(config.style)),
-                                _ => None,
-                            })
-                            .map(|variable| match variable {
-                                "context" => Some(Ok(ctx)),
-                                _ => None,
-                            })
-                            .parse(None)
-                    });
-
-                    module.set_segments(match parsed {
-                        Ok(segments) => segments,
-                        Err(error) => {
-                            log::warn!("Error in module `docker_context`:\n{}", error);
-                            return None;
-                        }
-                    });
-
-                    Some(module)
-                }
+    let (symbol, style) = if remote_host.is_some() {
+        (config.remote_symbol, config.remote_style)
+    } else {
+        (config.symbol, config.style)
+    };
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(style)),
                 _ => None,
-            }
+            })
+            .map(|variable| match variable {
+                "context" => Some(Ok(ctx.as_str())),
+                "host" => remote_host.as_deref().map(Ok),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `docker_context`:\n{}", error);
+            return None;
         }
-        _ => None,
+    });
+
+    Some(module)
+}
+
+/// Read `currentContext` from `config.json` in the docker config directory
+fn get_current_context(docker_config_dir: &Path) -> Option<String> {
+    let json = utils::read_file(docker_config_dir.join("config.json")).ok()?;
+    let parsed_json: serde_json::Value = serde_json::from_str(&json).ok()?;
+
+    parsed_json
+        .get("currentContext")?
+        .as_str()
+        .map(ToString::to_string)
+}
+
+/// Find the docker endpoint of a context in `contexts/meta/*/meta.json`
+fn get_context_host(docker_config_dir: &Path, context_name: &str) -> Option<String> {
+    let meta_dir = docker_config_dir.join("contexts").join("meta");
+
+    meta_dir.read_dir().ok()?.find_map(|entry| {
+        let json = utils::read_file(entry.ok()?.path().join("meta.json")).ok()?;
+        let meta: serde_json::Value = serde_json::from_str(&json).ok()?;
+        if meta.get("Name")?.as_str()? != context_name {
+            return None;
+        }
+
+        meta.get("Endpoints")?
+            .get("docker")?
+            .get("Host")?
+            .as_str()
+            .map(ToString::to_string)
+    })
+}
+
+/// Extract the hostname of a docker endpoint if it's a remote engine
+///
+/// e.g. `ssh://user@example.com:22` or `tcp://example.com:2376`
+fn get_remote_host(docker_host: &str) -> Option<String> {
+    let address = docker_host
+        .strip_prefix("ssh://")
+        .or_else(|| docker_host.strip_prefix("tcp://"))?;
+    let address = address.split('/').next()?;
+    let address = address.rsplit('@').next()?;
+
+    let host = if address.starts_with('[') {
+        // IPv6 addresses are wrapped in brackets, e.g. `[::1]:2376`
+        address.split(']').next()?.trim_start_matches('[')
+    } else {
+        address.split(':').next()?
+    };
+
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs::{self, File};
+    use std::io::{self, Write};
+
+    fn write_docker_config(dir: &std::path::Path) -> io::Result<()> {
+        let mut file = File::create(dir.join("config.json"))?;
+        file.write_all(br#"{"currentContext": "starship"}"#)?;
+        file.sync_all()
+    }
+
+    #[test]
+    fn only_with_files_and_no_files() -> io::Result<()> {
+        let project_dir = tempfile::tempdir()?;
+        let docker_dir = tempfile::tempdir()?;
+        write_docker_config(docker_dir.path())?;
+
+        let actual = ModuleRenderer::new("docker_context")
+            .path(project_dir.path())
+            .env("DOCKER_CONFIG", docker_dir.path().to_string_lossy())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        project_dir.close()?;
+        docker_dir.close()
+    }
+
+    #[test]
+    fn only_with_files_and_compose_file() -> io::Result<()> {
+        let project_dir = tempfile::tempdir()?;
+        File::create(project_dir.path().join("compose.yaml"))?.sync_all()?;
+        let docker_dir = tempfile::tempdir()?;
+        write_docker_config(docker_dir.path())?;
+
+        let actual = ModuleRenderer::new("docker_context")
+            .path(project_dir.path())
+            .env("DOCKER_CONFIG", docker_dir.path().to_string_lossy())
+            .collect();
+        let expected = Some(format!("via {} ", Color::Blue.bold().paint("🐳 starship")));
+
+        assert_eq!(expected, actual);
+        project_dir.close()?;
+        docker_dir.close()
+    }
+
+    #[test]
+    fn always_shown_without_only_with_files() -> io::Result<()> {
+        let project_dir = tempfile::tempdir()?;
+        let docker_dir = tempfile::tempdir()?;
+        write_docker_config(docker_dir.path())?;
+
+        let actual = ModuleRenderer::new("docker_context")
+            .path(project_dir.path())
+            .env("DOCKER_CONFIG", docker_dir.path().to_string_lossy())
+            .config(toml::toml! {
+                [docker_context]
+                only_with_files = false
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Blue.bold().paint("🐳 starship")));
+
+        assert_eq!(expected, actual);
+        project_dir.close()?;
+        docker_dir.close()
+    }
+
+    #[test]
+    fn remote_docker_host() -> io::Result<()> {
+        let project_dir = tempfile::tempdir()?;
+        let docker_dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("docker_context")
+            .path(project_dir.path())
+            .env("DOCKER_CONFIG", docker_dir.path().to_string_lossy())
+            .env("DOCKER_HOST", "ssh://deploy@prod.example.com:22")
+            .config(toml::toml! {
+                [docker_context]
+                only_with_files = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("🐳 default @ prod.example.com")
+        ));
+
+        assert_eq!(expected, actual);
+        project_dir.close()?;
+        docker_dir.close()
+    }
+
+    #[test]
+    fn local_docker_host() -> io::Result<()> {
+        let project_dir = tempfile::tempdir()?;
+        let docker_dir = tempfile::tempdir()?;
+        write_docker_config(docker_dir.path())?;
+
+        let actual = ModuleRenderer::new("docker_context")
+            .path(project_dir.path())
+            .env("DOCKER_CONFIG", docker_dir.path().to_string_lossy())
+            .env("DOCKER_HOST", "unix:///var/run/docker.sock")
+            .config(toml::toml! {
+                [docker_context]
+                only_with_files = false
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Blue.bold().paint("🐳 starship")));
+
+        assert_eq!(expected, actual);
+        project_dir.close()?;
+        docker_dir.close()
+    }
+
+    #[test]
+    fn remote_context_endpoint() -> io::Result<()> {
+        let project_dir = tempfile::tempdir()?;
+        let docker_dir = tempfile::tempdir()?;
+        write_docker_config(docker_dir.path())?;
+        let meta_dir = docker_dir.path().join("contexts/meta/0123456789abcdef");
+        fs::create_dir_all(&meta_dir)?;
+        let mut meta_file = File::create(meta_dir.join("meta.json"))?;
+        meta_file.write_all(
+            br#"{"Name": "starship", "Endpoints": {"docker": {"Host": "tcp://10.0.0.1:2376"}}}"#,
+        )?;
+        meta_file.sync_all()?;
+
+        let actual = ModuleRenderer::new("docker_context")
+            .path(project_dir.path())
+            .env("DOCKER_CONFIG", docker_dir.path().to_string_lossy())
+            .config(toml::toml! {
+                [docker_context]
+                only_with_files = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("🐳 starship @ 10.0.0.1")
+        ));
+
+        assert_eq!(expected, actual);
+        project_dir.close()?;
+        docker_dir.close()
+    }
+
+    #[test]
+    fn test_get_remote_host() {
+        assert_eq!(
+            get_remote_host("ssh://user@example.com:22"),
+            Some("example.com".to_string())
+        );
+        assert_eq!(
+            get_remote_host("tcp://example.com"),
+            Some("example.com".to_string())
+        );
+        assert_eq!(get_remote_host("tcp://[::1]:2376"), Some("::1".to_string()));
+        assert_eq!(get_remote_host("unix:///var/run/docker.sock"), None);
+        assert_eq!(get_remote_host("npipe:////./pipe/docker_engine"), None);
     }
 }
@@ -1,3 +1,4 @@
+use once_cell::sync::Lazy;
 use quick_xml::events::Event;
 use quick_xml::Reader;
 use std::ffi::OsStr;
@@ -5,19 +6,18 @@ use std::iter::Iterator;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::str;
+use std::time::Duration;
 
 use super::{Context, Module, RootModuleConfig};
 use crate::configs::dotnet::DotnetConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 type JValue = serde_json::Value;
 
 const GLOBAL_JSON_FILE: &str = "global.json";
 const PROJECT_JSON_FILE: &str = "project.json";
-const DIRECTORY_BUILD_PROPS_FILE: &str = "Directory.Build.props";
-const DIRECTORY_BUILD_TARGETS_FILE: &str = "Directory.Build.targets";
-const PACKAGES_PROPS_FILE: &str = "Packages.props";
+const CLI_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// A module which shows the latest (or pinned) version of the dotnet SDK
 ///
@@ -27,16 +27,14 @@ const PACKAGES_PROPS_FILE: &str = "Packages.props";
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     // First check if this is a DotNet Project before doing the O(n)
     // check for the version using the JSON files
+    let mut module = context.new_module("dotnet");
+    let config = DotnetConfig::try_load(module.config);
+
     let is_dotnet_project = context
         .try_begin_scan()?
-        .set_files(&[
-            GLOBAL_JSON_FILE,
-            PROJECT_JSON_FILE,
-            DIRECTORY_BUILD_PROPS_FILE,
-            DIRECTORY_BUILD_TARGETS_FILE,
-            PACKAGES_PROPS_FILE,
-        ])
-        .set_extensions(&["sln", "csproj", "fsproj", "xproj"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_dotnet_project {
@@ -45,17 +43,57 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     let dotnet_files = get_local_dotnet_files(context).ok()?;
 
-    let mut module = context.new_module("dotnet");
-    let config = DotnetConfig::try_load(module.config);
-
     // Internally, this module uses its own mechanism for version detection.
     // Typically it is twice as fast as running `dotnet --version`.
     let enable_heuristic = config.heuristic;
 
+    let repo_root = context.get_repo().ok().and_then(|r| r.root.as_deref());
+    let sdk_pin = find_sdk_pin(&dotnet_files, &context.current_dir, repo_root);
+
+    // Without the heuristic, the version is `dotnet --version`, and the SDKs are only listed
+    // when there is a global.json to satisfy. When both are needed the commands run at once,
+    // since each of them pays for the slow startup of the dotnet cli.
+    let cli_outputs = Lazy::new(|| {
+        let mut commands: Vec<(&str, &[&str])> = Vec::new();
+        if !enable_heuristic {
+            commands.push(("dotnet", &["--version"]));
+        }
+        if sdk_pin.is_some() {
+            commands.push(("dotnet", &["--list-sdks"]));
+        }
+        let mut outputs = utils::exec_cmds(&commands, CLI_TIMEOUT).into_iter();
+
+        let version = match enable_heuristic {
+            true => None,
+            false => outputs
+                .next()
+                .flatten()
+                .map(|output| Version(output.stdout.trim().to_string())),
+        };
+        let sdks = outputs
+            .next()
+            .flatten()
+            .map(|output| parse_installed_sdks(&output.stdout));
+        (version, sdks)
+    });
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => {
+                    let is_capable = match &sdk_pin {
+                        Some(sdk_pin) => match &cli_outputs.1 {
+                            Some(sdks) => sdks.iter().any(|sdk| sdk_pin.accepts(sdk)),
+                            None => true,
+                        },
+                        None => true,
+                    };
+                    if is_capable {
+                        Some(Ok(config.style))
+                    } else {
+                        Some(Ok(config.not_capable_style))
+                    }
+                }
                 _ => None,
             })
             .map(|variable| match variable {
@@ -65,13 +103,28 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             .map(|variable| match variable {
                 "version" => {
                     let version = if enable_heuristic {
-                        let repo_root = context.get_repo().ok().and_then(|r| r.root.as_deref());
                         estimate_dotnet_version(&dotnet_files, &context.current_dir, repo_root)
                     } else {
-                        get_version_from_cli()
+                        cli_outputs
+                            .0
+                            .as_ref()
+                            .map(|version| Version(version.0.clone()))
                     };
-                    version.map(|v| Ok(v.0))
+                    VersionFormatter::format_module_version(
+                        context,
+                        module.get_name(),
+                        &version?.0,
+                        config.version_format,
+                    )
+                    .map(Ok)
                 }
+                "pinned_version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    &sdk_pin.as_ref()?.version,
+                    config.version_format,
+                )
+                .map(Ok),
                 "tfm" => find_current_tfm(&dotnet_files).map(Ok),
                 _ => None,
             })
@@ -174,6 +227,18 @@ fn estimate_dotnet_version(
 ///     - The root of the git repository
 ///       (If there is one)
 fn try_find_nearby_global_json(current_dir: &Path, repo_root: Option<&Path>) -> Option<Version> {
+    nearby_global_json_dirs(current_dir, repo_root)
+        .iter()
+        .filter_map(|d| check_directory_for_global_json(d))
+        // This will lazily evaluate the first directory with a global.json
+        .next()
+}
+
+/// The directories that `try_find_nearby_global_json` scans
+fn nearby_global_json_dirs<'a>(
+    current_dir: &'a Path,
+    repo_root: Option<&'a Path>,
+) -> Vec<&'a Path> {
     let current_dir_is_repo_root = repo_root.map(|r| r == current_dir).unwrap_or(false);
     let parent_dir = if current_dir_is_repo_root {
         // Don't scan the parent directory if it's above the root of a git repository
@@ -193,13 +258,9 @@ fn try_find_nearby_global_json(current_dir: &Path, repo_root: Option<&Path>) ->
     // so avoid checking it twice.
     check_dirs.dedup();
 
+    // repo_root may be the same as the current directory. We don't need to scan it again.
+    check_dirs.retain(|&d| d != current_dir);
     check_dirs
-        .iter()
-        // repo_root may be the same as the current directory. We don't need to scan it again.
-        .filter(|&&d| d != current_dir)
-        .filter_map(|d| check_directory_for_global_json(d))
-        // This will lazily evaluate the first directory with a global.json
-        .next()
 }
 
 fn check_directory_for_global_json(path: &Path) -> Option<Version> {
@@ -234,12 +295,7 @@ fn get_pinned_sdk_version(json: &str) -> Option<Version> {
                 JValue::Object(sdk) => {
                     let version = sdk.get("version")?;
                     match version {
-                        JValue::String(version_string) => {
-                            let mut buffer = String::with_capacity(version_string.len() + 1);
-                            buffer.push('v');
-                            buffer.push_str(version_string);
-                            Some(Version(buffer))
-                        }
+                        JValue::String(version_string) => Some(Version(version_string.to_string())),
                         _ => None,
                     }
                 }
@@ -250,6 +306,42 @@ fn get_pinned_sdk_version(json: &str) -> Option<Version> {
     }
 }
 
+/// The SDK pinned by the `global.json` that `dotnet` uses in the current directory
+fn find_sdk_pin(
+    files: &[DotNetFile],
+    current_dir: &Path,
+    repo_root: Option<&Path>,
+) -> Option<SdkPin> {
+    let local_global_json = files
+        .iter()
+        .find(|f| f.file_type == FileType::GlobalJson)
+        .map(|f| f.path.clone());
+
+    local_global_json
+        .into_iter()
+        .chain(
+            nearby_global_json_dirs(current_dir, repo_root)
+                .iter()
+                .map(|d| d.join(GLOBAL_JSON_FILE)),
+        )
+        .find_map(|path| get_sdk_pin(&utils::read_file(path).ok()?))
+}
+
+fn get_sdk_pin(json: &str) -> Option<SdkPin> {
+    let parsed_json: JValue = serde_json::from_str(json).ok()?;
+    let sdk = parsed_json.get("sdk")?;
+
+    Some(SdkPin {
+        version: sdk.get("version")?.as_str()?.to_string(),
+        // This is the default policy when a version is pinned
+        roll_forward: sdk
+            .get("rollForward")
+            .and_then(JValue::as_str)
+            .unwrap_or("latestPatch")
+            .to_string(),
+    })
+}
+
 fn get_local_dotnet_files(context: &Context) -> Result<Vec<DotNetFile>, std::io::Error> {
     Ok(context
         .dir_contents()?
@@ -290,7 +382,16 @@ fn map_str_to_lower(value: Option<&OsStr>) -> Option<String> {
 
 fn get_version_from_cli() -> Option<Version> {
     let version_output = utils::exec_cmd("dotnet", &["--version"])?;
-    Some(Version(format!("v{}", version_output.stdout.trim())))
+    Some(Version(version_output.stdout.trim().to_string()))
+}
+
+/// All the SDKs listed by `dotnet --list-sdks`, e.g. `3.1.103 [/usr/share/dotnet/sdk]`
+fn parse_installed_sdks(sdks_output: &str) -> Vec<String> {
+    sdks_output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
 }
 
 fn get_latest_sdk_from_cli() -> Option<Version> {
@@ -310,10 +411,7 @@ fn get_latest_sdk_from_cli() -> Option<Version> {
             let take_until = latest_sdk.find('[').or_else(parse_failed)? - 1;
             if take_until > 1 {
                 let version = &latest_sdk[..take_until];
-                let mut buffer = String::with_capacity(version.len() + 1);
-                buffer.push('v');
-                buffer.push_str(version);
-                Some(Version(buffer))
+                Some(Version(version.to_string()))
             } else {
                 parse_failed()
             }
@@ -346,6 +444,47 @@ enum FileType {
 
 struct Version(String);
 
+/// The `sdk` section of a `global.json`
+struct SdkPin {
+    version: String,
+    roll_forward: String,
+}
+
+impl SdkPin {
+    /// Whether the `rollForward` policy allows an installed SDK to be used
+    ///
+    /// SDK versions like `3.1.103` have a feature band (`1`) and a patch (`03`) in their last
+    /// component. Unknown policies accept any SDK.
+    fn accepts(&self, sdk: &str) -> bool {
+        let parse = |version: &str| -> Option<(u64, u64, u64, u64)> {
+            let mut components = version.split('-').next()?.split('.');
+            let major = components.next()?.parse().ok()?;
+            let minor = components.next()?.parse().ok()?;
+            let patch: u64 = components.next()?.parse().ok()?;
+            Some((major, minor, patch / 100, patch % 100))
+        };
+        let (pinned, installed) = match (parse(&self.version), parse(sdk)) {
+            (Some(pinned), Some(installed)) => (pinned, installed),
+            _ => return false,
+        };
+
+        let (major, minor, band, _) = pinned;
+        match self.roll_forward.as_str() {
+            "disable" => installed == pinned,
+            "patch" | "latestPatch" => {
+                (installed.0, installed.1, installed.2) == (major, minor, band)
+                    && installed >= pinned
+            }
+            "feature" | "latestFeature" => {
+                (installed.0, installed.1) == (major, minor) && installed >= pinned
+            }
+            "minor" | "latestMinor" => installed.0 == major && installed >= pinned,
+            "major" | "latestMajor" => installed >= pinned,
+            _ => true,
+        }
+    }
+}
+
 impl Deref for Version {
     type Target = String;
     fn deref(&self) -> &Self::Target {
@@ -465,11 +604,11 @@ mod tests {
     #[test]
     fn shows_pinned_in_directory_with_global_json() -> io::Result<()> {
         let workspace = create_workspace(false)?;
-        let global_json = make_pinned_sdk_json("1.2.3");
+        let global_json = make_pinned_sdk_json("3.1.100");
         touch_path(&workspace, "global.json", Some(&global_json))?;
         expect_output(
             &workspace.path(),
-            Some(format!("{} ", Color::Blue.bold().paint("•NET v1.2.3"))),
+            Some(format!("{} ", Color::Blue.bold().paint("•NET v3.1.100"))),
         )?;
         workspace.close()
     }
@@ -477,7 +616,7 @@ mod tests {
     #[test]
     fn shows_pinned_in_project_below_root_with_global_json() -> io::Result<()> {
         let workspace = create_workspace(false)?;
-        let global_json = make_pinned_sdk_json("1.2.3");
+        let global_json = make_pinned_sdk_json("3.1.100");
         let csproj = make_csproj_with_tfm("TargetFramework", "netstandard2.0");
         touch_path(&workspace, "global.json", Some(&global_json))?;
         touch_path(&workspace, "project/project.csproj", Some(&csproj))?;
@@ -485,7 +624,7 @@ mod tests {
             &workspace.path().join("project"),
             Some(format!(
                 "{} ",
-                Color::Blue.bold().paint("•NET v1.2.3 🎯 netstandard2.0")
+                Color::Blue.bold().paint("•NET v3.1.100 🎯 netstandard2.0")
             )),
         )?;
         workspace.close()
@@ -494,7 +633,7 @@ mod tests {
     #[test]
     fn shows_pinned_in_deeply_nested_project_within_repository() -> io::Result<()> {
         let workspace = create_workspace(true)?;
-        let global_json = make_pinned_sdk_json("1.2.3");
+        let global_json = make_pinned_sdk_json("3.1.100");
         let csproj = make_csproj_with_tfm("TargetFramework", "netstandard2.0");
         touch_path(&workspace, "global.json", Some(&global_json))?;
         touch_path(
@@ -506,7 +645,7 @@ mod tests {
             &workspace.path().join("deep/path/to/project"),
             Some(format!(
                 "{} ",
-                Color::Blue.bold().paint("•NET v1.2.3 🎯 netstandard2.0")
+                Color::Blue.bold().paint("•NET v3.1.100 🎯 netstandard2.0")
             )),
         )?;
         workspace.close()
@@ -621,7 +760,60 @@ mod tests {
     "#;
 
         let version = get_pinned_sdk_version(json_text).unwrap();
-        assert_eq!("v1.2.3", version.0);
+        assert_eq!("1.2.3", version.0);
+    }
+
+    #[test]
+    fn shows_pinned_with_incompatible_sdks() -> io::Result<()> {
+        let workspace = create_workspace(false)?;
+        let global_json = make_pinned_sdk_json("1.2.3");
+        touch_path(&workspace, "global.json", Some(&global_json))?;
+        touch_path(&workspace, "project/project.fsproj", None)?;
+
+        let actual = ModuleRenderer::new("dotnet")
+            .path(workspace.path().join("project"))
+            .config(toml::toml! {
+                [dotnet]
+                heuristic = false
+                format = "[$symbol$version( pinned $pinned_version)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Red.bold().paint("•NET v3.1.103 pinned v1.2.3")
+        ));
+        assert_eq!(actual, expected);
+        workspace.close()
+    }
+
+    #[test]
+    fn shows_pinned_with_compatible_sdk() -> io::Result<()> {
+        let workspace = create_workspace(false)?;
+        let global_json = r#"{ "sdk": { "version": "3.1.100", "rollForward": "latestFeature" } }"#;
+        touch_path(&workspace, "global.json", Some(global_json))?;
+        expect_output(
+            &workspace.path(),
+            Some(format!("{} ", Color::Blue.bold().paint("•NET v3.1.100"))),
+        )?;
+        workspace.close()
+    }
+
+    #[test]
+    fn should_apply_roll_forward_policy() {
+        let pin = |roll_forward: &str| SdkPin {
+            version: "3.1.201".to_string(),
+            roll_forward: roll_forward.to_string(),
+        };
+        assert!(pin("latestPatch").accepts("3.1.205"));
+        assert!(!pin("latestPatch").accepts("3.1.301"));
+        assert!(!pin("latestPatch").accepts("3.1.200"));
+        assert!(pin("latestFeature").accepts("3.1.301"));
+        assert!(!pin("feature").accepts("3.2.100"));
+        assert!(pin("minor").accepts("3.2.100"));
+        assert!(!pin("latestMinor").accepts("5.0.100"));
+        assert!(pin("latestMajor").accepts("5.0.100-preview.1"));
+        assert!(!pin("disable").accepts("3.1.202"));
+        assert!(pin("disable").accepts("3.1.201"));
     }
 
     #[test]
@@ -1,7 +1,10 @@
+use super::utils::pinned_version::{
+    find_tool_versions, is_older_than, parse_tool_versions, satisfies_pin,
+};
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::elixir::ElixirConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 use regex::Regex;
@@ -9,22 +12,34 @@ const ELIXIR_VERSION_PATTERN: &str = "\
 Erlang/OTP (?P<otp>\\d+)[^\\n]+
 
 Elixir (?P<elixir>\\d[.\\d]+).*";
+const MIX_ELIXIR_REQUIREMENT_PATTERN: &str = r#"elixir:\s*"(?P<requirement>[^"]+)""#;
 
 /// Create a module with the current Elixir version
 ///
 /// Will display the Elixir version if any of the following criteria are met:
 ///     - Current directory contains a `mix.exs` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_elixir_project = context.try_begin_scan()?.set_files(&["mix.exs"]).is_match();
+    let mut module = context.new_module("elixir");
+    let config = ElixirConfig::try_load(module.config);
+
+    let is_elixir_project = context
+        .try_begin_scan()?
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
+        .is_match();
 
     if !is_elixir_project {
         return None;
     }
 
     let (otp_version, elixir_version) = get_elixir_version()?;
+    let (pinned_elixir, pinned_otp) = get_pinned_versions(context);
+    let is_pin_satisfied = pinned_elixir
+        .iter()
+        .all(|pin| satisfies_elixir_pin(&elixir_version, pin))
+        && pinned_otp.iter().all(|pin| *pin == otp_version);
 
-    let mut module = context.new_module("elixir");
-    let config = ElixirConfig::try_load(module.config);
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -32,12 +47,26 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => {
+                    if is_pin_satisfied {
+                        Some(Ok(config.style))
+                    } else {
+                        Some(Ok(config.not_capable_style))
+                    }
+                }
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(&elixir_version)),
-                "otp_version" => Some(Ok(&otp_version)),
+                "version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    &elixir_version,
+                    config.version_format,
+                )
+                .map(Ok),
+                "otp_version" => Some(Ok(otp_version.clone())),
+                "pinned_elixir" => pinned_elixir.clone().map(Ok),
+                "pinned_otp" => pinned_otp.clone().map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -60,6 +89,88 @@ fn get_elixir_version() -> Option<(String, String)> {
     parse_elixir_version(&output)
 }
 
+/// The Elixir and OTP versions pinned by `.tool-versions`, or the Elixir requirement of `mix.exs`
+///
+/// asdf pins Elixir builds for an OTP release like `1.10.3-otp-22`, and Erlang releases like
+/// `22.3.4`, of which only the OTP major version is compared.
+fn get_pinned_versions(context: &Context) -> (Option<String>, Option<String>) {
+    let tool_versions = find_tool_versions(context).unwrap_or_default();
+    let tools = parse_tool_versions(&tool_versions);
+    let get_tool = |name: &str| {
+        tools
+            .iter()
+            .find(|(tool, _)| *tool == name)
+            .map(|(_, version)| *version)
+    };
+
+    let (pinned_elixir, elixir_otp) = match get_tool("elixir") {
+        Some(version) => match version.split_once("-otp-") {
+            Some((elixir, otp)) => (Some(elixir.to_string()), Some(otp.to_string())),
+            None => (Some(version.to_string()), None),
+        },
+        None => (get_mix_elixir_requirement(context), None),
+    };
+    let pinned_otp = get_tool("erlang")
+        .and_then(|version| version.split('.').next())
+        .map(str::to_string)
+        .or(elixir_otp);
+
+    (pinned_elixir, pinned_otp)
+}
+
+fn get_mix_elixir_requirement(context: &Context) -> Option<String> {
+    let mix_exs = utils::read_file(context.current_dir.join("mix.exs")).ok()?;
+    let re = Regex::new(MIX_ELIXIR_REQUIREMENT_PATTERN).ok()?;
+    let captures = re.captures(&mix_exs)?;
+    Some(captures["requirement"].to_string())
+}
+
+/// Whether the Elixir version satisfies a pinned version like `1.10.3` or a requirement like
+/// `~> 1.10 or >= 1.12.0-rc.0`
+fn satisfies_elixir_pin(elixir_version: &str, pin: &str) -> bool {
+    if pin.starts_with(|c: char| c.is_ascii_digit()) {
+        return satisfies_pin(elixir_version, pin) || satisfies_pin(pin, elixir_version);
+    }
+
+    pin.split(" or ").any(|alternative| {
+        alternative.split(" and ").all(|clause| {
+            let clause = clause.trim();
+            let split = clause.find(|c: char| c.is_ascii_digit()).unwrap_or(0);
+            let (operator, version) = clause.split_at(split);
+            match operator.trim() {
+                "~>" => {
+                    let mut components: Vec<u64> = version
+                        .split('-')
+                        .next()
+                        .unwrap_or_default()
+                        .split('.')
+                        .filter_map(|component| component.parse().ok())
+                        .collect();
+                    // `~> 1.10` allows anything below `2.0`, and `~> 1.10.2` below `1.11.0`
+                    components.truncate(components.len().saturating_sub(1).max(1));
+                    if let Some(last) = components.last_mut() {
+                        *last += 1;
+                    }
+                    let upper_bound: Vec<String> = components.iter().map(u64::to_string).collect();
+                    !is_older_than(elixir_version, version)
+                        && is_older_than(elixir_version, &upper_bound.join("."))
+                }
+                ">=" => !is_older_than(elixir_version, version),
+                ">" => is_older_than(version, elixir_version),
+                "<=" => !is_older_than(version, elixir_version),
+                "<" => is_older_than(elixir_version, version),
+                "!=" => {
+                    is_older_than(elixir_version, version) || is_older_than(version, elixir_version)
+                }
+                _ => {
+                    !is_older_than(elixir_version, version)
+                        && !is_older_than(version, elixir_version)
+                }
+            }
+        })
+    })
+}
+
 fn parse_elixir_version(version: &str) -> Option<(String, String)> {
     let version_regex = Regex::new(ELIXIR_VERSION_PATTERN).ok()?;
     let captures = version_regex.captures(version)?;
@@ -75,7 +186,7 @@ mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io;
 
     #[test]
@@ -111,7 +222,7 @@ Elixir 1.10 (compiled with Erlang/OTP 22)
 
         let expected = Some(format!(
             "via {} ",
-            Color::Purple.bold().paint("💧 1.10 (OTP 22)")
+            Color::Purple.bold().paint("💧 v1.10 (OTP 22)")
         ));
         let output = ModuleRenderer::new("elixir").path(dir.path()).collect();
 
@@ -119,4 +230,82 @@ Elixir 1.10 (compiled with Erlang/OTP 22)
 
         dir.close()
     }
+
+    #[test]
+    fn test_with_mix_requirement() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("mix.exs"),
+            "defmodule Hello.MixProject do\n  def project do\n    [app: :hello, elixir: \"~> 1.11\"]\n  end\nend\n",
+        )?;
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red
+                .bold()
+                .paint("💧 v1.10 (OTP 22) requires ~> 1.11")
+        ));
+        let output = ModuleRenderer::new("elixir")
+            .path(dir.path())
+            .config(toml::toml! {
+                [elixir]
+                format = "via [$symbol$version \\(OTP $otp_version\\)( requires $pinned_elixir)]($style) "
+            })
+            .collect();
+
+        assert_eq!(output, expected);
+
+        dir.close()
+    }
+
+    #[test]
+    fn test_with_tool_versions() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("mix.exs"))?.sync_all()?;
+        fs::write(
+            dir.path().join(".tool-versions"),
+            "erlang 22.3.4\nelixir 1.10-otp-22\n",
+        )?;
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Purple.bold().paint("💧 v1.10 (OTP 22)")
+        ));
+        let output = ModuleRenderer::new("elixir").path(dir.path()).collect();
+        assert_eq!(output, expected);
+
+        fs::write(dir.path().join(".tool-versions"), "erlang 23.0\n")?;
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("💧 v1.10 (OTP 22, pinned 23)")
+        ));
+        let output = ModuleRenderer::new("elixir")
+            .path(dir.path())
+            .config(toml::toml! {
+                [elixir]
+                format = "via [$symbol$version \\(OTP $otp_version(, pinned $pinned_otp)\\)]($style) "
+            })
+            .collect();
+        assert_eq!(output, expected);
+
+        dir.close()
+    }
+
+    #[test]
+    fn test_satisfies_elixir_pin() {
+        assert!(satisfies_elixir_pin("1.10.3", "1.10"));
+        assert!(!satisfies_elixir_pin("1.10.3", "1.11.0"));
+        assert!(satisfies_elixir_pin("1.12.1", "~> 1.10"));
+        assert!(!satisfies_elixir_pin("2.0.0", "~> 1.10"));
+        assert!(satisfies_elixir_pin("1.10.4", "~> 1.10.2"));
+        assert!(!satisfies_elixir_pin("1.11.0", "~> 1.10.2"));
+        assert!(satisfies_elixir_pin(
+            "1.9.0",
+            "~> 1.6 and < 1.10 or >= 1.12.0"
+        ));
+        assert!(!satisfies_elixir_pin(
+            "1.10.0",
+            "~> 1.6 and < 1.10 or >= 1.12.0"
+        ));
+    }
 }
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::elm::ElmConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Elm version
@@ -13,11 +13,14 @@ use crate::utils;
 ///     - The current directory contains a `elm-stuff` folder
 ///     - The current directory contains a `*.elm` files
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("elm");
+    let config: ElmConfig = ElmConfig::try_load(module.config);
+
     let is_elm_project = context
         .try_begin_scan()?
-        .set_files(&["elm.json", "elm-package.json", ".elm-version"])
-        .set_extensions(&["elm"])
-        .set_folders(&["elm-stuff"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_elm_project {
@@ -25,10 +28,6 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 
     let elm_version = utils::exec_cmd("elm", &["--version"])?.stdout;
-    let module_version = Some(format!("v{}", elm_version.trim()))?;
-
-    let mut module = context.new_module("elm");
-    let config: ElmConfig = ElmConfig::try_load(module.config);
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -41,7 +40,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(&module_version)),
+                "version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    elm_version.trim(),
+                    config.version_format,
+                )
+                .map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -1,4 +1,6 @@
 use super::{Context, Module};
+use regex::Regex;
+use std::collections::HashMap;
 
 use crate::config::RootModuleConfig;
 use crate::configs::env_var::EnvVarConfig;
@@ -10,11 +12,35 @@ use crate::formatter::StringFormatter;
 ///     - env_var.disabled is absent or false
 ///     - env_var.variable is defined
 ///     - a variable named as the value of env_var.variable is defined
+///     - env_var.regex is absent or matches the value of the variable
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("env_var");
     let config: EnvVarConfig = EnvVarConfig::try_load(module.config);
 
-    let env_value = get_env_value(context, config.variable?, config.default)?;
+    let variable = config.variable?;
+    let (env_value, groups) = match config.regex {
+        Some(regex) => {
+            let regex = match Regex::new(regex) {
+                Ok(regex) => regex,
+                Err(error) => {
+                    log::warn!("Invalid regex in [env_var]:\n{}", error);
+                    return None;
+                }
+            };
+            let matched = context
+                .get_env(variable)
+                .and_then(|value| extract_with_regex(&value, &regex));
+            match matched {
+                Some(matched) => matched,
+                None => (config.default?.to_owned(), HashMap::new()),
+            }
+        }
+        None => (
+            get_env_value(context, variable, config.default)?,
+            HashMap::new(),
+        ),
+    };
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -26,8 +52,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "env_value" => Some(Ok(&env_value)),
-                _ => None,
+                "env_value" => Some(Ok(env_value.as_str())),
+                group => groups.get(group).map(|value| Ok(value.as_str())),
             })
             .parse(None)
     });
@@ -50,6 +76,30 @@ fn get_env_value(context: &Context, name: &str, default: Option<&str>) -> Option
     }
 }
 
+/// Extract the part of `value` matched by `regex`, along with its capture groups
+///
+/// The extracted part is the first capture group if the regex has one, and
+/// otherwise the whole match. Capture groups are keyed by both their index and
+/// their name, with unmatched optional groups being empty.
+fn extract_with_regex(value: &str, regex: &Regex) -> Option<(String, HashMap<String, String>)> {
+    let captures = regex.captures(value)?;
+    let extracted = captures.get(1).or_else(|| captures.get(0))?.as_str();
+
+    let mut groups = HashMap::new();
+    for (index, name) in regex.capture_names().enumerate().skip(1) {
+        let group = captures
+            .get(index)
+            .map(|group| group.as_str().to_owned())
+            .unwrap_or_default();
+        if let Some(name) = name {
+            groups.insert(name.to_owned(), group.clone());
+        }
+        groups.insert(index.to_string(), group);
+    }
+
+    Some((extracted.to_owned(), groups))
+}
+
 #[cfg(test)]
 mod test {
     use crate::test::ModuleRenderer;
@@ -188,6 +238,72 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn regex_extracts_first_group() -> io::Result<()> {
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var]
+                variable = "TEST_VAR"
+                regex = "([A-Z]+-[0-9]+)"
+            })
+            .env("TEST_VAR", "feature/PROJ-1234-add-the-thing")
+            .collect();
+        let expected = Some(format!("with {} ", style().paint("PROJ-1234")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn regex_groups_as_variables() -> io::Result<()> {
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var]
+                variable = "TEST_VAR"
+                regex = "(?P<project>[A-Z]+)-([0-9]+)"
+                format = "[$project #${2}]($style) "
+            })
+            .env("TEST_VAR", "feature/PROJ-1234-add-the-thing")
+            .collect();
+        let expected = Some(format!("{} ", style().paint("PROJ #1234")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn regex_without_match_uses_default() -> io::Result<()> {
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var]
+                variable = "TEST_VAR"
+                regex = "[A-Z]+-[0-9]+"
+                default = "no ticket"
+            })
+            .env("TEST_VAR", TEST_VAR_VALUE)
+            .collect();
+        let expected = Some(format!("with {} ", style().paint("no ticket")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn regex_without_match() -> io::Result<()> {
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var]
+                variable = "TEST_VAR"
+                regex = "[A-Z]+-[0-9]+"
+            })
+            .env("TEST_VAR", TEST_VAR_VALUE)
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
     fn style() -> Style {
         // default style
         Color::Black.bold().dimmed()
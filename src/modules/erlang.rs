@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::erlang::ErlangConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 
 /// Create a module with the current Erlang version
 ///
@@ -9,18 +9,20 @@ use crate::formatter::StringFormatter;
 ///     - Current directory contains a rebar.config file
 ///     - Current directory contains a erlang.mk file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("erlang");
+    let config = ErlangConfig::try_load(module.config);
+
     let is_erlang_project = context
         .try_begin_scan()?
-        .set_files(&["rebar.config", "erlang.mk"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_erlang_project {
         return None;
     }
 
-    let mut module = context.new_module("erlang");
-    let config = ErlangConfig::try_load(module.config);
-
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
@@ -32,7 +34,16 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => get_erlang_version().map(Ok),
+                "version" => {
+                    let erlang_version = get_erlang_version()?;
+                    VersionFormatter::format_module_version(
+                        context,
+                        module.get_name(),
+                        &erlang_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
                 _ => None,
             })
             .parse(None)
@@ -89,7 +100,7 @@ mod tests {
         let dir = tempfile::tempdir()?;
         File::create(dir.path().join("rebar.config"))?.sync_all()?;
 
-        let expected = Some(format!("via {} ", Color::Red.bold().paint(" 22.1.3")));
+        let expected = Some(format!("via {} ", Color::Red.bold().paint(" v22.1.3")));
         let output = ModuleRenderer::new("erlang").path(dir.path()).collect();
 
         assert_eq!(output, expected);
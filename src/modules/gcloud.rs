@@ -9,48 +9,37 @@ use super::{Context, Module, RootModuleConfig};
 use crate::configs::gcloud::GcloudConfig;
 use crate::formatter::StringFormatter;
 
-type Account = String;
-type Project = String;
-type Region = String;
 type Active = String;
 
-fn get_gcloud_account_from_config(current_config: &Path) -> Option<Account> {
-    let file = File::open(&current_config).ok()?;
-    let reader = BufReader::new(file);
-    let lines = reader.lines().filter_map(Result::ok);
-    let account_line = lines
-        .skip_while(|line| line != "[core]")
-        .skip(1)
-        .take_while(|line| !line.starts_with('['))
-        .find(|line| line.starts_with("account"))?;
-    let account = account_line.split('=').nth(1)?.trim();
-    Some(account.to_string())
-}
-
-fn get_gcloud_project_from_config(current_config: &Path) -> Option<Project> {
-    let file = File::open(&current_config).ok()?;
-    let reader = BufReader::new(file);
-    let lines = reader.lines().filter_map(Result::ok);
-    let project_line = lines
-        .skip_while(|line| line != "[core]")
-        .skip(1)
-        .take_while(|line| !line.starts_with('['))
-        .find(|line| line.starts_with("project"))?;
-    let project = project_line.split('=').nth(1)?.trim();
-    Some(project.to_string())
-}
+/// Read a property like `core/project` of the current configuration
+///
+/// Like gcloud, a `CLOUDSDK_SECTION_PROPERTY` env var overrides the configuration file.
+fn get_gcloud_property(
+    context: &Context,
+    current_config: &Path,
+    section: &str,
+    property: &str,
+) -> Option<String> {
+    let env_var = format!(
+        "CLOUDSDK_{}_{}",
+        section.to_uppercase(),
+        property.to_uppercase()
+    );
+    if let Some(value) = context.get_env(&env_var) {
+        return Some(value);
+    }
 
-fn get_gcloud_region_from_config(current_config: &Path) -> Option<Region> {
     let file = File::open(&current_config).ok()?;
     let reader = BufReader::new(file);
     let lines = reader.lines().filter_map(Result::ok);
-    let region_line = lines
-        .skip_while(|line| line != "[compute]")
+    let section_header = format!("[{}]", section);
+    let property_line = lines
+        .skip_while(|line| line != &section_header)
         .skip(1)
         .take_while(|line| !line.starts_with('['))
-        .find(|line| line.starts_with("region"))?;
-    let region = region_line.split('=').nth(1)?.trim();
-    Some(region.to_string())
+        .find(|line| line.split('=').next().map(str::trim) == Some(property))?;
+    let value = property_line.split('=').nth(1)?.trim();
+    Some(value.to_string())
 }
 
 fn get_active_config(context: &Context, config_root: &Path) -> Option<String> {
@@ -89,9 +78,9 @@ fn get_config_dir(context: &Context) -> Option<PathBuf> {
     Some(config_dir)
 }
 
-fn alias_region(region: String, aliases: &HashMap<String, &str>) -> String {
-    match aliases.get(&region) {
-        None => region.to_string(),
+fn alias_name(name: String, aliases: &HashMap<String, &str>) -> String {
+    match aliases.get(&name) {
+        None => name,
         Some(alias) => (*alias).to_string(),
     }
 }
@@ -101,25 +90,26 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let config: GcloudConfig = GcloudConfig::try_load(module.config);
 
     let config_path = get_current_config_path(context)?;
-    let gcloud_account = get_gcloud_account_from_config(&config_path);
-    let gcloud_project = get_gcloud_project_from_config(&config_path);
-    let gcloud_region = get_gcloud_region_from_config(&config_path);
+    let gcloud_account = get_gcloud_property(context, &config_path, "core", "account");
+    let gcloud_project = get_gcloud_property(context, &config_path, "core", "project");
+    let gcloud_region = get_gcloud_property(context, &config_path, "compute", "region");
+    let gcloud_zone = get_gcloud_property(context, &config_path, "compute", "zone");
     let config_dir = get_config_dir(context)?;
     let gcloud_active: Option<Active> = get_active_config(context, &config_dir);
 
     if gcloud_account.is_none()
         && gcloud_project.is_none()
         && gcloud_region.is_none()
+        && gcloud_zone.is_none()
         && gcloud_active.is_none()
     {
         return None;
     }
 
-    let mapped_region = if let Some(gcloud_region) = gcloud_region {
-        Some(alias_region(gcloud_region, &config.region_aliases))
-    } else {
-        None
-    };
+    let mapped_region =
+        gcloud_region.map(|gcloud_region| alias_name(gcloud_region, &config.region_aliases));
+    let mapped_project =
+        gcloud_project.map(|gcloud_project| alias_name(gcloud_project, &config.project_aliases));
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -133,8 +123,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "account" => gcloud_account.as_ref().map(Ok),
-                "project" => gcloud_project.as_ref().map(Ok),
+                "project" => mapped_project.as_ref().map(Ok),
                 "region" => mapped_region.as_ref().map(Ok),
+                "zone" => gcloud_zone.as_ref().map(Ok),
                 "active" => gcloud_active.as_ref().map(Ok),
                 _ => None,
             })
@@ -357,4 +348,69 @@ project = overridden
         assert_eq!(actual, expected);
         dir.close()
     }
+
+    #[test]
+    fn project_set_with_alias() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let active_config_path = dir.path().join("active_config");
+        let mut active_config_file = File::create(&active_config_path)?;
+        active_config_file.write_all(b"default")?;
+
+        create_dir(dir.path().join("configurations"))?;
+        let config_default_path = dir.path().join("configurations/config_default");
+        let mut config_default_file = File::create(&config_default_path)?;
+        config_default_file.write_all(
+            b"[core]
+project = very-long-project-name-123456
+",
+        )?;
+
+        let actual = ModuleRenderer::new("gcloud")
+            .env("CLOUDSDK_CONFIG", dir.path().to_string_lossy())
+            .config(toml::toml! {
+                [gcloud]
+                format = "on [$symbol$project]($style) "
+                [gcloud.project_aliases]
+                very-long-project-name-123456 = "vlpn"
+            })
+            .collect();
+        let expected = Some(format!("on {} ", Color::Blue.bold().paint("☁️ vlpn")));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn zone_set_and_region_overridden() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let active_config_path = dir.path().join("active_config");
+        let mut active_config_file = File::create(&active_config_path)?;
+        active_config_file.write_all(b"default")?;
+
+        create_dir(dir.path().join("configurations"))?;
+        let config_default_path = dir.path().join("configurations/config_default");
+        let mut config_default_file = File::create(&config_default_path)?;
+        config_default_file.write_all(
+            b"[compute]
+region = us-central1
+zone = us-central1-a
+",
+        )?;
+
+        let actual = ModuleRenderer::new("gcloud")
+            .env("CLOUDSDK_CONFIG", dir.path().to_string_lossy())
+            .env("CLOUDSDK_COMPUTE_REGION", "europe-west1")
+            .config(toml::toml! {
+                [gcloud]
+                format = "on [$symbol$region $zone]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Blue.bold().paint("☁️ europe-west1 us-central1-a")
+        ));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
 }
@@ -1,7 +1,10 @@
+use once_cell::sync::Lazy;
+
+use super::utils::pinned_version::is_older_than;
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::go::GoConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Go version
@@ -16,26 +19,23 @@ use crate::utils;
 ///     - Current directory contains a `Godeps` directory
 ///     - Current directory contains a file with the `.go` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("golang");
+    let config = GoConfig::try_load(module.config);
+
     let is_go_project = context
         .try_begin_scan()?
-        .set_files(&[
-            "go.mod",
-            "go.sum",
-            "glide.yaml",
-            "Gopkg.yml",
-            "Gopkg.lock",
-            ".go-version",
-        ])
-        .set_extensions(&["go"])
-        .set_folders(&["Godeps"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_go_project {
         return None;
     }
 
-    let mut module = context.new_module("golang");
-    let config = GoConfig::try_load(module.config);
+    let go_version = Lazy::new(|| parse_go_version(&utils::exec_cmd("go", &["version"])?.stdout));
+    let go_mod = get_go_mod(context);
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -43,13 +43,28 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => match go_mod.as_ref().and_then(GoMod::required_version) {
+                    Some(required) if is_older_than(go_version.as_deref()?, required) => {
+                        Some(Ok(config.not_capable_style))
+                    }
+                    _ => Some(Ok(config.style)),
+                },
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => {
-                    format_go_version(&utils::exec_cmd("go", &["version"])?.stdout.as_str()).map(Ok)
-                }
+                "version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    go_version.as_deref()?,
+                    config.version_format,
+                )
+                .map(Ok),
+                "module" => go_mod.as_ref()?.module.clone().map(Ok),
+                "module_name" => go_mod
+                    .as_ref()?
+                    .module_name()
+                    .map(|name| Ok(name.to_string())),
+                "mod_version" => go_mod.as_ref()?.go.clone().map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -66,7 +81,62 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn format_go_version(go_stdout: &str) -> Option<String> {
+/// The directives of `go.mod` that the module uses
+#[derive(Debug, Default, PartialEq)]
+struct GoMod {
+    /// The `module` path, e.g. `github.com/starship/starship`
+    module: Option<String>,
+    /// The `go` directive, e.g. `1.21`
+    go: Option<String>,
+    /// The `toolchain` directive without its `go` prefix, e.g. `1.21.3`
+    toolchain: Option<String>,
+}
+
+impl GoMod {
+    /// The last segment of the module path, skipping major version suffixes like `/v2`
+    fn module_name(&self) -> Option<&str> {
+        let mut segments = self.module.as_deref()?.rsplit('/');
+        let last = segments.next()?;
+        let is_major_version = last.len() > 1
+            && last.starts_with('v')
+            && last[1..].chars().all(|c| c.is_ascii_digit());
+        match segments.next() {
+            Some(name) if is_major_version => Some(name),
+            _ => Some(last),
+        }
+    }
+
+    /// The oldest Go version that can build the module
+    fn required_version(&self) -> Option<&str> {
+        self.toolchain.as_deref().or(self.go.as_deref())
+    }
+}
+
+fn get_go_mod(context: &Context) -> Option<GoMod> {
+    let contents = utils::read_file(context.current_dir.join("go.mod")).ok()?;
+    Some(parse_go_mod(&contents))
+}
+
+fn parse_go_mod(contents: &str) -> GoMod {
+    let mut go_mod = GoMod::default();
+    for line in contents.lines() {
+        let line = line.split("//").next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("module"), Some(path)) => {
+                go_mod.module = Some(path.trim_matches('"').to_string())
+            }
+            (Some("go"), Some(version)) => go_mod.go = Some(version.to_string()),
+            (Some("toolchain"), Some(toolchain)) => {
+                go_mod.toolchain = toolchain.strip_prefix("go").map(str::to_string)
+            }
+            _ => {}
+        }
+    }
+    go_mod
+}
+
+fn parse_go_version(go_stdout: &str) -> Option<String> {
     // go version output looks like this:
     // go version go1.13.3 linux/amd64
 
@@ -80,7 +150,7 @@ fn format_go_version(go_stdout: &str) -> Option<String> {
         // return "1.12.4"
         .next()?;
 
-    Some(format!("v{}", version))
+    Some(version.to_string())
 }
 
 #[cfg(test)]
@@ -114,6 +184,23 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    fn folder_with_go_file_and_root_version_format() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.go"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("golang")
+            .path(dir.path())
+            .config(toml::toml! {
+                version_format = "v${major}.${minor}"
+            })
+            .collect();
+
+        let expected = Some(format!("via {} ", Color::Cyan.bold().paint("🐹 v1.12")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
     #[test]
     fn folder_with_go_mod() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -196,8 +283,66 @@ mod tests {
     }
 
     #[test]
-    fn test_format_go_version() {
+    fn folder_with_go_mod_requiring_newer_go() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("go.mod"),
+            "module example.com/hello\n\ngo 1.16\n",
+        )?;
+
+        let actual = ModuleRenderer::new("golang")
+            .path(dir.path())
+            .config(toml::toml! {
+                [golang]
+                format = "via [$symbol$version( requires $mod_version)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("🐹 v1.12.1 requires 1.16")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_go_mod_module() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("go.mod"),
+            "module github.com/starship/starship/v2\n",
+        )?;
+
+        let actual = ModuleRenderer::new("golang")
+            .path(dir.path())
+            .config(toml::toml! {
+                [golang]
+                format = "[$module_name $module]($style)"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{}",
+            Color::Cyan
+                .bold()
+                .paint("starship github.com/starship/starship/v2")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_parse_go_mod() {
+        let input = "module example.com/hello // comment\n\ngo 1.21\ntoolchain go1.21.3\n\nrequire (\n\tgolang.org/x/text v0.3.0\n)\n";
+        let go_mod = parse_go_mod(input);
+        assert_eq!(go_mod.go, Some("1.21".to_string()));
+        assert_eq!(go_mod.required_version(), Some("1.21.3"));
+        assert_eq!(go_mod.module_name(), Some("hello"));
+        assert_eq!(parse_go_mod("go 1.16\n").module_name(), None);
+    }
+
+    #[test]
+    fn test_parse_go_version() {
         let input = "go version go1.12 darwin/amd64";
-        assert_eq!(format_go_version(input), Some("v1.12".to_string()));
+        assert_eq!(parse_go_version(input), Some("1.12".to_string()));
     }
 }
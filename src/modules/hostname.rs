@@ -1,21 +1,24 @@
+use super::utils::remote;
 use super::{Context, Module};
+use indexmap::IndexMap;
+use regex::Regex;
 use std::ffi::OsString;
 
 use crate::config::RootModuleConfig;
-use crate::configs::hostname::HostnameConfig;
+use crate::configs::hostname::{HostnameAliasConfig, HostnameConfig};
 use crate::formatter::StringFormatter;
 
 /// Creates a module with the system hostname
 ///
 /// Will display the hostname if all of the following criteria are met:
 ///     - hostname.disabled is absent or false
-///     - hostname.ssh_only is false OR the user is currently connected as an SSH session (`$SSH_CONNECTION`)
+///     - hostname.ssh_only is false OR the user is currently connected as a remote session
+///       (see `remote_detection`)
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("hostname");
     let config: HostnameConfig = HostnameConfig::try_load(module.config);
 
-    let ssh_connection = context.get_env("SSH_CONNECTION");
-    if config.ssh_only && ssh_connection.is_none() {
+    if config.ssh_only && !remote::is_remote_session(context, &config.remote_detection) {
         return None;
     }
 
@@ -29,9 +32,16 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         }
     };
 
+    let alias = find_alias(&host, &config.aliases);
+    let style = alias.and_then(|alias| alias.style).unwrap_or(config.style);
+
     //rustc doesn't let you do an "if" and an "if let" in the same if statement
     // if this changes in the future this can become a lot cleaner
-    let host = if !config.trim_at.is_empty() {
+    let host = if let Some(name) = alias.and_then(|alias| alias.name) {
+        name
+    } else if let Some(trimmed) = trim_with_regex(&host, config.trim_regex) {
+        trimmed
+    } else if !config.trim_at.is_empty() {
         if let Some(index) = host.find(config.trim_at) {
             host.split_at(index).0
         } else {
@@ -44,7 +54,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => Some(Ok(style)),
                 _ => None,
             })
             .map(|variable| match variable {
@@ -65,8 +75,52 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// Extract the part of the hostname matched by `trim_regex`
+///
+/// This is the first capture group if the regex has one, otherwise the whole match.
+fn trim_with_regex<'a>(host: &'a str, trim_regex: &str) -> Option<&'a str> {
+    if trim_regex.is_empty() {
+        return None;
+    }
+
+    let regex = match Regex::new(trim_regex) {
+        Ok(regex) => regex,
+        Err(error) => {
+            log::warn!("Invalid trim_regex in [hostname]:\n{}", error);
+            return None;
+        }
+    };
+    let captures = regex.captures(host)?;
+    let matched = captures.get(1).or_else(|| captures.get(0))?;
+
+    Some(matched.as_str())
+}
+
+/// Find the alias for a host, preferring an exact match of the hostname over
+/// the first alias whose regex matches the entire hostname
+fn find_alias<'a, 'b>(
+    host: &str,
+    aliases: &'b IndexMap<String, HostnameAliasConfig<'a>>,
+) -> Option<&'b HostnameAliasConfig<'a>> {
+    if let Some(alias) = aliases.get(host) {
+        return Some(alias);
+    }
+
+    aliases.iter().find_map(
+        |(pattern, alias)| match Regex::new(&format!("^(?:{})$", pattern)) {
+            Ok(regex) if regex.is_match(host) => Some(alias),
+            Ok(_) => None,
+            Err(error) => {
+                log::warn!("Invalid hostname alias pattern `{}`:\n{}", pattern, error);
+                None
+            }
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::test::ModuleRenderer;
     use ansi_term::{Color, Style};
     use std::io;
@@ -132,6 +186,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn ssh_in_tmux() -> io::Result<()> {
+        let hostname = get_hostname!();
+        let actual = ModuleRenderer::new("hostname")
+            .config(toml::toml! {
+                [hostname]
+                ssh_only = true
+                trim_at = ""
+                remote_detection = ["env", "tmux"]
+            })
+            .env("TMUX", "/tmp/tmux-1000/default,1234,0")
+            .collect();
+        let expected = Some(format!("{} in ", style().paint(hostname)));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn ssh_in_tmux_not_detected_by_default() -> io::Result<()> {
+        let actual = ModuleRenderer::new("hostname")
+            .config(toml::toml! {
+                [hostname]
+                ssh_only = true
+            })
+            .env("TMUX", "/tmp/tmux-1000/default,1234,0")
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
     #[test]
     fn no_trim_at() -> io::Result<()> {
         let hostname = get_hostname!();
@@ -165,6 +252,82 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_trim_with_regex() {
+        let host = "web.prod-eu.example.com";
+        assert_eq!(trim_with_regex(host, r"^[^.]+\.([^.]+)"), Some("prod-eu"));
+        assert_eq!(trim_with_regex(host, r"^\w+"), Some("web"));
+        assert_eq!(trim_with_regex(host, r"^db"), None);
+        assert_eq!(trim_with_regex(host, ""), None);
+    }
+
+    #[test]
+    fn trim_regex() -> io::Result<()> {
+        let hostname = get_hostname!();
+        let first_char = hostname.chars().next().unwrap().to_string();
+        let actual = ModuleRenderer::new("hostname")
+            .config(toml::toml! {
+                [hostname]
+                ssh_only = false
+                trim_regex = "^(.)"
+            })
+            .collect();
+        let expected = Some(format!("{} in ", style().paint(first_char)));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn alias_exact() -> io::Result<()> {
+        let hostname = get_hostname!();
+        let config = format!(
+            "[hostname]\nssh_only = false\n[hostname.aliases]\n{:?} = \"host\"",
+            hostname
+        );
+        let actual = ModuleRenderer::new("hostname")
+            .config(toml::from_str(&config).unwrap())
+            .collect();
+        let expected = Some(format!("{} in ", style().paint("host")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn alias_regex_with_style() -> io::Result<()> {
+        let actual = ModuleRenderer::new("hostname")
+            .config(toml::toml! {
+                [hostname]
+                ssh_only = false
+                [hostname.aliases]
+                ".*" = { name = "anywhere", style = "bold red" }
+            })
+            .collect();
+        let expected = Some(format!("{} in ", Color::Red.bold().paint("anywhere")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn alias_no_match() -> io::Result<()> {
+        let hostname = get_hostname!();
+        let actual = ModuleRenderer::new("hostname")
+            .config(toml::toml! {
+                [hostname]
+                ssh_only = false
+                trim_at = ""
+                [hostname.aliases]
+                "" = "nowhere"
+            })
+            .collect();
+        let expected = Some(format!("{} in ", style().paint(hostname)));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
     fn style() -> Style {
         Color::Green.bold().dimmed()
     }
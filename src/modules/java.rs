@@ -1,12 +1,17 @@
 use crate::configs::java::JavaConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 
+use super::utils::pinned_version::{read_pinned_version, satisfies_pin};
 use super::{Context, Module, RootModuleConfig};
 
 use crate::utils;
 
 use regex::Regex;
+use std::path::Path;
 const JAVA_VERSION_PATTERN: &str = "(?P<version>[\\d\\.]+)[^\\s]*\\s(?:built|from)";
+const MAVEN_RELEASE_PATTERN: &str =
+    r"<maven\.compiler\.release>\s*(?P<version>[\d.]+)\s*</maven\.compiler\.release>";
+const GRADLE_TOOLCHAIN_PATTERN: &str = r"JavaLanguageVersion\.of\(\s*(?P<version>\d+)\s*\)";
 
 /// Creates a module with the current Java version
 ///
@@ -14,28 +19,24 @@ const JAVA_VERSION_PATTERN: &str = "(?P<version>[\\d\\.]+)[^\\s]*\\s(?:built|fro
 ///     - Current directory contains a file with a `.java`, `.class`, `.jar`, `.gradle`, `.clj`, or `.cljc` extension
 ///     - Current directory contains a `pom.xml`, `build.gradle.kts`, `build.sbt`, `.java-version`, `deps.edn`, `project.clj`, or `build.boot` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("java");
+    let config: JavaConfig = JavaConfig::try_load(module.config);
+
     let is_java_project = context
         .try_begin_scan()?
-        .set_files(&[
-            "pom.xml",
-            "build.gradle.kts",
-            "build.sbt",
-            ".java-version",
-            "deps.edn",
-            "project.clj",
-            "build.boot",
-        ])
-        .set_extensions(&["java", "class", "jar", "gradle", "clj", "cljc"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_java_project {
         return None;
     }
 
-    let java_version = get_java_version(context)?;
-
-    let mut module = context.new_module("java");
-    let config: JavaConfig = JavaConfig::try_load(module.config);
+    let java_internal_version = get_java_internal_version(context)?;
+    let java_version = parse_java_version(&java_internal_version)?;
+    let expected_version = get_pinned_java_version(context)
+        .filter(|pinned_version| !matches_pinned_version(&java_version, pinned_version));
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -44,11 +45,35 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => {
+                    if expected_version.is_none() {
+                        Some(Ok(config.style))
+                    } else {
+                        Some(Ok(config.not_capable_style))
+                    }
+                }
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(&java_version)),
+                "version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    &java_version,
+                    config.version_format,
+                )
+                .map(Ok),
+                "vendor" => {
+                    let vendor = get_java_vendor(context, &java_internal_version)?;
+                    let vendor = config.vendor_symbols.get(vendor).copied().unwrap_or(vendor);
+                    Some(Ok(vendor.to_string()))
+                }
+                "expected_version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    expected_version.as_deref()?,
+                    config.version_format,
+                )
+                .map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -65,20 +90,120 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_java_version(context: &Context) -> Option<String> {
+/// The output of `java -Xinternalversion`, which has both the version and the vendor
+fn get_java_internal_version(context: &Context) -> Option<String> {
     let java_command = match context.get_env("JAVA_HOME") {
         Some(java_home) => format!("{}/bin/java", java_home),
         None => String::from("java"),
     };
 
     let output = utils::exec_cmd(&java_command.as_str(), &["-Xinternalversion"])?;
-    let java_version = if output.stdout.is_empty() {
-        output.stderr
+    if output.stdout.is_empty() {
+        Some(output.stderr)
     } else {
-        output.stdout
-    };
+        Some(output.stdout)
+    }
+}
+
+/// The vendors of JDK distributions, with their SDKMAN! identifier and the names they are
+/// recognized by in the output of `java -Xinternalversion`, the `IMPLEMENTOR` of
+/// `$JAVA_HOME/release` or the `$JAVA_HOME` path
+const JAVA_VENDORS: &[(&str, &str, &[&str])] = &[
+    ("graalvm", "grl", &["graalvm"]),
+    ("temurin", "tem", &["temurin", "adoptium"]),
+    ("adoptopenjdk", "adpt", &["adoptopenjdk"]),
+    ("corretto", "amzn", &["corretto", "amazon"]),
+    ("zulu", "zulu", &["zulu", "azul"]),
+    ("openj9", "sem", &["openj9", "semeru"]),
+    ("sapmachine", "sapmchn", &["sapmachine"]),
+    ("liberica", "librca", &["liberica", "bellsoft"]),
+    ("microsoft", "ms", &["microsoft"]),
+    ("oracle", "oracle", &["oracle", "(tm)"]),
+];
+
+fn get_java_vendor(context: &Context, java_internal_version: &str) -> Option<&'static str> {
+    let java_home = context.get_env("JAVA_HOME");
+    let implementor = java_home.as_ref().and_then(|java_home| {
+        let release = utils::read_file(Path::new(java_home).join("release")).ok()?;
+        let implementor = release
+            .lines()
+            .find_map(|line| line.strip_prefix("IMPLEMENTOR="))?;
+        Some(implementor.trim_matches('"').to_string())
+    });
+
+    let vendor = [
+        implementor,
+        Some(java_internal_version.to_string()),
+        java_home.clone(),
+    ]
+    .iter()
+    .flatten()
+    .find_map(|source| {
+        let source = source.to_lowercase();
+        JAVA_VENDORS
+            .iter()
+            .find(|(_, _, names)| names.iter().any(|name| source.contains(name)))
+    });
+
+    // JDKs installed by SDKMAN! are in directories like `17.0.2-tem`
+    let vendor = vendor.or_else(|| {
+        let candidate = Path::new(java_home.as_ref()?).file_name()?.to_str()?;
+        let (_, id) = candidate.rsplit_once('-')?;
+        JAVA_VENDORS
+            .iter()
+            .find(|(_, sdkman_id, _)| *sdkman_id == id)
+    });
+
+    vendor.map(|(vendor, _, _)| *vendor)
+}
 
-    parse_java_version(&java_version)
+/// The JDK the project expects, from `.sdkmanrc`, `.java-version`, the `maven.compiler.release`
+/// of `pom.xml` or the toolchain of a Gradle build
+fn get_pinned_java_version(context: &Context) -> Option<String> {
+    let dir = &context.current_dir;
+
+    utils::read_file(dir.join(".sdkmanrc"))
+        .ok()
+        .and_then(|sdkmanrc| get_sdkmanrc_version(&sdkmanrc))
+        .or_else(|| read_pinned_version(dir, &[".java-version"]))
+        .or_else(|| {
+            let pom = utils::read_file(dir.join("pom.xml")).ok()?;
+            capture_version(MAVEN_RELEASE_PATTERN, &pom)
+        })
+        .or_else(|| {
+            ["build.gradle.kts", "build.gradle"]
+                .iter()
+                .find_map(|file| {
+                    let build_script = utils::read_file(dir.join(file)).ok()?;
+                    capture_version(GRADLE_TOOLCHAIN_PATTERN, &build_script)
+                })
+        })
+}
+
+/// Candidates in `.sdkmanrc` look like `java=17.0.2-tem`
+fn get_sdkmanrc_version(sdkmanrc: &str) -> Option<String> {
+    let candidate = sdkmanrc
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("java="))?;
+    let version = candidate.split('-').next()?;
+    Some(version.to_string())
+}
+
+fn capture_version(pattern: &str, contents: &str) -> Option<String> {
+    let re = Regex::new(pattern).ok()?;
+    let captures = re.captures(contents)?;
+    Some(captures["version"].to_string())
+}
+
+/// Whether the running JDK matches the pinned one
+///
+/// Pins may have more components than `java` reports (`8.0.302` for `1.8.0`) or fewer (`17` for
+/// `17.0.2`), so they match if either is a prefix of the other. Legacy versions like `1.8` are
+/// treated as `8`.
+fn matches_pinned_version(java_version: &str, pinned_version: &str) -> bool {
+    let java_version = java_version.strip_prefix("1.").unwrap_or(java_version);
+    let pinned_version = pinned_version.strip_prefix("1.").unwrap_or(pinned_version);
+    satisfies_pin(java_version, pinned_version) || satisfies_pin(pinned_version, java_version)
 }
 
 fn parse_java_version(java_version: &str) -> Option<String> {
@@ -86,7 +211,7 @@ fn parse_java_version(java_version: &str) -> Option<String> {
     let captures = re.captures(java_version)?;
     let version = &captures["version"];
 
-    Some(format!("v{}", &version))
+    Some(version.to_string())
 }
 
 #[cfg(test)]
@@ -94,65 +219,65 @@ mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io;
 
     #[test]
     fn test_parse_java_version_openjdk() {
         let java_8 = "OpenJDK 64-Bit Server VM (25.222-b10) for linux-amd64 JRE (1.8.0_222-b10), built on Jul 11 2019 10:18:43 by \"openjdk\" with gcc 4.4.7 20120313 (Red Hat 4.4.7-23)";
         let java_11 = "OpenJDK 64-Bit Server VM (11.0.4+11-post-Ubuntu-1ubuntu219.04) for linux-amd64 JRE (11.0.4+11-post-Ubuntu-1ubuntu219.04), built on Jul 18 2019 18:21:46 by \"build\" with gcc 8.3.0";
-        assert_eq!(parse_java_version(java_11), Some("v11.0.4".to_string()));
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_11), Some("11.0.4".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_oracle() {
         let java_8 = "Java HotSpot(TM) Client VM (25.65-b01) for linux-arm-vfp-hflt JRE (1.8.0_65-b17), built on Oct  6 2015 16:19:04 by \"java_re\" with gcc 4.7.2 20120910 (prerelease)";
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_redhat() {
         let java_8 = "OpenJDK 64-Bit Server VM (25.222-b10) for linux-amd64 JRE (1.8.0_222-b10), built on Jul 11 2019 20:48:53 by \"root\" with gcc 7.3.1 20180303 (Red Hat 7.3.1-5)";
         let java_12 = "OpenJDK 64-Bit Server VM (12.0.2+10) for linux-amd64 JRE (12.0.2+10), built on Jul 18 2019 14:41:47 by \"jenkins\" with gcc 7.3.1 20180303 (Red Hat 7.3.1-5)";
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
-        assert_eq!(parse_java_version(java_12), Some("v12.0.2".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_12), Some("12.0.2".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_zulu() {
         let java_8 = "OpenJDK 64-Bit Server VM (25.222-b10) for linux-amd64 JRE (Zulu 8.40.0.25-CA-linux64) (1.8.0_222-b10), built on Jul 11 2019 11:36:39 by \"zulu_re\" with gcc 4.4.7 20120313 (Red Hat 4.4.7-3)";
         let java_11 = "OpenJDK 64-Bit Server VM (11.0.4+11-LTS) for linux-amd64 JRE (Zulu11.33+15-CA) (11.0.4+11-LTS), built on Jul 11 2019 21:37:17 by \"zulu_re\" with gcc 4.9.2 20150212 (Red Hat 4.9.2-6)";
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
-        assert_eq!(parse_java_version(java_11), Some("v11.0.4".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_11), Some("11.0.4".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_eclipse_openj9() {
         let java_8 = "Eclipse OpenJ9 OpenJDK 64-bit Server VM (1.8.0_222-b10) from linux-amd64 JRE with Extensions for OpenJDK for Eclipse OpenJ9 8.0.222.0, built on Jul 17 2019 21:29:18 by jenkins with g++ (GCC) 7.3.1 20180303 (Red Hat 7.3.1-5)";
         let java_11 = "Eclipse OpenJ9 OpenJDK 64-bit Server VM (11.0.4+11) from linux-amd64 JRE with Extensions for OpenJDK for Eclipse OpenJ9 11.0.4.0, built on Jul 17 2019 21:51:37 by jenkins with g++ (GCC) 7.3.1 20180303 (Red Hat 7.3.1-5)";
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
-        assert_eq!(parse_java_version(java_11), Some("v11.0.4".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_11), Some("11.0.4".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_graalvm() {
         let java_8 = "OpenJDK 64-Bit GraalVM CE 19.2.0.1 (25.222-b08-jvmci-19.2-b02) for linux-amd64 JRE (8u222), built on Jul 19 2019 17:37:13 by \"buildslave\" with gcc 7.3.0";
-        assert_eq!(parse_java_version(java_8), Some("v8".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("8".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_amazon_corretto() {
         let java_8 = "OpenJDK 64-Bit Server VM (25.222-b10) for linux-amd64 JRE (1.8.0_222-b10), built on Jul 11 2019 20:48:53 by \"root\" with gcc 7.3.1 20180303 (Red Hat 7.3.1-5)";
         let java_11 = "OpenJDK 64-Bit Server VM (11.0.4+11-LTS) for linux-amd64 JRE (11.0.4+11-LTS), built on Jul 11 2019 20:06:11 by \"\" with gcc 7.3.1 20180303 (Red Hat 7.3.1-5)";
-        assert_eq!(parse_java_version(java_8), Some("v1.8.0".to_string()));
-        assert_eq!(parse_java_version(java_11), Some("v11.0.4".to_string()));
+        assert_eq!(parse_java_version(java_8), Some("1.8.0".to_string()));
+        assert_eq!(parse_java_version(java_11), Some("11.0.4".to_string()));
     }
 
     #[test]
     fn test_parse_java_version_sapmachine() {
         let java_11 = "OpenJDK 64-Bit Server VM (11.0.4+11-LTS-sapmachine) for linux-amd64 JRE (11.0.4+11-LTS-sapmachine), built on Jul 17 2019 08:58:43 by \"\" with gcc 7.3.0";
-        assert_eq!(parse_java_version(java_11), Some("v11.0.4".to_string()));
+        assert_eq!(parse_java_version(java_11), Some("11.0.4".to_string()));
     }
 
     #[test]
@@ -249,4 +374,108 @@ mod tests {
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn folder_with_matching_pinned_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join(".sdkmanrc"),
+            "# sdk env\njava=13.0.2-open\n",
+        )?;
+        let actual = ModuleRenderer::new("java").path(dir.path()).collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+
+        fs::write(dir.path().join("Main.java"), "")?;
+        let actual = ModuleRenderer::new("java").path(dir.path()).collect();
+        let expected = Some(format!("via {} ", Color::Red.dimmed().paint("☕ v13.0.2")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_mismatched_maven_release() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("pom.xml"),
+            "<project>\n  <properties>\n    <maven.compiler.release>17</maven.compiler.release>\n  </properties>\n</project>\n",
+        )?;
+        let actual = ModuleRenderer::new("java")
+            .path(dir.path())
+            .config(toml::toml! {
+                [java]
+                format = "via [$symbol$version( expected $expected_version)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("☕ v13.0.2 expected v17")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_get_gradle_toolchain_version() {
+        let build_script = "java {\n    toolchain {\n        languageVersion.set(JavaLanguageVersion.of(11))\n    }\n}\n";
+        assert_eq!(
+            capture_version(GRADLE_TOOLCHAIN_PATTERN, build_script),
+            Some("11".to_string())
+        );
+    }
+
+    #[test]
+    fn test_matches_pinned_version() {
+        assert!(matches_pinned_version("1.8.0", "8.0.302"));
+        assert!(matches_pinned_version("17.0.2", "17"));
+        assert!(matches_pinned_version("1.8.0", "1.8"));
+        assert!(!matches_pinned_version("11.0.4", "17"));
+        assert!(!matches_pinned_version("17.0.1", "17.0.2"));
+    }
+
+    #[test]
+    fn test_java_vendor() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Main.java"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("java")
+            .path(dir.path())
+            .config(toml::toml! {
+                [java]
+                format = "[$version( $vendor)]($style)"
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Red.dimmed().paint("v13.0.2")));
+        assert_eq!(expected, actual);
+
+        let java_home = tempfile::tempdir()?;
+        fs::write(
+            java_home.path().join("release"),
+            "IMPLEMENTOR=\"Eclipse Adoptium\"\nJAVA_VERSION=\"13.0.2\"\n",
+        )?;
+        let actual = ModuleRenderer::new("java")
+            .path(dir.path())
+            .env("JAVA_HOME", java_home.path().to_string_lossy())
+            .config(toml::toml! {
+                [java]
+                format = "[$version( $vendor)]($style)"
+                vendor_symbols = { temurin = "🏔" }
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Red.dimmed().paint("v13.0.2 🏔")));
+        assert_eq!(expected, actual);
+
+        java_home.close()?;
+        dir.close()
+    }
+
+    #[test]
+    fn test_get_java_vendor() {
+        let context = Context::new(Default::default());
+        let zulu = "OpenJDK 64-Bit Server VM (11.0.4+11-LTS) for linux-amd64 JRE (Zulu11.33+15-CA) (11.0.4+11-LTS), built on Jul 11 2019 21:37:17 by \"zulu_re\" with gcc 4.9.2 20150212 (Red Hat 4.9.2-6)";
+        let graalvm = "OpenJDK 64-Bit GraalVM CE 19.2.0.1 (25.222-b08-jvmci-19.2-b02) for linux-amd64 JRE (8u222), built on Jul 19 2019 17:37:13 by \"buildslave\" with gcc 7.3.0";
+        assert_eq!(get_java_vendor(&context, zulu), Some("zulu"));
+        assert_eq!(get_java_vendor(&context, graalvm), Some("graalvm"));
+        assert_eq!(get_java_vendor(&context, "Unknown JRE"), None);
+    }
 }
@@ -18,17 +18,42 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     if num_of_jobs == 0 {
         return None;
     }
+    // Only bash and zsh report stopped jobs, other shells count every job as running
+    let num_of_stopped_jobs = props
+        .get("stopped_jobs")
+        .and_then(|jobs| jobs.trim().parse::<i64>().ok())
+        .unwrap_or(0)
+        .min(num_of_jobs)
+        .max(0);
+
+    // `threshold` predates `number_threshold`, so honor it if it has been changed
+    let number_threshold = if config.threshold != 1 {
+        config.threshold + 1
+    } else {
+        config.number_threshold
+    };
 
-    let module_number = if num_of_jobs > config.threshold {
+    let module_symbol = if num_of_jobs < config.symbol_threshold {
+        ""
+    } else if num_of_stopped_jobs > 0 {
+        config.stopped_symbol.unwrap_or(config.symbol)
+    } else {
+        config.symbol
+    };
+    let module_number = if num_of_jobs >= number_threshold {
         num_of_jobs.to_string()
     } else {
         "".to_string()
     };
 
+    if module_symbol.is_empty() && module_number.is_empty() {
+        return None;
+    }
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
-                "symbol" => Some(config.symbol),
+                "symbol" => Some(module_symbol),
                 _ => None,
             })
             .map_style(|variable| match variable {
@@ -37,6 +62,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "number" => Some(Ok(module_number.clone())),
+                "running" => Some(Ok((num_of_jobs - num_of_stopped_jobs).to_string())),
+                "stopped" => Some(Ok(num_of_stopped_jobs.to_string())),
                 _ => None,
             })
             .parse(None)
@@ -115,4 +142,83 @@ mod test {
         assert_eq!(expected, actual);
         Ok(())
     }
+
+    #[test]
+    fn config_symbol_threshold_2_job_1() -> io::Result<()> {
+        let actual = ModuleRenderer::new("jobs")
+            .config(toml::toml! {
+                [jobs]
+                symbol_threshold = 2
+                number_threshold = 1
+            })
+            .jobs(1)
+            .collect();
+
+        let expected = Some(format!("{} ", Color::Blue.bold().paint("1")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn config_number_threshold_3_job_2() -> io::Result<()> {
+        let actual = ModuleRenderer::new("jobs")
+            .config(toml::toml! {
+                [jobs]
+                number_threshold = 3
+            })
+            .jobs(2)
+            .collect();
+
+        let expected = Some(format!("{} ", Color::Blue.bold().paint("✦")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn config_both_thresholds_unmet() -> io::Result<()> {
+        let actual = ModuleRenderer::new("jobs")
+            .config(toml::toml! {
+                [jobs]
+                symbol_threshold = 3
+                number_threshold = 3
+            })
+            .jobs(2)
+            .collect();
+
+        let expected = None;
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn config_stopped_symbol() -> io::Result<()> {
+        let actual = ModuleRenderer::new("jobs")
+            .config(toml::toml! {
+                [jobs]
+                stopped_symbol = "⏸"
+            })
+            .jobs(2)
+            .stopped_jobs(1)
+            .collect();
+
+        let expected = Some(format!("{} ", Color::Blue.bold().paint("⏸2")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn config_running_and_stopped() -> io::Result<()> {
+        let actual = ModuleRenderer::new("jobs")
+            .config(toml::toml! {
+                [jobs]
+                format = "[$running/$stopped]($style) "
+            })
+            .jobs(3)
+            .stopped_jobs(1)
+            .collect();
+
+        let expected = Some(format!("{} ", Color::Blue.bold().paint("2/1")));
+        assert_eq!(expected, actual);
+        Ok(())
+    }
 }
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::julia::JuliaConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Julia version
@@ -11,18 +11,20 @@ use crate::utils;
 ///     - Current directory contains a `Manifest.toml` file
 ///     - Current directory contains a file with the `.jl` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("julia");
+    let config = JuliaConfig::try_load(module.config);
+
     let is_julia_project = context
         .try_begin_scan()?
-        .set_files(&["Project.toml", "Manifest.toml"])
-        .set_extensions(&["jl"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_julia_project {
         return None;
     }
 
-    let mut module = context.new_module("julia");
-    let config = JuliaConfig::try_load(module.config);
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -35,8 +37,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    format_julia_version(&utils::exec_cmd("julia", &["--version"])?.stdout.as_str())
-                        .map(Ok)
+                    let julia_version =
+                        parse_julia_version(&utils::exec_cmd("julia", &["--version"])?.stdout)?;
+                    VersionFormatter::format_module_version(
+                        context,
+                        module.get_name(),
+                        &julia_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
                 }
                 _ => None,
             })
@@ -54,7 +63,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn format_julia_version(julia_stdout: &str) -> Option<String> {
+fn parse_julia_version(julia_stdout: &str) -> Option<String> {
     // julia version output looks like this:
     // julia version 1.4.0
 
@@ -66,7 +75,7 @@ fn format_julia_version(julia_stdout: &str) -> Option<String> {
         .split_whitespace()
         .next()?;
 
-    Some(format!("v{}", version))
+    Some(version.to_string())
 }
 
 #[cfg(test)]
@@ -125,8 +134,8 @@ mod tests {
     }
 
     #[test]
-    fn test_format_julia_version() {
+    fn test_parse_julia_version() {
         let input = "julia version 1.4.0";
-        assert_eq!(format_julia_version(input), Some("v1.4.0".to_string()));
+        assert_eq!(parse_julia_version(input), Some("1.4.0".to_string()));
     }
 }
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::kotlin::KotlinConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 use regex::Regex;
@@ -12,18 +12,21 @@ const KOTLIN_VERSION_PATTERN: &str = "(?P<version>[\\d\\.]+[\\d\\.]+[\\d\\.]+)";
 /// Will display the Kotlin version if any of the following criteria are met:
 ///     - Current directory contains a file with a `.kt` or `.kts` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("kotlin");
+    let config = KotlinConfig::try_load(module.config);
+
     let is_kotlin_project = context
         .try_begin_scan()?
-        .set_extensions(&["kt", "kts"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_kotlin_project {
         return None;
     }
 
-    let mut module = context.new_module("kotlin");
-    let config = KotlinConfig::try_load(module.config);
-    let kotlin_version = format_kotlin_version(&get_kotlin_version(&config.kotlin_binary)?)?;
+    let kotlin_version = parse_kotlin_version(&get_kotlin_version(&config.kotlin_binary)?)?;
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -35,7 +38,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(&kotlin_version)),
+                "version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    &kotlin_version,
+                    config.version_format,
+                )
+                .map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -65,7 +74,7 @@ fn get_kotlin_version(kotlin_binary: &str) -> Option<String> {
     }
 }
 
-fn format_kotlin_version(kotlin_stdout: &str) -> Option<String> {
+fn parse_kotlin_version(kotlin_stdout: &str) -> Option<String> {
     // kotlin -version output looks like this:
     // Kotlin version 1.4.21-release-411 (JRE 14.0.1+7)
 
@@ -74,7 +83,7 @@ fn format_kotlin_version(kotlin_stdout: &str) -> Option<String> {
     let re = Regex::new(KOTLIN_VERSION_PATTERN).ok()?;
     let captures = re.captures(kotlin_stdout)?;
     let version = &captures["version"];
-    Some(format!("v{}", version))
+    Some(version.to_string())
 }
 
 #[cfg(test)]
@@ -155,20 +164,20 @@ mod tests {
     }
 
     #[test]
-    fn test_format_kotlin_version_from_runtime() {
+    fn test_parse_kotlin_version_from_runtime() {
         let kotlin_input = "Kotlin version 1.4.21-release-411 (JRE 14.0.1+7)";
         assert_eq!(
-            format_kotlin_version(kotlin_input),
-            Some("v1.4.21".to_string())
+            parse_kotlin_version(kotlin_input),
+            Some("1.4.21".to_string())
         );
     }
 
     #[test]
-    fn test_format_kotlin_version_from_compiler() {
+    fn test_parse_kotlin_version_from_compiler() {
         let kotlin_input = "info: kotlinc-jvm 1.4.21 (JRE 14.0.1+7)";
         assert_eq!(
-            format_kotlin_version(kotlin_input),
-            Some("v1.4.21".to_string())
+            parse_kotlin_version(kotlin_input),
+            Some("1.4.21".to_string())
         );
     }
 }
@@ -1,5 +1,8 @@
-use yaml_rust::YamlLoader;
+use indexmap::IndexMap;
+use regex::{NoExpand, Regex};
+use yaml_rust::{Yaml, YamlLoader};
 
+use std::borrow::Cow;
 use std::env;
 use std::path;
 
@@ -9,44 +12,80 @@ use crate::configs::kubernetes::KubernetesConfig;
 use crate::formatter::StringFormatter;
 use crate::utils;
 
-fn get_kube_context(filename: path::PathBuf) -> Option<String> {
+fn parse_kubeconfig(filename: &path::Path) -> Option<Yaml> {
     let contents = utils::read_file(filename).ok()?;
 
     let yaml_docs = YamlLoader::load_from_str(&contents).ok()?;
-    if yaml_docs.is_empty() {
-        return None;
-    }
-    let conf = &yaml_docs[0];
+    yaml_docs.into_iter().next()
+}
 
-    let current_ctx = conf["current-context"].as_str()?;
+/// The current context, along with the kubeconfig file which set it
+///
+/// Like kubectl, the first file setting `current-context` wins.
+fn get_kube_context(configs: &[(path::PathBuf, Yaml)]) -> Option<(&path::Path, &str)> {
+    configs.iter().find_map(|(filename, conf)| {
+        let current_ctx = conf["current-context"].as_str()?;
 
-    if current_ctx.is_empty() {
-        return None;
-    }
-    Some(current_ctx.to_string())
+        if current_ctx.is_empty() {
+            return None;
+        }
+        Some((filename.as_path(), current_ctx))
+    })
 }
 
-fn get_kube_ns(filename: path::PathBuf, current_ctx: String) -> Option<String> {
-    let contents = utils::read_file(filename).ok()?;
+/// The definition of a context
+///
+/// Like kubectl, contexts are merged across all files, with the first file
+/// defining a context winning.
+fn get_kube_ctx_definition<'a>(
+    configs: &'a [(path::PathBuf, Yaml)],
+    current_ctx: &str,
+) -> Option<&'a Yaml> {
+    configs.iter().find_map(|(_, conf)| {
+        conf["contexts"]
+            .as_vec()?
+            .iter()
+            .find(|ctx| ctx["name"].as_str() == Some(current_ctx))
+            .map(|ctx| &ctx["context"])
+    })
+}
 
-    let yaml_docs = YamlLoader::load_from_str(&contents).ok()?;
-    if yaml_docs.is_empty() {
-        return None;
-    }
-    let conf = &yaml_docs[0];
+/// Read a field of a context definition, like its `namespace`, `user` or `cluster`
+fn get_kube_ctx_component<'a>(definition: Option<&'a Yaml>, component: &str) -> Option<&'a str> {
+    definition?[component]
+        .as_str()
+        .filter(|value| !value.is_empty())
+}
 
-    let ns = conf["contexts"].as_vec().and_then(|contexts| {
-        contexts
-            .iter()
-            .filter_map(|ctx| Some((ctx, ctx["name"].as_str()?)))
-            .find(|(_, name)| *name == current_ctx)
-            .and_then(|(ctx, _)| ctx["context"]["namespace"].as_str())
-    })?;
+/// Look up the alias of a context
+///
+/// An alias for the exact context name wins. Otherwise the first alias whose
+/// key, as a regex, matches the entire context name is used, with capture
+/// groups like `$1` or `$name` replaced in the alias. Aliases of patterns
+/// without capture groups are used literally.
+fn get_aliased_name<'a>(name: &'a str, aliases: &IndexMap<String, &'a str>) -> Cow<'a, str> {
+    if let Some(&alias) = aliases.get(name) {
+        return Cow::Borrowed(alias);
+    }
 
-    if ns.is_empty() {
-        return None;
+    for (pattern, &alias) in aliases {
+        let regex = match Regex::new(&format!("^(?:{})$", pattern)) {
+            Ok(regex) => regex,
+            Err(error) => {
+                log::warn!("Invalid context alias pattern `{}`:\n{}", pattern, error);
+                continue;
+            }
+        };
+        if regex.is_match(name) {
+            return if regex.captures_len() > 1 {
+                regex.replace(name, alias)
+            } else {
+                regex.replace(name, NoExpand(alias))
+            };
+        }
     }
-    Some(ns.to_owned())
+
+    Cow::Borrowed(name)
 }
 
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
@@ -59,16 +98,44 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     };
 
+    // Without any detection rules the module is shown everywhere
+    let has_detect_rules = !(config.detect_extensions.is_empty()
+        && config.detect_files.is_empty()
+        && config.detect_folders.is_empty());
+    if has_detect_rules {
+        let is_kube_project = context
+            .try_begin_scan()?
+            .set_files(&config.detect_files)
+            .set_extensions(&config.detect_extensions)
+            .set_folders(&config.detect_folders)
+            .is_match();
+
+        if !is_kube_project {
+            return None;
+        }
+    }
+
     let default_config_file = dirs_next::home_dir()?.join(".kube").join("config");
 
     let kube_cfg = context
         .get_env("KUBECONFIG")
         .unwrap_or(default_config_file.to_str()?.to_string());
 
-    let kube_ctx = env::split_paths(&kube_cfg).find_map(get_kube_context)?;
+    // Empty entries in `KUBECONFIG` are ignored, as are files which can't be read
+    let kube_configs: Vec<(path::PathBuf, Yaml)> = env::split_paths(&kube_cfg)
+        .filter(|filename| !filename.as_os_str().is_empty())
+        .filter_map(|filename| Some((filename.clone(), parse_kubeconfig(&filename)?)))
+        .collect();
+
+    let (kube_cfg_file, kube_ctx) = get_kube_context(&kube_configs)?;
 
-    let kube_ns =
-        env::split_paths(&kube_cfg).find_map(|filename| get_kube_ns(filename, kube_ctx.clone()));
+    let kube_ctx_definition = get_kube_ctx_definition(&kube_configs, kube_ctx);
+    let kube_ns = get_kube_ctx_component(kube_ctx_definition, "namespace");
+    let kube_user = get_kube_ctx_component(kube_ctx_definition, "user");
+    let kube_cluster = get_kube_ctx_component(kube_ctx_definition, "cluster");
+    let kube_cfg_file = kube_cfg_file.to_string_lossy();
+
+    let display_ctx = get_aliased_name(kube_ctx, &config.context_aliases);
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -81,14 +148,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "context" => match config.context_aliases.get(&kube_ctx) {
-                    None => Some(Ok(kube_ctx.as_str())),
-                    Some(&alias) => Some(Ok(alias)),
-                },
-                "namespace" => match &kube_ns {
-                    Some(kube_ns) => Some(Ok(kube_ns)),
-                    None => None,
-                },
+                "context" => Some(Ok(display_ctx.as_ref())),
+                "namespace" => kube_ns.map(Ok),
+                "user" => kube_user.map(Ok),
+                "cluster" => kube_cluster.map(Ok),
+                "kubeconfig" => Some(Ok(kube_cfg_file.as_ref())),
                 _ => None,
             })
             .parse(None)
@@ -107,6 +171,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
     use std::env;
@@ -395,4 +460,268 @@ users: []
 
         dir.close()
     }
+
+    #[test]
+    fn test_ctx_alias_regex() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let filename = dir.path().join("config");
+
+        let mut file = File::create(&filename)?;
+        file.write_all(
+            b"
+apiVersion: v1
+clusters: []
+contexts: []
+current-context: arn:aws:eks:us-east-1:123456789012:cluster/production
+kind: Config
+preferences: {}
+users: []
+",
+        )?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("kubernetes")
+            .path(dir.path())
+            .env("KUBECONFIG", filename.to_string_lossy().as_ref())
+            .config(toml::toml! {
+                [kubernetes]
+                disabled = false
+                [kubernetes.context_aliases]
+                "gke_.*_(?P<cluster>[\\w-]+)" = "gke-$cluster"
+                "arn:aws:eks:(?P<region>[\\w-]+):\\d+:cluster/(?P<cluster>[\\w-]+)" = "$cluster ($region)"
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "{} in ",
+            Color::Cyan.bold().paint("☸ production (us-east-1)")
+        ));
+        assert_eq!(expected, actual);
+
+        dir.close()
+    }
+
+    #[test]
+    fn test_get_aliased_name() {
+        let mut aliases = IndexMap::new();
+        aliases.insert(String::from("dev.local.cluster.k8s"), "dev");
+        aliases.insert(String::from(".*/(\\w+)"), "$1");
+        aliases.insert(String::from("prod-.*"), "$prod");
+        aliases.insert(String::from("eu-(\\w+)"), "$$$1");
+
+        assert_eq!(get_aliased_name("dev.local.cluster.k8s", &aliases), "dev");
+        assert_eq!(get_aliased_name("cluster/staging", &aliases), "staging");
+        assert_eq!(get_aliased_name("minikube", &aliases), "minikube");
+        assert_eq!(get_aliased_name("prod-west", &aliases), "$prod");
+        assert_eq!(get_aliased_name("eu-west", &aliases), "$west");
+    }
+
+    #[test]
+    fn test_user_and_cluster() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let filename = dir.path().join("config");
+
+        let mut file = File::create(&filename)?;
+        file.write_all(
+            b"
+apiVersion: v1
+clusters: []
+contexts:
+  - context:
+      cluster: test_cluster
+      user: test_user
+    name: test_context
+current-context: test_context
+kind: Config
+preferences: {}
+users: []
+",
+        )?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("kubernetes")
+            .path(dir.path())
+            .env("KUBECONFIG", filename.to_string_lossy().as_ref())
+            .config(toml::toml! {
+                [kubernetes]
+                disabled = false
+                format = "[$user@$cluster( \\($namespace\\))]($style) in "
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "{} in ",
+            Color::Cyan.bold().paint("test_user@test_cluster")
+        ));
+        assert_eq!(expected, actual);
+
+        dir.close()
+    }
+
+    fn write_test_config(dir: &std::path::Path) -> io::Result<std::path::PathBuf> {
+        let filename = dir.join("config");
+
+        let mut file = File::create(&filename)?;
+        file.write_all(
+            b"
+apiVersion: v1
+clusters: []
+contexts: []
+current-context: test_context
+kind: Config
+preferences: {}
+users: []
+",
+        )?;
+        file.sync_all()?;
+
+        Ok(filename)
+    }
+
+    #[test]
+    fn test_detect_files_not_matched() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let filename = write_test_config(dir.path())?;
+
+        let actual = ModuleRenderer::new("kubernetes")
+            .path(dir.path())
+            .env("KUBECONFIG", filename.to_string_lossy().as_ref())
+            .config(toml::toml! {
+                [kubernetes]
+                disabled = false
+                detect_files = ["kustomization.yaml", "Chart.yaml"]
+            })
+            .collect();
+
+        assert_eq!(None, actual);
+
+        dir.close()
+    }
+
+    #[test]
+    fn test_detect_files_matched() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let filename = write_test_config(dir.path())?;
+        File::create(dir.path().join("Chart.yaml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("kubernetes")
+            .path(dir.path())
+            .env("KUBECONFIG", filename.to_string_lossy().as_ref())
+            .config(toml::toml! {
+                [kubernetes]
+                disabled = false
+                detect_files = ["kustomization.yaml", "Chart.yaml"]
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "{} in ",
+            Color::Cyan.bold().paint("☸ test_context")
+        ));
+        assert_eq!(expected, actual);
+
+        dir.close()
+    }
+
+    #[test]
+    fn test_detect_extensions_matched() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let filename = write_test_config(dir.path())?;
+        File::create(dir.path().join("deployment.yaml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("kubernetes")
+            .path(dir.path())
+            .env("KUBECONFIG", filename.to_string_lossy().as_ref())
+            .config(toml::toml! {
+                [kubernetes]
+                disabled = false
+                detect_extensions = ["yaml"]
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "{} in ",
+            Color::Cyan.bold().paint("☸ test_context")
+        ));
+        assert_eq!(expected, actual);
+
+        dir.close()
+    }
+
+    #[test]
+    fn test_multiple_config_files_first_wins() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let filename_first = dir.path().join("config_first");
+        let mut file_first = File::create(&filename_first)?;
+        file_first.write_all(
+            b"
+apiVersion: v1
+clusters: []
+contexts:
+  - context:
+      cluster: test_cluster
+      user: test_user
+      namespace: first_namespace
+    name: test_context
+current-context: test_context
+kind: Config
+preferences: {}
+users: []
+",
+        )?;
+        file_first.sync_all()?;
+
+        let filename_second = dir.path().join("config_second");
+        let mut file_second = File::create(&filename_second)?;
+        file_second.write_all(
+            b"
+apiVersion: v1
+clusters: []
+contexts:
+  - context:
+      cluster: test_cluster
+      user: test_user
+      namespace: second_namespace
+    name: test_context
+current-context: other_context
+kind: Config
+preferences: {}
+users: []
+",
+        )?;
+        file_second.sync_all()?;
+
+        let kubeconfig = env::join_paths(
+            [
+                std::path::PathBuf::new(),
+                filename_first.clone(),
+                filename_second,
+            ]
+            .iter(),
+        )
+        .unwrap();
+        let actual = ModuleRenderer::new("kubernetes")
+            .path(dir.path())
+            .env("KUBECONFIG", kubeconfig.to_string_lossy())
+            .config(toml::toml! {
+                [kubernetes]
+                disabled = false
+                format = "[$context \\($namespace\\) from $kubeconfig]($style)"
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "{}",
+            Color::Cyan.bold().paint(format!(
+                "test_context (first_namespace) from {}",
+                filename_first.to_string_lossy()
+            ))
+        ));
+        assert_eq!(expected, actual);
+
+        dir.close()
+    }
 }
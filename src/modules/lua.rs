@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::lua::LuaConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 use regex::Regex;
@@ -14,20 +14,21 @@ const LUA_VERSION_PATERN: &str = "(?P<version>[\\d\\.]+[a-z\\-]*[1-9]*)[^\\s]*";
 ///     - Current directory contains a `lua` directory
 ///     - Current directory contains a file with the `.lua` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("lua");
+    let config = LuaConfig::try_load(module.config);
+
     let is_lua_project = context
         .try_begin_scan()?
-        .set_files(&[".lua-version"])
-        .set_folders(&["lua"])
-        .set_extensions(&["lua"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_lua_project {
         return None;
     }
 
-    let mut module = context.new_module("lua");
-    let config = LuaConfig::try_load(module.config);
-    let lua_version = format_lua_version(&get_lua_version(&config.lua_binary)?)?;
+    let lua_version = parse_lua_version(&get_lua_version(&config.lua_binary)?)?;
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -39,7 +40,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(&lua_version)),
+                "version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    &lua_version,
+                    config.version_format,
+                )
+                .map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -69,7 +76,7 @@ fn get_lua_version(lua_binary: &str) -> Option<String> {
     }
 }
 
-fn format_lua_version(lua_stdout: &str) -> Option<String> {
+fn parse_lua_version(lua_stdout: &str) -> Option<String> {
     // lua -v output looks like this:
     // Lua 5.4.0  Copyright (C) 1994-2020 Lua.org, PUC-Rio
 
@@ -78,7 +85,7 @@ fn format_lua_version(lua_stdout: &str) -> Option<String> {
     let re = Regex::new(LUA_VERSION_PATERN).ok()?;
     let captures = re.captures(lua_stdout)?;
     let version = &captures["version"];
-    Some(format!("v{}", version))
+    Some(version.to_string())
 }
 
 #[cfg(test)]
@@ -152,15 +159,15 @@ mod tests {
     }
 
     #[test]
-    fn test_format_lua_version() {
+    fn test_parse_lua_version() {
         let lua_input = "Lua 5.4.0  Copyright (C) 1994-2020 Lua.org, PUC-Rio";
-        assert_eq!(format_lua_version(lua_input), Some("v5.4.0".to_string()));
+        assert_eq!(parse_lua_version(lua_input), Some("5.4.0".to_string()));
 
         let luajit_input =
             "LuaJIT 2.1.0-beta3 -- Copyright (C) 2005-2017 Mike Pall. http://luajit.org/";
         assert_eq!(
-            format_lua_version(luajit_input),
-            Some("v2.1.0-beta3".to_string())
+            parse_lua_version(luajit_input),
+            Some("2.1.0-beta3".to_string())
         );
     }
 }
@@ -1,133 +1,231 @@
 // While adding out new module add out module to src/module.rs ALL_MODULES const array also.
+#[cfg(feature = "languages")]
+mod asdf;
+#[cfg(feature = "cloud")]
 mod aws;
 mod character;
+#[cfg(feature = "languages")]
 mod cmake;
 mod cmd_duration;
+#[cfg(feature = "languages")]
 mod conda;
+#[cfg(feature = "languages")]
 mod crystal;
 pub(crate) mod custom;
+#[cfg(feature = "languages")]
 mod dart;
 mod directory;
+#[cfg(feature = "cloud")]
 mod docker_context;
+#[cfg(feature = "languages")]
 mod dotnet;
+#[cfg(feature = "languages")]
 mod elixir;
+#[cfg(feature = "languages")]
 mod elm;
 mod env_var;
+#[cfg(feature = "languages")]
 mod erlang;
+#[cfg(feature = "cloud")]
 mod gcloud;
 mod git_branch;
 mod git_commit;
 mod git_state;
 mod git_status;
+#[cfg(feature = "languages")]
 mod golang;
+#[cfg(feature = "cloud")]
 mod helm;
+#[cfg(feature = "hg")]
 mod hg_branch;
 mod hostname;
+#[cfg(feature = "languages")]
 mod java;
 mod jobs;
+#[cfg(feature = "languages")]
 mod julia;
+#[cfg(feature = "languages")]
 mod kotlin;
+#[cfg(feature = "cloud")]
 mod kubernetes;
 mod line_break;
+#[cfg(feature = "languages")]
 mod lua;
 mod memory_usage;
+#[cfg(feature = "languages")]
 mod nim;
 mod nix_shell;
+#[cfg(feature = "languages")]
 mod nodejs;
+#[cfg(feature = "languages")]
 mod ocaml;
+#[cfg(feature = "cloud")]
 mod openstack;
+#[cfg(feature = "languages")]
 mod package;
+#[cfg(feature = "languages")]
 mod perl;
+#[cfg(feature = "languages")]
 mod php;
+pub(crate) mod plugin;
+#[cfg(feature = "cloud")]
+mod podman;
+#[cfg(feature = "languages")]
 mod purescript;
+#[cfg(feature = "languages")]
 mod python;
+#[cfg(feature = "languages")]
 mod ruby;
+#[cfg(feature = "languages")]
 mod rust;
 mod shlvl;
+#[cfg(feature = "cloud")]
 mod singularity;
 mod status;
+#[cfg(feature = "languages")]
 mod swift;
+#[cfg(feature = "cloud")]
 mod terraform;
 mod time;
 mod username;
 mod utils;
+#[cfg(feature = "languages")]
 mod zig;
 
 #[cfg(feature = "battery")]
 mod battery;
 
 use crate::config::RootModuleConfig;
-use crate::context::{Context, Shell};
+use crate::context::{self, Context, Shell};
 use crate::module::Module;
+use crate::palette;
+use crate::trace;
 use std::time::Instant;
 
 pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
     let start: Instant = Instant::now();
+    let _span = trace::span("module", || module);
+    let _palette = palette::use_palette(context.palette());
+    context::take_detection();
 
     let mut m: Option<Module> = {
         match module {
             // Keep these ordered alphabetically.
             // Default ordering is handled in configs/starship_root.rs
+            #[cfg(feature = "languages")]
+            "asdf" => asdf::module(context),
+            #[cfg(feature = "cloud")]
             "aws" => aws::module(context),
             #[cfg(feature = "battery")]
             "battery" => battery::module(context),
             "character" => character::module(context),
+            #[cfg(feature = "languages")]
             "cmake" => cmake::module(context),
             "cmd_duration" => cmd_duration::module(context),
+            #[cfg(feature = "languages")]
             "conda" => conda::module(context),
+            #[cfg(feature = "languages")]
             "dart" => dart::module(context),
             "directory" => directory::module(context),
+            #[cfg(feature = "cloud")]
             "docker_context" => docker_context::module(context),
+            #[cfg(feature = "languages")]
             "dotnet" => dotnet::module(context),
+            #[cfg(feature = "languages")]
             "elixir" => elixir::module(context),
+            #[cfg(feature = "languages")]
             "elm" => elm::module(context),
+            #[cfg(feature = "languages")]
             "erlang" => erlang::module(context),
             "env_var" => env_var::module(context),
+            #[cfg(feature = "cloud")]
             "gcloud" => gcloud::module(context),
             "git_branch" => git_branch::module(context),
             "git_commit" => git_commit::module(context),
             "git_state" => git_state::module(context),
             "git_status" => git_status::module(context),
+            #[cfg(feature = "languages")]
             "golang" => golang::module(context),
+            #[cfg(feature = "cloud")]
             "helm" => helm::module(context),
+            #[cfg(feature = "hg")]
             "hg_branch" => hg_branch::module(context),
             "hostname" => hostname::module(context),
+            #[cfg(feature = "languages")]
             "java" => java::module(context),
             "jobs" => jobs::module(context),
+            #[cfg(feature = "languages")]
             "julia" => julia::module(context),
+            #[cfg(feature = "languages")]
             "kotlin" => kotlin::module(context),
+            #[cfg(feature = "cloud")]
             "kubernetes" => kubernetes::module(context),
             "line_break" => line_break::module(context),
+            #[cfg(feature = "languages")]
             "lua" => lua::module(context),
             "memory_usage" => memory_usage::module(context),
+            #[cfg(feature = "languages")]
             "nim" => nim::module(context),
             "nix_shell" => nix_shell::module(context),
+            #[cfg(feature = "languages")]
             "nodejs" => nodejs::module(context),
+            #[cfg(feature = "languages")]
             "ocaml" => ocaml::module(context),
+            #[cfg(feature = "cloud")]
             "openstack" => openstack::module(context),
+            #[cfg(feature = "languages")]
             "package" => package::module(context),
+            #[cfg(feature = "languages")]
             "perl" => perl::module(context),
+            #[cfg(feature = "languages")]
             "php" => php::module(context),
+            #[cfg(feature = "cloud")]
+            "podman" => podman::module(context),
+            #[cfg(feature = "languages")]
             "purescript" => purescript::module(context),
+            #[cfg(feature = "languages")]
             "python" => python::module(context),
+            #[cfg(feature = "languages")]
             "ruby" => ruby::module(context),
+            #[cfg(feature = "languages")]
             "rust" => rust::module(context),
             "shlvl" => shlvl::module(context),
+            #[cfg(feature = "cloud")]
             "singularity" => singularity::module(context),
+            #[cfg(feature = "languages")]
             "swift" => swift::module(context),
             "status" => status::module(context),
+            #[cfg(feature = "cloud")]
             "terraform" => terraform::module(context),
             "time" => time::module(context),
+            #[cfg(feature = "languages")]
             "crystal" => crystal::module(context),
             "username" => username::module(context),
+            #[cfg(feature = "languages")]
             "zig" => zig::module(context),
-            _ => {
-                eprintln!("Error: Unknown module {}. Use starship module --list to list out all supported modules.", module);
-                None
-            }
+            // Custom and plugin modules can also be printed on their own, e.g. `starship module custom.foo`
+            _ => match (
+                module.strip_prefix("custom."),
+                module.strip_prefix("plugin."),
+            ) {
+                (Some(name), _) if context.config.get_custom_module_config(name).is_some() => {
+                    custom::module(name, context)
+                }
+                (_, Some(name)) if context.config.get_plugin_config(name).is_some() => {
+                    plugin::module(name, context)
+                }
+                _ => {
+                    eprintln!("Error: Unknown module {}. Use starship module --list to list out all supported modules.", module);
+                    None
+                }
+            },
         }
     };
 
+    if let Some(m) = m.as_mut() {
+        m.detected_by = context::take_detection();
+    }
+
     let elapsed = start.elapsed();
     log::trace!("Took {:?} to compute module {:?}", elapsed, module);
     if elapsed.as_millis() < 1 {
@@ -144,6 +242,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
 
 pub fn description(module: &str) -> &'static str {
     match module {
+        "asdf" => "The tool versions pinned by asdf for the current directory",
         "aws" => "The current AWS region and profile",
         "battery" => "The current charge of the device's battery and its current charging status",
         "character" => {
@@ -184,6 +283,7 @@ pub fn description(module: &str) -> &'static str {
         "package" => "The package version of the current directory's project",
         "perl" => "The currently installed version of Perl",
         "php" => "The currently installed version of PHP",
+        "podman" => "The current podman connection",
         "purescript" => "The currently installed version of PureScript",
         "python" => "The currently installed version of Python",
         "ruby" => "The currently installed version of Ruby",
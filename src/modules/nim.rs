@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::nim::NimConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Nim version
@@ -10,19 +10,20 @@ use crate::utils;
 ///     - The current directory contains a file with extension `.nim`, `.nims`, or `.nimble`
 ///     - The current directory contains a `nim.cfg` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("nim");
+    let config = NimConfig::try_load(module.config);
+
     let is_nim_project = context
         .try_begin_scan()?
-        .set_files(&["nim.cfg"])
-        .set_extensions(&["nim", "nims", "nimble"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_nim_project {
         return None;
     }
 
-    let mut module = context.new_module("nim");
-    let config = NimConfig::try_load(module.config);
-
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -34,12 +35,17 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => utils::exec_cmd("nim", &["--version"])
-                    .map(|command_output| command_output.stdout)
-                    .and_then(|nim_version_output| {
-                        Some(format!("v{}", parse_nim_version(&nim_version_output)?))
-                    })
-                    .map(Ok),
+                "version" => {
+                    let nim_version_output = utils::exec_cmd("nim", &["--version"])?.stdout;
+                    let nim_version = parse_nim_version(&nim_version_output)?;
+                    VersionFormatter::format_module_version(
+                        context,
+                        module.get_name(),
+                        nim_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
                 _ => None,
             })
             .parse(None)
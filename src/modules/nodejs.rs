@@ -1,7 +1,8 @@
+use super::utils::pinned_version::{read_pinned_version, satisfies_pin};
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::nodejs::NodejsConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 use regex::Regex;
@@ -9,6 +10,10 @@ use semver::Version;
 use semver::VersionReq;
 use serde_json as json;
 use std::path::Path;
+use std::time::Duration;
+
+/// How long to wait for `node` and the package manager to report their versions
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// Creates a module with the current Node.js version
 ///
@@ -17,28 +22,72 @@ use std::path::Path;
 ///     - Current directory contains a `.ts` file
 ///     - Current directory contains a `package.json` or `.node-version` file
 ///     - Current directory contains a `node_modules` directory
+///
+/// Unless the current directory contains an `esy.lock` directory or a Deno config or lockfile
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_js_project = context
-        .try_begin_scan()?
-        .set_files(&["package.json", ".node-version"])
-        .set_extensions(&["js", "mjs", "cjs", "ts"])
-        .set_folders(&["node_modules"])
-        .is_match();
+    let mut module = context.new_module("nodejs");
+    let config = NodejsConfig::try_load(module.config);
 
-    let is_esy_project = context
+    let is_js_project = context
         .try_begin_scan()?
-        .set_folders(&["esy.lock"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
-    if !is_js_project || is_esy_project {
+    if !is_js_project {
         return None;
     }
 
-    let mut module = context.new_module("nodejs");
-    let config = NodejsConfig::try_load(module.config);
-    let nodejs_version = utils::exec_cmd("node", &["--version"])?.stdout;
+    // Volta's pin is what its `node` shim runs, so it takes precedence over nvm's and nodenv's
+    let pinned_version = get_volta_version(&context.current_dir)
+        .or_else(|| read_pinned_version(&context.current_dir, &[".nvmrc", ".node-version"]));
+    // The package manager pinned for corepack, checked against the installed one only when
+    // it's shown, since running it is slow
+    let package_manager_pin = get_package_manager_pin(&context.current_dir);
+    let checked_package_manager = package_manager_pin
+        .as_ref()
+        .filter(|_| config.format.contains("$package_manager"));
+    // Reading the version pinned by Volta, nvm or nodenv is faster than running `node`
+    let is_installed_version_needed = !config.use_pinned_version
+        || pinned_version.is_none()
+        || config.format.contains("$installed");
+
+    // `node` and the package manager are run at once, so that their latencies overlap
+    let mut commands: Vec<(&str, &[&str])> = Vec::new();
+    if is_installed_version_needed {
+        commands.push(("node", &["--version"]));
+    }
+    if let Some((package_manager, _)) = checked_package_manager {
+        commands.push((package_manager, &["--version"]));
+    }
+    let mut outputs = utils::exec_cmds(&commands, COMMAND_TIMEOUT).into_iter();
+
+    let installed_version = if is_installed_version_needed {
+        outputs
+            .next()
+            .flatten()
+            .map(|output| output.stdout.trim().trim_start_matches('v').to_string())
+    } else {
+        None
+    };
+    let nodejs_version = match &pinned_version {
+        Some(pinned_version) if config.use_pinned_version => pinned_version.clone(),
+        _ => installed_version.clone()?,
+    };
     let engines_version = get_engines_version(&context.current_dir);
-    let in_engines_range = check_engines_version(&nodejs_version, engines_version);
+    let in_engines_range = check_engines_version(&nodejs_version, engines_version.clone());
+    let is_package_manager_pinned = match checked_package_manager {
+        Some((_, version)) => !matches!(
+            outputs.next().flatten(),
+            Some(output) if output.stdout.trim() != version
+        ),
+        None => true,
+    };
+    // Remind users to `nvm use` when the active node isn't the pinned one
+    let expected_version = pinned_version
+        .as_ref()
+        .filter(|pinned_version| !satisfies_pin(&nodejs_version, pinned_version));
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -47,7 +96,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map_style(|variable| match variable {
                 "style" => {
-                    if in_engines_range {
+                    if in_engines_range && expected_version.is_none() && is_package_manager_pinned {
                         Some(Ok(config.style))
                     } else {
                         Some(Ok(config.not_capable_style))
@@ -56,7 +105,69 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(nodejs_version.trim())),
+                "version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    &nodejs_version,
+                    config.version_format,
+                )
+                .map(Ok),
+                "expected_version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    expected_version?,
+                    config.version_format,
+                )
+                .map(Ok),
+                "engines_version" => engines_version.clone().map(Ok),
+                "expected_engines_version" => engines_version
+                    .clone()
+                    .filter(|_| !in_engines_range)
+                    .map(Ok),
+                // A flag without a value, to show conditional groups like `($in_engines_range✓)`
+                "in_engines_range" => engines_version
+                    .as_ref()
+                    .filter(|_| in_engines_range)
+                    .map(|_| Ok(String::new())),
+                "package_manager" => {
+                    let package_manager = match &package_manager_pin {
+                        Some((package_manager, _)) => package_manager.as_str(),
+                        None => get_package_manager(&context.current_dir)?,
+                    };
+                    let symbol = config
+                        .package_manager_symbols
+                        .get(package_manager)
+                        .copied()
+                        .unwrap_or(package_manager);
+                    Some(Ok(symbol.to_string()))
+                }
+                "package_manager_version" => package_manager_pin
+                    .as_ref()
+                    .map(|(_, version)| Ok(version.clone())),
+                "workspace_root" => {
+                    let workspace_root = get_workspace_root(&context.current_dir)?;
+                    Some(Ok(workspace_root
+                        .file_name()?
+                        .to_string_lossy()
+                        .to_string()))
+                }
+                "workspace_package" => get_workspace_package(&context.current_dir).map(Ok),
+                "installed" => {
+                    let pinned_version = pinned_version
+                        .as_ref()
+                        .filter(|_| config.use_pinned_version)?;
+                    let installed_version = installed_version.as_ref()?;
+                    if satisfies_pin(installed_version, pinned_version) {
+                        return None;
+                    }
+                    VersionFormatter::format_module_version(
+                        context,
+                        module.get_name(),
+                        installed_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
                 _ => None,
             })
             .parse(None)
@@ -73,6 +184,84 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// Find the root of the npm, yarn or pnpm workspace the directory is in
+fn get_workspace_root(base_dir: &Path) -> Option<&Path> {
+    base_dir.ancestors().find(|dir| {
+        if dir.join("pnpm-workspace.yaml").is_file() {
+            return true;
+        }
+        let package_json = utils::read_file(dir.join("package.json"))
+            .ok()
+            .and_then(|json_str| json::from_str::<json::Value>(&json_str).ok());
+        matches!(package_json, Some(package_json) if package_json.get("workspaces").is_some())
+    })
+}
+
+/// Find the name of the workspace package the directory is in, which is the nearest
+/// `package.json` below the workspace root
+fn get_workspace_package(base_dir: &Path) -> Option<String> {
+    let workspace_root = get_workspace_root(base_dir)?;
+    let package_dir = base_dir
+        .ancestors()
+        .take_while(|dir| *dir != workspace_root)
+        .find(|dir| dir.join("package.json").is_file())?;
+
+    let name = utils::read_file(package_dir.join("package.json"))
+        .ok()
+        .and_then(|json_str| json::from_str::<json::Value>(&json_str).ok())
+        .and_then(|package_json| Some(package_json.get("name")?.as_str()?.to_string()));
+    // Unnamed packages are shown with their path in the workspace
+    name.or_else(|| {
+        let path = package_dir.strip_prefix(workspace_root).ok()?;
+        Some(path.to_string_lossy().to_string())
+    })
+}
+
+/// Lockfiles of the package managers, in the order they are checked when there are several
+const LOCKFILES: &[(&str, &str)] = &[
+    ("pnpm-lock.yaml", "pnpm"),
+    ("yarn.lock", "yarn"),
+    ("bun.lockb", "bun"),
+    ("bun.lock", "bun"),
+    ("package-lock.json", "npm"),
+    ("npm-shrinkwrap.json", "npm"),
+];
+
+/// Find the package manager from the nearest lockfile, which is at the root of a monorepo
+fn get_package_manager(base_dir: &Path) -> Option<&'static str> {
+    base_dir.ancestors().find_map(|dir| {
+        LOCKFILES
+            .iter()
+            .find(|(lockfile, _)| dir.join(lockfile).is_file())
+            .map(|(_, package_manager)| *package_manager)
+    })
+}
+
+/// Find the package manager pinned by the `packageManager` field of the nearest `package.json`
+/// that has one, e.g. `yarn@3.2.0+sha224.953c8233` pins yarn 3.2.0
+fn get_package_manager_pin(base_dir: &Path) -> Option<(String, String)> {
+    let pin = base_dir.ancestors().find_map(|dir| {
+        let json_str = utils::read_file(dir.join("package.json")).ok()?;
+        let package_json: json::Value = json::from_str(&json_str).ok()?;
+        Some(package_json.get("packageManager")?.as_str()?.to_string())
+    })?;
+
+    let at = pin.find('@')?;
+    let package_manager = &pin[..at];
+    let version = pin[at + 1..].split('+').next()?;
+    Some((package_manager.to_string(), version.to_string()))
+}
+
+/// Find the node version pinned by the `volta` section of the nearest `package.json`
+fn get_volta_version(base_dir: &Path) -> Option<String> {
+    let json_str = base_dir
+        .ancestors()
+        .find_map(|dir| utils::read_file(dir.join("package.json")).ok())?;
+    let package_json: json::Value = json::from_str(&json_str).ok()?;
+    let version = package_json.get("volta")?.get("node")?.as_str()?;
+    Some(version.trim_start_matches('v').to_string())
+}
+
 fn get_engines_version(base_dir: &Path) -> Option<String> {
     let json_str = utils::read_file(base_dir.join("package.json")).ok()?;
     let package_json: json::Value = json::from_str(&json_str).ok()?;
@@ -89,12 +278,11 @@ fn check_engines_version(nodejs_version: &str, engines_version: Option<String>)
         Err(_e) => return true,
     };
     let re = Regex::new(r"\d+\.\d+\.\d+").unwrap();
-    let version = re
-        .captures(nodejs_version)
-        .unwrap()
-        .get(0)
-        .unwrap()
-        .as_str();
+    // Pins like `14` don't have all the components
+    let version = match re.captures(nodejs_version).and_then(|caps| caps.get(0)) {
+        Some(version) => version.as_str(),
+        None => return true,
+    };
     let v = match Version::parse(version) {
         Ok(v) => v,
         Err(_e) => return true,
@@ -130,6 +318,23 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    fn folder_with_package_json_and_version_format() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("package.json"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                version_format = "${major}.${minor}"
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ 12.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
     #[test]
     fn folder_with_package_json_and_esy_lock() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -165,6 +370,388 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    fn folder_with_ts_file_and_detect_extensions_without_ts() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("index.ts"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                detect_extensions = ["js", "mjs", "cjs"]
+            })
+            .collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_custom_detect_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".yarnrc.yml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                detect_files = [".yarnrc.yml"]
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v12.0.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_yarn_lock() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("package.json"))?.sync_all()?;
+        File::create(dir.path().join("yarn.lock"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( $package_manager)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("⬢ v12.0.0 yarn")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn workspace_package_with_pnpm_lock_in_root() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("pnpm-lock.yaml"))?.sync_all()?;
+        let package_dir = dir.path().join("packages").join("app");
+        fs::create_dir_all(&package_dir)?;
+        File::create(package_dir.join("package.json"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(&package_dir)
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( $package_manager)]($style) "
+                package_manager_symbols = { pnpm = "📦" }
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("⬢ v12.0.0 📦")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_package_manager_pin() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"packageManager": "yarn@1.22.10"}"#,
+        )?;
+        File::create(dir.path().join("package-lock.json"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( $package_manager@$package_manager_version)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("⬢ v12.0.0 yarn@1.22.10")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_package_manager_pin_not_matching_installed_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"packageManager": "yarn@3.2.0+sha224.953c8233f7a92884eee2de69a1b92d1f2ec1655e66d08071ba9a02fa"}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( $package_manager@$package_manager_version)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("⬢ v12.0.0 yarn@3.2.0")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_volta_pin_not_matching_node_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"volta": {"node": "14.2.0", "yarn": "1.22.10"}}"#,
+        )?;
+        fs::write(dir.path().join(".nvmrc"), "12.0.0\n")?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( \\(expected $expected_version\\))]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("⬢ v12.0.0 (expected v14.2.0)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_volta_pin_and_pinned_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"volta": {"node": "14.2.0"}}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                use_pinned_version = true
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v14.2.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_in_yarn_workspace_package() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root_dir = dir.path().join("monorepo");
+        let package_dir = root_dir.join("packages").join("app");
+        fs::create_dir_all(&package_dir)?;
+        fs::write(
+            root_dir.join("package.json"),
+            r#"{"private": true, "workspaces": ["packages/*"]}"#,
+        )?;
+        fs::write(package_dir.join("package.json"), r#"{"name": "@acme/app"}"#)?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(&package_dir)
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( $workspace_root:$workspace_package)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("⬢ v12.0.0 monorepo:@acme/app")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_in_pnpm_workspace() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root_dir = dir.path().join("monorepo");
+        let package_dir = root_dir.join("tools");
+        fs::create_dir_all(&package_dir)?;
+        fs::write(
+            root_dir.join("pnpm-workspace.yaml"),
+            "packages:\n  - tools\n",
+        )?;
+        fs::write(root_dir.join("package.json"), "{}")?;
+        fs::write(package_dir.join("package.json"), "{}")?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(&package_dir)
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( $workspace_root)( $workspace_package)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("⬢ v12.0.0 monorepo tools")
+        ));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(&root_dir)
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( $workspace_root)( $workspace_package)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("⬢ v12.0.0 monorepo")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn engines_version_variables() -> io::Result<()> {
+        let format = "via [$symbol$version( $in_engines_range✓)( \\(wants $expected_engines_version\\))]($style) ";
+        let dir = tempfile::tempdir()?;
+        let config = toml::toml! {
+            [nodejs]
+            format = format
+        };
+
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"engines": {"node": ">=20"}}"#,
+        )?;
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("⬢ v12.0.0 (wants >=20)")
+        ));
+        assert_eq!(expected, actual);
+
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"engines": {"node": "^12.0.0"}}"#,
+        )?;
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(config.clone())
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v12.0.0 ✓")));
+        assert_eq!(expected, actual);
+
+        fs::write(dir.path().join("package.json"), "{}")?;
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(config)
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v12.0.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_nvmrc_not_matching_node_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".nvmrc"), "14.2.0\n")?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( \\(expected $expected_version\\))]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("⬢ v12.0.0 (expected v14.2.0)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_node_version_matching_node_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".node-version"), "v12\n")?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( \\(expected $expected_version\\))]($style) "
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v12.0.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_nvmrc_and_pinned_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".nvmrc"), "v14\n")?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( \\(installed $installed\\))]($style) "
+                use_pinned_version = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Green.bold().paint("⬢ v14 (installed v12.0.0)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_node_version_matching_installed_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".node-version"), "12.0\n")?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol$version( \\(installed $installed\\))]($style) "
+                use_pinned_version = true
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("⬢ v12.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_ts_file_and_deno_json() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.ts"))?.sync_all()?;
+        File::create(dir.path().join("deno.json"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs").path(dir.path()).collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_ts_file_and_deno_lock() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.ts"))?.sync_all()?;
+        File::create(dir.path().join("deno.lock"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs").path(dir.path()).collect();
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
     #[test]
     fn folder_with_mjs_file() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::ocaml::OCamlConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current OCaml version
@@ -14,11 +14,14 @@ use crate::utils;
 ///     - Current directory contains a `.merlin` file
 ///     - Current directory contains a file with `.ml`, `.mli`, `.re` or `.rei` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("ocaml");
+    let config: OCamlConfig = OCamlConfig::try_load(module.config);
+
     let is_ocaml_project = context
         .try_begin_scan()?
-        .set_files(&["dune", "dune-project", "jbuild", "jbuild-ignore", ".merlin"])
-        .set_folders(&["_opam", "esy.lock"])
-        .set_extensions(&["opam", "ml", "mli", "re", "rei"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_ocaml_project {
@@ -36,9 +39,6 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         utils::exec_cmd("ocaml", &["-vnum"])?.stdout
     };
 
-    let mut module = context.new_module("ocaml");
-    let config: OCamlConfig = OCamlConfig::try_load(module.config);
-
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
@@ -50,7 +50,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(format!("v{}", &ocaml_version.trim()))),
+                "version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    ocaml_version.trim(),
+                    config.version_format,
+                )
+                .map(Ok),
                 _ => None,
             })
             .parse(None)
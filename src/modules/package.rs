@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use std::path::Path;
+use std::str;
 
 use super::{Context, Module, RootModuleConfig};
 use crate::configs::package::PackageConfig;
@@ -16,7 +18,24 @@ use serde_json as json;
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("package");
     let config: PackageConfig = PackageConfig::try_load(module.config);
-    let module_version = get_package_version(&context.current_dir, &config)?;
+    let module_version = if config.search_upwards {
+        // The search stops at the root of the repository, so it isn't done outside of one
+        let repo_root = context
+            .get_repo()
+            .ok()
+            .and_then(|repo| repo.root.as_deref());
+        let mut dirs = context.current_dir.ancestors();
+        match repo_root {
+            Some(repo_root) if context.current_dir.starts_with(repo_root) => dirs
+                .take_while(|dir| dir.starts_with(repo_root))
+                .find_map(|dir| get_package_version(dir, &config))?,
+            _ => get_package_version(dirs.next()?, &config)?,
+        }
+    } else {
+        get_package_version(&context.current_dir, &config)?
+    };
+
+    let module_version = apply_version_metadata(&module_version, &config);
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -46,14 +65,33 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn extract_cargo_version(file_contents: &str) -> Option<String> {
+fn extract_cargo_version(file_contents: &str, base_dir: &Path) -> Option<String> {
     let cargo_toml: toml::Value = toml::from_str(file_contents).ok()?;
-    let raw_version = cargo_toml.get("package")?.get("version")?.as_str()?;
+    let version = cargo_toml.get("package")?.get("version")?;
 
-    let formatted_version = format_version(raw_version);
+    // Members of a workspace may inherit the version with `version.workspace = true`
+    let raw_version = if version.get("workspace").and_then(toml::Value::as_bool) == Some(true) {
+        get_cargo_workspace_version(base_dir)?
+    } else {
+        version.as_str()?.to_string()
+    };
+
+    let formatted_version = format_version(&raw_version);
     Some(formatted_version)
 }
 
+/// The `[workspace.package]` version of the workspace root above a member crate
+fn get_cargo_workspace_version(base_dir: &Path) -> Option<String> {
+    let workspace = base_dir.ancestors().skip(1).find_map(|dir| {
+        let cargo_toml = utils::read_file(dir.join("Cargo.toml")).ok()?;
+        let cargo_toml: toml::Value = toml::from_str(&cargo_toml).ok()?;
+        cargo_toml.get("workspace").cloned()
+    })?;
+
+    let version = workspace.get("package")?.get("version")?;
+    Some(version.as_str()?.to_string())
+}
+
 fn extract_package_version(file_contents: &str, display_private: bool) -> Option<String> {
     let package_json: json::Value = json::from_str(file_contents).ok()?;
 
@@ -71,26 +109,48 @@ fn extract_package_version(file_contents: &str, display_private: bool) -> Option
     Some(formatted_version)
 }
 
-fn extract_poetry_version(file_contents: &str) -> Option<String> {
-    let poetry_toml: toml::Value = toml::from_str(file_contents).ok()?;
-    let raw_version = poetry_toml
-        .get("tool")?
-        .get("poetry")?
-        .get("version")?
-        .as_str()?;
+/// The version of a `pyproject.toml`, from the PEP 621 `[project]` table or Poetry's
+/// `[tool.poetry]` table
+fn extract_pyproject_version(file_contents: &str) -> Option<String> {
+    let pyproject_toml: toml::Value = toml::from_str(file_contents).ok()?;
+    let project_version = pyproject_toml
+        .get("project")
+        .and_then(|project| project.get("version"));
+    let poetry_version = || pyproject_toml.get("tool")?.get("poetry")?.get("version");
+    let raw_version = project_version.or_else(poetry_version)?.as_str()?;
 
     let formatted_version = format_version(raw_version);
     Some(formatted_version)
 }
 
 fn extract_gradle_version(file_contents: &str) -> Option<String> {
-    let re = Regex::new(r#"(?m)^version ['"](?P<version>[^'"]+)['"]$"#).unwrap();
+    // Groovy scripts use `version '0.1.0'` or `version = '0.1.0'`, Kotlin scripts `version = "0.1.0"`
+    let re = Regex::new(r#"(?m)^version\s*=?\s*['"](?P<version>[^'"]+)['"]\s*$"#).unwrap();
     let caps = re.captures(file_contents)?;
 
     let formatted_version = format_version(&caps["version"]);
     Some(formatted_version)
 }
 
+fn extract_gradle_properties_version(file_contents: &str) -> Option<String> {
+    let re = Regex::new(r#"(?m)^\s*version\s*[=:]\s*(?P<version>\S+)\s*$"#).unwrap();
+    let caps = re.captures(file_contents)?;
+
+    let formatted_version = format_version(&caps["version"]);
+    Some(formatted_version)
+}
+
+/// The version of a Gradle project, from its build script or else its `gradle.properties`
+fn get_gradle_version(base_dir: &Path) -> Option<String> {
+    ["build.gradle", "build.gradle.kts"]
+        .iter()
+        .find_map(|file| extract_gradle_version(&utils::read_file(base_dir.join(file)).ok()?))
+        .or_else(|| {
+            let gradle_properties = utils::read_file(base_dir.join("gradle.properties")).ok()?;
+            extract_gradle_properties_version(&gradle_properties)
+        })
+}
+
 fn extract_composer_version(file_contents: &str) -> Option<String> {
     let composer_json: json::Value = json::from_str(file_contents).ok()?;
     let raw_version = composer_json.get("version")?.as_str()?;
@@ -116,6 +176,20 @@ fn extract_helm_package_version(file_contents: &str) -> Option<String> {
     Some(format_version(version))
 }
 
+/// The top-level `version` of a YAML manifest, like the `shard.yml` of a Crystal shard or the
+/// `galaxy.yml` of an Ansible collection
+fn extract_yaml_version(file_contents: &str) -> Option<String> {
+    let yaml = yaml_rust::YamlLoader::load_from_str(file_contents).ok()?;
+    let version = yaml.first()?["version"].as_str()?;
+    Some(format_version(version))
+}
+
+fn extract_vpkg_version(file_contents: &str) -> Option<String> {
+    let vpkg_json: json::Value = json::from_str(file_contents).ok()?;
+    let version = vpkg_json.get("version")?.as_str()?;
+    Some(format_version(version))
+}
+
 fn extract_mix_version(file_contents: &str) -> Option<String> {
     let re = Regex::new(r#"(?m)version: "(?P<version>[^"]+)""#).unwrap();
     let caps = re.captures(file_contents)?;
@@ -124,31 +198,36 @@ fn extract_mix_version(file_contents: &str) -> Option<String> {
     Some(formatted_version)
 }
 
-fn extract_maven_version(file_contents: &str) -> Option<String> {
+/// The version of a `pom.xml`, with the properties it references resolved
+///
+/// CI-friendly versions like `${revision}${changelist}` are looked up in the `<properties>` of the
+/// POM, and in the `-D` options of `.mvn/maven.config`, which take precedence like they do on the
+/// command line. Versions with other references, like `${project.parent.version}`, aren't shown.
+fn extract_maven_version(file_contents: &str, maven_config: Option<&str>) -> Option<String> {
     let mut reader = QXReader::from_str(file_contents);
     reader.trim_text(true);
 
     let mut buf = vec![];
-    let mut in_ver = false;
-    let mut depth = 0;
+    let mut path: Vec<Vec<u8>> = vec![];
+    let mut version = None;
+    let mut properties = HashMap::new();
     loop {
         match reader.read_event(&mut buf) {
-            Ok(QXEvent::Start(ref e)) => {
-                in_ver = depth == 1 && e.name() == b"version";
-                depth += 1;
-            }
+            Ok(QXEvent::Start(ref e)) => path.push(e.name().to_vec()),
             Ok(QXEvent::End(_)) => {
-                in_ver = false;
-                depth -= 1;
-            }
-            Ok(QXEvent::Text(t)) if in_ver => {
-                let ver = t.unescape_and_decode(&reader).ok();
-                return match ver {
-                    // Ignore version which is just a property reference
-                    Some(ref v) if !v.starts_with('$') => ver,
-                    _ => None,
-                };
+                path.pop();
             }
+            Ok(QXEvent::Text(t)) => match path.as_slice() {
+                [_, tag] if tag == b"version" => version = t.unescape_and_decode(&reader).ok(),
+                [_, properties_tag, property] if properties_tag == b"properties" => {
+                    if let (Ok(name), Ok(value)) =
+                        (str::from_utf8(property), t.unescape_and_decode(&reader))
+                    {
+                        properties.insert(name.to_string(), value);
+                    }
+                }
+                _ => (),
+            },
             Ok(QXEvent::Eof) => break,
             Ok(_) => (),
 
@@ -157,9 +236,33 @@ fn extract_maven_version(file_contents: &str) -> Option<String> {
                 break;
             }
         }
+        buf.clear();
     }
 
-    None
+    for option in maven_config.unwrap_or_default().split_whitespace() {
+        if let Some((name, value)) = option.strip_prefix("-D").and_then(|o| o.split_once('=')) {
+            properties.insert(name.to_string(), value.to_string());
+        }
+    }
+
+    let re = Regex::new(r"\$\{(?P<property>[^}]+)\}").unwrap();
+    let version = version?;
+    let mut resolved = true;
+    let version = re.replace_all(&version, |caps: &regex::Captures| {
+        properties
+            .get(&caps["property"])
+            .cloned()
+            .unwrap_or_else(|| {
+                resolved = false;
+                String::new()
+            })
+    });
+
+    if resolved && !version.is_empty() {
+        Some(version.into_owned())
+    } else {
+        None
+    }
 }
 
 fn extract_meson_version(file_contents: &str) -> Option<String> {
@@ -172,17 +275,27 @@ fn extract_meson_version(file_contents: &str) -> Option<String> {
     Some(formatted_version)
 }
 
+fn extract_cmake_version(file_contents: &str) -> Option<String> {
+    // Commands are case-insensitive, like `project(starship VERSION 0.1.0 LANGUAGES CXX)`
+    let re =
+        Regex::new(r#"(?i)\bproject\s*\([^)]*?\bVERSION\s+"?(?P<version>[0-9][0-9.]*)"#).unwrap();
+    let caps = re.captures(file_contents)?;
+
+    let formatted_version = format_version(&caps["version"]);
+    Some(formatted_version)
+}
+
 fn get_package_version(base_dir: &Path, config: &PackageConfig) -> Option<String> {
     if let Ok(cargo_toml) = utils::read_file(base_dir.join("Cargo.toml")) {
-        extract_cargo_version(&cargo_toml)
+        extract_cargo_version(&cargo_toml, base_dir)
     } else if let Ok(package_json) = utils::read_file(base_dir.join("package.json")) {
         extract_package_version(&package_json, config.display_private)
-    } else if let Ok(poetry_toml) = utils::read_file(base_dir.join("pyproject.toml")) {
-        extract_poetry_version(&poetry_toml)
+    } else if let Ok(pyproject_toml) = utils::read_file(base_dir.join("pyproject.toml")) {
+        extract_pyproject_version(&pyproject_toml)
     } else if let Ok(composer_json) = utils::read_file(base_dir.join("composer.json")) {
         extract_composer_version(&composer_json)
-    } else if let Ok(build_gradle) = utils::read_file(base_dir.join("build.gradle")) {
-        extract_gradle_version(&build_gradle)
+    } else if let Some(gradle_version) = get_gradle_version(base_dir) {
+        Some(gradle_version)
     } else if let Ok(project_toml) = utils::read_file(base_dir.join("Project.toml")) {
         extract_project_version(&project_toml)
     } else if let Ok(mix_file) = utils::read_file(base_dir.join("mix.exs")) {
@@ -190,14 +303,46 @@ fn get_package_version(base_dir: &Path, config: &PackageConfig) -> Option<String
     } else if let Ok(chart_file) = utils::read_file(base_dir.join("Chart.yaml")) {
         extract_helm_package_version(&chart_file)
     } else if let Ok(pom_file) = utils::read_file(base_dir.join("pom.xml")) {
-        extract_maven_version(&pom_file)
+        let maven_config = utils::read_file(base_dir.join(".mvn").join("maven.config")).ok();
+        extract_maven_version(&pom_file, maven_config.as_deref())
     } else if let Ok(meson_build) = utils::read_file(base_dir.join("meson.build")) {
         extract_meson_version(&meson_build)
+    } else if let Ok(cmake_lists) = utils::read_file(base_dir.join("CMakeLists.txt")) {
+        extract_cmake_version(&cmake_lists)
+    } else if let Ok(shard_yml) = utils::read_file(base_dir.join("shard.yml")) {
+        extract_yaml_version(&shard_yml)
+    } else if let Ok(vpkg_json) = utils::read_file(base_dir.join("vpkg.json")) {
+        extract_vpkg_version(&vpkg_json)
+    } else if let Ok(galaxy_yml) = utils::read_file(base_dir.join("galaxy.yml")) {
+        extract_yaml_version(&galaxy_yml)
     } else {
         None
     }
 }
 
+/// Shows, truncates or strips the prerelease tag and build metadata of a version like
+/// `v1.2.3-rc.1+abc`, according to the configured `version_metadata`
+fn apply_version_metadata(version: &str, config: &PackageConfig) -> String {
+    let without_build = version.split('+').next().unwrap_or(version);
+
+    match config.version_metadata {
+        "full" => version.to_string(),
+        "truncate" => without_build.to_string(),
+        "strip" => without_build
+            .split('-')
+            .next()
+            .unwrap_or(without_build)
+            .to_string(),
+        mode => {
+            log::warn!(
+                "Unknown version_metadata in [package]: {}, falling back to \"full\"",
+                mode
+            );
+            version.to_string()
+        }
+    }
+}
+
 fn format_version(version: &str) -> String {
     let cleaned = version.replace('"', "").trim().to_string();
     if cleaned.starts_with('v') {
@@ -212,9 +357,10 @@ mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io;
     use std::io::Write;
+    use std::process::Command;
     use tempfile::TempDir;
 
     #[test]
@@ -232,6 +378,38 @@ mod tests {
         assert_eq!(format_version("\"v0.1.0\""), "v0.1.0");
     }
 
+    #[test]
+    fn test_version_metadata() -> io::Result<()> {
+        let config_name = "Cargo.toml";
+        let config_content = toml::toml! {
+            [package]
+            name = "starship"
+            version = "0.1.0-rc.1+abc"
+        }
+        .to_string();
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, config_name, Some(&config_content))?;
+        expect_output(&project_dir, Some("v0.1.0-rc.1+abc"), None)?;
+        expect_output(
+            &project_dir,
+            Some("v0.1.0-rc.1"),
+            Some(toml::toml! {
+                [package]
+                version_metadata = "truncate"
+            }),
+        )?;
+        expect_output(
+            &project_dir,
+            Some("v0.1.0"),
+            Some(toml::toml! {
+                [package]
+                version_metadata = "strip"
+            }),
+        )?;
+        project_dir.close()
+    }
+
     #[test]
     fn test_extract_cargo_version() -> io::Result<()> {
         let config_name = "Cargo.toml";
@@ -248,6 +426,68 @@ mod tests {
         project_dir.close()
     }
 
+    #[test]
+    fn test_extract_cargo_workspace_version() -> io::Result<()> {
+        let workspace_content = toml::toml! {
+            [workspace]
+            members = ["member"]
+
+            [workspace.package]
+            version = "0.2.0"
+        }
+        .to_string();
+        let member_content = "[package]\nname = \"member\"\nversion.workspace = true\n";
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, "Cargo.toml", Some(&workspace_content))?;
+        fs::create_dir(project_dir.path().join("member"))?;
+        fill_config(&project_dir, "member/Cargo.toml", Some(member_content))?;
+
+        let actual = ModuleRenderer::new("package")
+            .path(project_dir.path().join("member"))
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.2.0")
+        ));
+        assert_eq!(actual, expected);
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_search_upwards() -> io::Result<()> {
+        let project_dir = create_project_dir()?;
+        Command::new("git")
+            .args(&["init", "--quiet"])
+            .current_dir(project_dir.path())
+            .output()?;
+        let config_content = json::json!({
+            "name": "starship",
+            "version": "0.1.0"
+        })
+        .to_string();
+        fill_config(&project_dir, "package.json", Some(&config_content))?;
+        let src_dir = project_dir.path().join("src").join("lib");
+        fs::create_dir_all(&src_dir)?;
+
+        let actual = ModuleRenderer::new("package").path(&src_dir).collect();
+        assert_eq!(actual, None);
+
+        let actual = ModuleRenderer::new("package")
+            .path(&src_dir)
+            .config(toml::toml! {
+                [package]
+                search_upwards = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+        assert_eq!(actual, expected);
+        project_dir.close()
+    }
+
     #[test]
     fn test_extract_package_version() -> io::Result<()> {
         let config_name = "package.json";
@@ -374,6 +614,41 @@ mod tests {
         project_dir.close()
     }
 
+    #[test]
+    fn test_extract_pep621_version() -> io::Result<()> {
+        let config_name = "pyproject.toml";
+        let config_content = toml::toml! {
+            [project]
+            name = "starship"
+            version = "0.1.0"
+
+            [tool.poetry]
+            version = "0.2.0"
+        }
+        .to_string();
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, config_name, Some(&config_content))?;
+        expect_output(&project_dir, Some("v0.1.0"), None)?;
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_pep621_dynamic_version() -> io::Result<()> {
+        let config_name = "pyproject.toml";
+        let config_content = toml::toml! {
+            [project]
+            name = "starship"
+            dynamic = ["version"]
+        }
+        .to_string();
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, config_name, Some(&config_content))?;
+        expect_output(&project_dir, None, None)?;
+        project_dir.close()
+    }
+
     #[test]
     fn test_extract_gradle_version_single_quote() -> io::Result<()> {
         let config_name = "build.gradle";
@@ -449,6 +724,38 @@ java {
         project_dir.close()
     }
 
+    #[test]
+    fn test_extract_gradle_kotlin_version() -> io::Result<()> {
+        let config_name = "build.gradle.kts";
+        let config_content = "plugins {
+    kotlin(\"jvm\") version \"1.4.21\"
+}
+version = \"0.1.0\"
+";
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, config_name, Some(&config_content))?;
+        expect_output(&project_dir, Some("v0.1.0"), None)?;
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_gradle_properties_version() -> io::Result<()> {
+        let project_dir = create_project_dir()?;
+        fill_config(
+            &project_dir,
+            "build.gradle",
+            Some("plugins {\n    id 'java'\n}\n"),
+        )?;
+        fill_config(
+            &project_dir,
+            "gradle.properties",
+            Some("org.gradle.jvmargs=-Xmx2g\nversion = 0.1.0\n"),
+        )?;
+        expect_output(&project_dir, Some("v0.1.0"), None)?;
+        project_dir.close()
+    }
+
     #[test]
     fn test_extract_mix_version() -> io::Result<()> {
         let config_name = "mix.exs";
@@ -713,6 +1020,98 @@ end";
         project_dir.close()
     }
 
+    #[test]
+    fn test_extract_shard_version() -> io::Result<()> {
+        let config_name = "shard.yml";
+        let config_content = "name: starship\nversion: 0.1.0\n\ndependencies:\n  kemal:\n    github: kemalcr/kemal\n";
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, config_name, Some(&config_content))?;
+        expect_output(&project_dir, Some("v0.1.0"), None)?;
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_vpkg_version() -> io::Result<()> {
+        let config_name = "vpkg.json";
+        let config_content = json::json!({
+            "name": "starship",
+            "version": "0.1.0",
+            "dependencies": []
+        })
+        .to_string();
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, config_name, Some(&config_content))?;
+        expect_output(&project_dir, Some("v0.1.0"), None)?;
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_galaxy_version() -> io::Result<()> {
+        let config_name = "galaxy.yml";
+        let config_content = "namespace: starship\nname: prompt\nversion: 0.1.0\n";
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, config_name, Some(&config_content))?;
+        expect_output(&project_dir, Some("v0.1.0"), None)?;
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_cmake_version() -> io::Result<()> {
+        let config_name = "CMakeLists.txt";
+        let config_content = "cmake_minimum_required(VERSION 3.10)
+
+PROJECT(starship
+    VERSION 0.1.0
+    LANGUAGES CXX)
+";
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, config_name, Some(&config_content))?;
+        expect_output(&project_dir, Some("v0.1.0"), None)?;
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_cmake_version_without_version() -> io::Result<()> {
+        let config_name = "CMakeLists.txt";
+        let config_content =
+            "cmake_minimum_required(VERSION 3.10)\nproject(starship LANGUAGES C)\n";
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, config_name, Some(&config_content))?;
+        expect_output(&project_dir, None, None)?;
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_maven_ci_friendly_version() -> io::Result<()> {
+        let pom = "
+            <project>
+              <modelVersion>4.0.0</modelVersion>
+              <version>${revision}${changelist}</version>
+              <properties>
+                <revision>1.2.3</revision>
+                <changelist>-SNAPSHOT</changelist>
+              </properties>
+            </project>";
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, "pom.xml", Some(&pom))?;
+        expect_output(&project_dir, Some("1.2.3-SNAPSHOT"), None)?;
+
+        fs::create_dir(project_dir.path().join(".mvn"))?;
+        fill_config(
+            &project_dir,
+            ".mvn/maven.config",
+            Some("-Drevision=2.0.0 -Dchangelist="),
+        )?;
+        expect_output(&project_dir, Some("2.0.0"), None)?;
+        project_dir.close()
+    }
+
     #[test]
     fn test_extract_maven_version_no_version_but_deps() -> io::Result<()> {
         // pom.xml with common nested tags and dependencies
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::perl::PerlConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current perl version
@@ -11,18 +11,14 @@ use crate::utils;
 ///     - Current directory contains a "Makefile.PL", "Build.PL",  "cpanfile", "cpanfile.snapshot",
 ///       "META.json", "META.yml", or ".perl-version" file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("perl");
+    let config: PerlConfig = PerlConfig::try_load(module.config);
+
     let is_perl_project = context
         .try_begin_scan()?
-        .set_files(&[
-            "Makefile.PL",
-            "Build.PL",
-            "cpanfile",
-            "cpanfile.snapshot",
-            "META.json",
-            "META.yml",
-            ".perl-version",
-        ])
-        .set_extensions(&["pl", "pm", "pod"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_perl_project {
@@ -31,9 +27,6 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     let perl_version = utils::exec_cmd("perl", &["-e", "printf q#%vd#,$^V;"])?.stdout;
 
-    let mut module = context.new_module("perl");
-    let config: PerlConfig = PerlConfig::try_load(module.config);
-
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -45,7 +38,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(format!("v{}", &perl_version))),
+                "version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    &perl_version,
+                    config.version_format,
+                )
+                .map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -1,7 +1,10 @@
+use semver::{Version, VersionReq};
+use serde_json as json;
+
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::php::PhpConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current PHP version
@@ -10,10 +13,14 @@ use crate::utils;
 ///     - Current directory contains a `.php` file
 ///     - Current directory contains a `composer.json` or `.php-version` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("php");
+    let config: PhpConfig = PhpConfig::try_load(module.config);
+
     let is_php_project = context
         .try_begin_scan()?
-        .set_files(&["composer.json", ".php-version"])
-        .set_extensions(&["php"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_php_project {
@@ -28,8 +35,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         ],
     ) {
         Some(php_cmd_output) => {
-            let mut module = context.new_module("php");
-            let config: PhpConfig = PhpConfig::try_load(module.config);
+            let php_version = php_cmd_output.stdout.trim();
+            let required_version = get_composer_php_requirement(context);
+            let is_capable = !matches!(
+                &required_version,
+                Some(constraint) if !satisfies_constraint(php_version, constraint)
+            );
 
             let parsed = StringFormatter::new(config.format).and_then(|formatter| {
                 formatter
@@ -38,11 +49,24 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                         _ => None,
                     })
                     .map_style(|variable| match variable {
-                        "style" => Some(Ok(config.style)),
+                        "style" => {
+                            if is_capable {
+                                Some(Ok(config.style))
+                            } else {
+                                Some(Ok(config.not_capable_style))
+                            }
+                        }
                         _ => None,
                     })
                     .map(|variable| match variable {
-                        "version" => Some(Ok(format_php_version(&php_cmd_output.stdout))),
+                        "version" => VersionFormatter::format_module_version(
+                            context,
+                            module.get_name(),
+                            php_version,
+                            config.version_format,
+                        )
+                        .map(Ok),
+                        "required_version" => required_version.clone().map(Ok),
                         _ => None,
                     })
                     .parse(None)
@@ -62,8 +86,50 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
-fn format_php_version(php_version: &str) -> String {
-    format!("v{}", php_version)
+/// The `require.php` constraint of `composer.json`, e.g. `^7.4 || ^8.0`
+fn get_composer_php_requirement(context: &Context) -> Option<String> {
+    let composer_json = utils::read_file(context.current_dir.join("composer.json")).ok()?;
+    let composer_json: json::Value = json::from_str(&composer_json).ok()?;
+    let constraint = composer_json.get("require")?.get("php")?.as_str()?;
+    Some(constraint.to_string())
+}
+
+/// Whether a version satisfies a Composer constraint
+///
+/// Composer separates alternatives with `||` and requirements with spaces or commas, so they are
+/// converted to requirements of the `semver` crate. Constraints that can't be parsed are always
+/// satisfied.
+fn satisfies_constraint(version: &str, constraint: &str) -> bool {
+    let version = match Version::parse(version) {
+        Ok(version) => version,
+        Err(_) => return true,
+    };
+
+    let alternatives: Vec<VersionReq> = constraint
+        .split('|')
+        .filter(|alternative| !alternative.trim().is_empty())
+        .filter_map(|alternative| {
+            let mut requirements: Vec<String> = Vec::new();
+            for token in alternative.split(|c: char| c == ',' || c.is_whitespace()) {
+                // Stability flags like `@dev` don't apply to the installed version
+                let token = token.split('@').next().unwrap_or_default();
+                match requirements.last_mut() {
+                    // The version of an operator separated from it, like `>= 7.2`
+                    Some(last) if last.chars().all(|c| "<>=!~^".contains(c)) => {
+                        last.push_str(token)
+                    }
+                    _ if !token.is_empty() => requirements.push(token.to_string()),
+                    _ => {}
+                }
+            }
+            VersionReq::parse(&requirements.join(", ")).ok()
+        })
+        .collect();
+
+    alternatives.is_empty()
+        || alternatives
+            .iter()
+            .any(|requirement| requirement.matches(&version))
 }
 
 #[cfg(test)]
@@ -71,15 +137,9 @@ mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io;
 
-    #[test]
-    fn test_format_php_version() {
-        let input = "7.3.8";
-        assert_eq!(format_php_version(input), "v7.3.8".to_string());
-    }
-
     #[test]
     fn folder_without_php_files() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -135,4 +195,39 @@ mod tests {
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn folder_with_unsatisfied_composer_requirement() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("composer.json"),
+            r#"{"require": {"php": ">= 7.4 <8.0", "ext-json": "*"}}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("php")
+            .path(dir.path())
+            .config(toml::toml! {
+                [php]
+                format = "via [$symbol$version( \\(requires $required_version\\))]($style) "
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("🐘 v7.3.8 (requires >= 7.4 <8.0)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_satisfies_constraint() {
+        assert!(satisfies_constraint("7.3.8", "^7.2"));
+        assert!(satisfies_constraint("8.0.1", "^7.4 || ^8.0"));
+        assert!(satisfies_constraint("7.3.8", ">=7.1,<8.0"));
+        assert!(satisfies_constraint("7.3.8", "7.3.*@dev"));
+        assert!(satisfies_constraint("7.3.8", "not a constraint"));
+        assert!(!satisfies_constraint("7.3.8", "^7.4 | ^8.0"));
+        assert!(!satisfies_constraint("7.3.8", ">= 7.4"));
+    }
 }
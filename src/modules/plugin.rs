@@ -0,0 +1,310 @@
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{Context, Module, RootModuleConfig};
+
+use crate::config::parse_style_string;
+use crate::configs::plugin::PluginConfig;
+use crate::segment::Segment;
+
+/// Creates a module from an external plugin, registered in the config with `[plugin.<name>]`
+///
+/// The plugin is a program named `starship-module-<name>` on the `PATH`, unless `command`
+/// is set. It's given the context as JSON on its stdin, with:
+///     - `name`: the name of the plugin
+///     - `current_dir`: the current directory
+///     - `shell`: the shell, e.g. `zsh`
+///     - `width`: the width of the terminal
+///     - `properties`: what the shell gave starship, e.g. `status_code` or `cmd_duration`
+///     - `config`: the `[plugin.<name>]` table, with any options of the plugin
+///
+/// It prints the segments of the module as JSON on its stdout, e.g.
+/// `{"segments": [{"text": "☀️ 21°C", "style": "bold yellow"}]}`. The style of a segment is
+/// optional, and defaults to the `style` option. The module is hidden if the plugin fails, or
+/// if it doesn't finish within `plugin_timeout`.
+pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
+    let toml_config = context.config.get_plugin_config(name)?;
+    let config = PluginConfig::load(toml_config);
+    if config.disabled {
+        return None;
+    }
+
+    let command = match config.command {
+        Some(command) => command.to_owned(),
+        None => format!("starship-module-{}", name),
+    };
+    let input = json!({
+        "name": name,
+        "current_dir": context.current_dir,
+        "shell": format!("{:?}", context.shell).to_lowercase(),
+        "width": context.width,
+        "properties": context.properties,
+        "config": toml_config,
+    });
+    let output = run_plugin(&command, &input.to_string(), context)?;
+
+    #[derive(Deserialize)]
+    struct PluginOutput {
+        segments: Vec<PluginSegment>,
+    }
+
+    #[derive(Deserialize)]
+    struct PluginSegment {
+        text: String,
+        style: Option<String>,
+    }
+
+    let output: PluginOutput = match serde_json::from_str(&output) {
+        Ok(output) => output,
+        Err(error) => {
+            log::warn!("Invalid output from plugin `{}`: {}", name, error);
+            return None;
+        }
+    };
+
+    let mut module = Module::new(
+        &format!("plugin.{}", name),
+        config.description,
+        Some(toml_config),
+    );
+    module.set_segments(
+        output
+            .segments
+            .into_iter()
+            .map(|segment| {
+                let style = segment.style.as_deref().unwrap_or(config.style);
+                Segment::new(parse_style_string(style), segment.text)
+            })
+            .collect(),
+    );
+    Some(module)
+}
+
+/// Runs a plugin with the context on its stdin, and returns its stdout if it succeeds
+///
+/// The plugin is killed if it's still running after `plugin_timeout`.
+fn run_plugin(command: &str, input: &str, context: &Context) -> Option<String> {
+    let timeout = Duration::from_millis(context.config.get_root_config().plugin_timeout);
+    let deadline = Instant::now() + timeout;
+    let path = match which::which(command) {
+        Ok(path) => path,
+        Err(error) => {
+            log::warn!("Unable to find plugin {:?}: {}", command, error);
+            return None;
+        }
+    };
+
+    let mut child = Command::new(path)
+        .current_dir(&context.current_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| log::warn!("Unable to run plugin {:?}: {}", command, error))
+        .ok()?;
+
+    // The input is written and the output read on their own threads, so that neither blocks
+    // on a full pipe while the plugin waits for the other
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = input.to_owned();
+        thread::spawn(move || {
+            // A plugin that doesn't read its input closes the pipe, which isn't an error
+            let _ = stdin.write_all(input.as_bytes());
+        });
+    }
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(1)),
+            Ok(None) => {
+                log::warn!("Plugin {:?} timed out after {:?}", command, timeout);
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            Err(error) => {
+                log::warn!("Unable to wait for plugin {:?}: {}", command, error);
+                return None;
+            }
+        }
+    };
+
+    // The pipes may still be held open by processes the plugin left running
+    let remaining = || deadline.saturating_duration_since(Instant::now());
+    if !status.success() {
+        let stderr = stderr.recv_timeout(remaining()).unwrap_or_default();
+        log::warn!(
+            "Plugin {:?} failed with {}: {}",
+            command,
+            status,
+            String::from_utf8_lossy(&stderr).trim()
+        );
+        return None;
+    }
+    match stdout.recv_timeout(remaining()) {
+        Ok(stdout) => String::from_utf8(stdout).ok(),
+        Err(_) => {
+            log::warn!("Plugin {:?} timed out after {:?}", command, timeout);
+            None
+        }
+    }
+}
+
+/// Reads a pipe of a plugin to its end on another thread
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>) -> mpsc::Receiver<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        let _ = sender.send(output);
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    #[cfg(unix)]
+    fn write_plugin(dir: &Path, name: &str, script: &str) -> io::Result<String> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}", script))?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn plugin_renders_segments() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let command = write_plugin(
+            dir.path(),
+            "starship-module-test",
+            r#"
+case "$(cat)" in
+    *'"units":"metric"'*) units=metric ;;
+    *) units=unknown ;;
+esac
+printf '{"segments": [{"text": "%s", "style": "bold red"}, {"text": " "}]}' "$units"
+"#,
+        )?;
+
+        let actual = ModuleRenderer::new("plugin.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [plugin.test]
+                command = command
+                units = "metric"
+                style = "green"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{}{}",
+            Color::Red.bold().paint("metric"),
+            Color::Green.paint(" ")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn failing_plugin_is_hidden() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let invalid = write_plugin(dir.path(), "invalid", "echo 'not json'")?;
+        let failing = write_plugin(dir.path(), "failing", "exit 1")?;
+
+        for command in vec![invalid, failing] {
+            let actual = ModuleRenderer::new("plugin.test")
+                .path(dir.path())
+                .config(toml::toml! {
+                    [plugin.test]
+                    command = command
+                })
+                .collect();
+            assert_eq!(None, actual);
+        }
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn slow_plugin_is_killed() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let command = write_plugin(
+            dir.path(),
+            "slow",
+            "sleep 5\necho '{\"segments\": [{\"text\": \"late\"}]}'",
+        )?;
+
+        let start = Instant::now();
+        let actual = ModuleRenderer::new("plugin.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                plugin_timeout = 100
+                [plugin.test]
+                command = command
+            })
+            .collect();
+        assert_eq!(None, actual);
+        assert!(start.elapsed() < Duration::from_secs(2));
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn plugin_writing_before_reading() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        // More output than fits in a pipe, before the input is read
+        let command = write_plugin(
+            dir.path(),
+            "verbose",
+            r#"
+text=$(head -c 200000 /dev/zero | tr '\0' 'a')
+printf '{"segments": [{"text": "%s"}]}' "$text"
+cat > /dev/null
+"#,
+        )?;
+
+        let actual = ModuleRenderer::new("plugin.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                plugin_timeout = 5000
+                [plugin.test]
+                command = command
+                style = ""
+            })
+            .collect();
+        assert_eq!(actual.map(|output| output.len()), Some(200_000));
+        dir.close()
+    }
+
+    #[test]
+    fn missing_plugin_is_hidden() {
+        let actual = ModuleRenderer::new("plugin.test")
+            .config(toml::toml! {
+                [plugin.test]
+                command = "starship-module-that-does-not-exist"
+            })
+            .collect();
+        assert_eq!(None, actual);
+    }
+}
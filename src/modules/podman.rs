@@ -0,0 +1,288 @@
+use std::path::PathBuf;
+
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::podman::PodmanConfig;
+use crate::formatter::StringFormatter;
+use crate::utils;
+
+/// A podman system connection, as listed by `podman system connection list`
+#[derive(Debug, PartialEq)]
+struct Connection {
+    name: String,
+    uri: String,
+}
+
+/// Creates a module with the active Podman connection
+///
+/// Will display the connection if the following criteria are met:
+///     - `CONTAINER_CONNECTION` is set
+///     - Or a default connection is set in `podman-connections.json`
+///     - Or `engine.active_service` is set in `containers.conf`
+///     - The current directory contains a file from `detect_files` (e.g. a `Containerfile`),
+///       or `only_with_files` is false
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("podman");
+    let config: PodmanConfig = PodmanConfig::try_load(module.config);
+
+    if config.only_with_files
+        && !context
+            .try_begin_scan()?
+            .set_files(&config.detect_files)
+            .set_extensions(&config.detect_extensions)
+            .set_folders(&config.detect_folders)
+            .is_match()
+    {
+        return None;
+    }
+
+    let connection = get_active_connection(context)?;
+    let machine = get_machine_name(&connection);
+    let mode = if is_rootful(&connection.uri) {
+        "rootful"
+    } else {
+        "rootless"
+    };
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "connection" => Some(Ok(connection.name.as_str())),
+                "machine" => machine.map(Ok),
+                "mode" => Some(Ok(mode)),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `podman`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+fn get_config_dir(context: &Context) -> Option<PathBuf> {
+    context
+        .get_env("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs_next::home_dir().map(|home| home.join(".config")))
+        .map(|config_dir| config_dir.join("containers"))
+}
+
+/// Find the connection podman would use, like `podman system connection list` does
+fn get_active_connection(context: &Context) -> Option<Connection> {
+    let config_dir = get_config_dir(context);
+    let connections_json = config_dir
+        .as_ref()
+        .and_then(|dir| utils::read_file(dir.join("podman-connections.json")).ok())
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok());
+    let containers_conf = context
+        .get_env("CONTAINERS_CONF")
+        .map(PathBuf::from)
+        .or_else(|| config_dir.map(|dir| dir.join("containers.conf")))
+        .and_then(|path| utils::read_file(path).ok())
+        .and_then(|conf| toml::from_str::<toml::Value>(&conf).ok());
+
+    let name = context
+        .get_env("CONTAINER_CONNECTION")
+        .or_else(|| {
+            connections_json
+                .as_ref()?
+                .get("Connection")?
+                .get("Default")?
+                .as_str()
+                .map(ToString::to_string)
+        })
+        .or_else(|| {
+            containers_conf
+                .as_ref()?
+                .get("engine")?
+                .get("active_service")?
+                .as_str()
+                .map(ToString::to_string)
+        })?;
+
+    let uri = connections_json
+        .as_ref()
+        .and_then(|json| {
+            json.get("Connection")?
+                .get("Connections")?
+                .get(&name)?
+                .get("URI")?
+                .as_str()
+        })
+        .or_else(|| {
+            containers_conf
+                .as_ref()?
+                .get("engine")?
+                .get("service_destinations")?
+                .get(&name)?
+                .get("uri")?
+                .as_str()
+        })
+        .unwrap_or_default()
+        .to_string();
+
+    Some(Connection { name, uri })
+}
+
+/// Connections to a podman machine point at localhost and are named after the machine,
+/// with a `-root` suffix for the rootful connection
+fn get_machine_name(connection: &Connection) -> Option<&str> {
+    let host = connection
+        .uri
+        .strip_prefix("ssh://")?
+        .split('/')
+        .next()?
+        .rsplit('@')
+        .next()?
+        .split(':')
+        .next()?;
+    if host != "localhost" && host != "127.0.0.1" {
+        return None;
+    }
+
+    let name = &connection.name;
+    Some(name.strip_suffix("-root").unwrap_or(name))
+}
+
+/// Rootful connections either log in as root or use the system-wide socket
+fn is_rootful(uri: &str) -> bool {
+    let address = uri.find("://").map_or(uri, |index| &uri[index + 3..]);
+    let path_start = address.find('/').unwrap_or(address.len());
+    let (authority, path) = address.split_at(path_start);
+
+    authority.starts_with("root@") || path.starts_with("/run/podman/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs::{self, File};
+    use std::io::{self, Write};
+
+    fn write_file(path: PathBuf, contents: &str) -> io::Result<()> {
+        fs::create_dir_all(path.parent().unwrap())?;
+        let mut file = File::create(path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    }
+
+    #[test]
+    fn no_connection() -> io::Result<()> {
+        let config_dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("podman")
+            .env("XDG_CONFIG_HOME", config_dir.path().to_string_lossy())
+            .config(toml::toml! {
+                [podman]
+                only_with_files = false
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        config_dir.close()
+    }
+
+    #[test]
+    fn only_with_files_and_no_files() -> io::Result<()> {
+        let project_dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("podman")
+            .path(project_dir.path())
+            .env("CONTAINER_CONNECTION", "prod")
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        project_dir.close()
+    }
+
+    #[test]
+    fn connection_from_containers_conf() -> io::Result<()> {
+        let project_dir = tempfile::tempdir()?;
+        write_file(project_dir.path().join("Containerfile"), "")?;
+        let config_dir = tempfile::tempdir()?;
+        write_file(
+            config_dir.path().join("containers/containers.conf"),
+            "[engine]
+active_service = \"podman-machine-default-root\"
+
+[engine.service_destinations.podman-machine-default-root]
+uri = \"ssh://root@127.0.0.1:50783/run/podman/podman.sock\"
+",
+        )?;
+
+        let actual = ModuleRenderer::new("podman")
+            .path(project_dir.path())
+            .env("XDG_CONFIG_HOME", config_dir.path().to_string_lossy())
+            .config(toml::toml! {
+                [podman]
+                format = "via [$symbol$machine \\($mode\\)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Purple
+                .bold()
+                .paint("🦭 podman-machine-default (rootful)")
+        ));
+
+        assert_eq!(expected, actual);
+        project_dir.close()?;
+        config_dir.close()
+    }
+
+    #[test]
+    fn connection_from_connections_json() -> io::Result<()> {
+        let config_dir = tempfile::tempdir()?;
+        write_file(
+            config_dir.path().join("containers/podman-connections.json"),
+            r#"{"Connection": {"Default": "build", "Connections": {"build": {"URI": "ssh://core@build.example.com/run/user/1000/podman/podman.sock"}}}}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("podman")
+            .env("XDG_CONFIG_HOME", config_dir.path().to_string_lossy())
+            .config(toml::toml! {
+                [podman]
+                format = "via [$symbol$connection( on $machine) \\($mode\\)]($style) "
+                only_with_files = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Purple.bold().paint("🦭 build (rootless)")
+        ));
+
+        assert_eq!(expected, actual);
+        config_dir.close()
+    }
+
+    #[test]
+    fn test_is_rootful() {
+        assert!(is_rootful(
+            "ssh://root@localhost:50783/run/podman/podman.sock"
+        ));
+        assert!(is_rootful("unix:///run/podman/podman.sock"));
+        assert!(!is_rootful(
+            "ssh://core@localhost:50783/run/user/501/podman/podman.sock"
+        ));
+        assert!(!is_rootful("unix:///run/user/1000/podman/podman.sock"));
+    }
+}
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::purescript::PureScriptConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current PureScript version
@@ -10,10 +10,14 @@ use crate::utils;
 ///     - Current directory contains a `spago.dhall` file
 ///     - Current directory contains a `*.purs` files
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("purescript");
+    let config: PureScriptConfig = PureScriptConfig::try_load(module.config);
+
     let is_purs_project = context
         .try_begin_scan()?
-        .set_files(&["spago.dhall"])
-        .set_extensions(&["purs"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_purs_project {
@@ -22,9 +26,6 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     let purs_version = utils::exec_cmd("purs", &["--version"])?.stdout;
 
-    let mut module = context.new_module("purescript");
-    let config: PureScriptConfig = PureScriptConfig::try_load(module.config);
-
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
@@ -36,7 +37,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(format!("v{}", purs_version.trim()))),
+                "version" => VersionFormatter::format_module_version(
+                    context,
+                    module.get_name(),
+                    purs_version.trim(),
+                    config.version_format,
+                )
+                .map(Ok),
                 _ => None,
             })
             .parse(None)
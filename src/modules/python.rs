@@ -1,9 +1,11 @@
 use ini::Ini;
+use regex::Regex;
 use std::path::Path;
 
+use super::utils::pinned_version::{read_pinned_version, satisfies_pin};
 use super::{Context, Module, RootModuleConfig};
 use crate::configs::python::PythonConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Python version
@@ -20,17 +22,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let config: PythonConfig = PythonConfig::try_load(module.config);
 
     let is_py_project = {
-        let base = context.try_begin_scan()?.set_files(&[
-            "requirements.txt",
-            ".python-version",
-            "pyproject.toml",
-            "Pipfile",
-            "tox.ini",
-            "setup.py",
-            "__init__.py",
-        ]);
+        let base = context
+            .try_begin_scan()?
+            .set_files(&config.detect_files)
+            .set_folders(&config.detect_folders);
         if config.scan_for_pyfiles {
-            base.set_extensions(&["py"]).is_match()
+            base.set_extensions(&config.detect_extensions).is_match()
         } else {
             base.is_match()
         }
@@ -42,15 +39,39 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
-    let python_version = if config.pyenv_version_name {
-        utils::exec_cmd("pyenv", &["version-name"])?.stdout
-    } else {
-        let version = config
+    let pinned_version = read_pinned_version(&context.current_dir, &[".python-version"]);
+    let get_installed_version = || {
+        config
             .python_binary
             .0
             .iter()
-            .find_map(|binary| get_python_version(binary))?;
-        format_python_version(&version)
+            .find_map(|binary| get_python_version(binary))
+            .map(|version| parse_python_version(&version))
+    };
+    let format_version = |version: &str| {
+        VersionFormatter::format_module_version(context, "python", version, config.version_format)
+    };
+    let (python_version, matches_pin) = match &pinned_version {
+        // Reading the version pinned by pyenv is faster than running `python`
+        Some(pinned_version) if config.use_pinned_version => (format_version(pinned_version), true),
+        // pyenv version names like `system` aren't versions
+        _ if config.pyenv_version_name => (
+            Some(
+                utils::exec_cmd("pyenv", &["version-name"])?
+                    .stdout
+                    .trim()
+                    .to_string(),
+            ),
+            true,
+        ),
+        _ => {
+            let installed_version = get_installed_version()?;
+            let matches_pin = !matches!(
+                &pinned_version,
+                Some(pinned_version) if !satisfies_pin(&installed_version, pinned_version)
+            );
+            (format_version(&installed_version), matches_pin)
+        }
     };
     let virtual_env = get_python_virtual_env(context);
     let pyenv_prefix = if config.pyenv_version_name {
@@ -66,13 +87,31 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => {
+                    if matches_pin {
+                        Some(Ok(config.style))
+                    } else {
+                        Some(Ok(config.not_capable_style))
+                    }
+                }
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(python_version.trim())),
-                "virtualenv" => virtual_env.as_ref().map(|e| Ok(e.trim())),
-                "pyenv_prefix" => Some(Ok(pyenv_prefix)),
+                "version" => python_version.clone().map(Ok),
+                "pinned_version" => format_version(pinned_version.as_ref()?).map(Ok),
+                "installed" => {
+                    let pinned_version = pinned_version
+                        .as_ref()
+                        .filter(|_| config.use_pinned_version)?;
+                    let installed_version = get_installed_version()?;
+                    if satisfies_pin(&installed_version, pinned_version) {
+                        return None;
+                    }
+                    format_version(&installed_version).map(Ok)
+                }
+                "virtualenv" => virtual_env.as_ref().map(|e| Ok(e.trim().to_string())),
+                "project_env" => get_project_env(context).map(Ok),
+                "pyenv_prefix" => Some(Ok(pyenv_prefix.to_string())),
                 _ => None,
             })
             .parse(None)
@@ -102,14 +141,12 @@ fn get_python_version(python_binary: &str) -> Option<String> {
     }
 }
 
-fn format_python_version(python_stdout: &str) -> String {
-    format!(
-        "v{}",
-        python_stdout
-            .trim_start_matches("Python ")
-            .trim_end_matches(":: Anaconda, Inc.")
-            .trim()
-    )
+fn parse_python_version(python_stdout: &str) -> String {
+    python_stdout
+        .trim_start_matches("Python ")
+        .trim_end_matches(":: Anaconda, Inc.")
+        .trim()
+        .to_string()
 }
 
 fn get_python_virtual_env(context: &Context) -> Option<String> {
@@ -121,12 +158,42 @@ fn get_python_virtual_env(context: &Context) -> Option<String> {
         })
     })
 }
+/// Find the name of the project the active virtual environment was created for
+///
+/// Poetry and PDM name the environments they manage like `<project>-<hash>-py3.8` and
+/// `<project>-<hash>-3.8`, and in-project environments are named `.venv` or `venv`.
+fn get_project_env(context: &Context) -> Option<String> {
+    let venv = context.get_env("VIRTUAL_ENV")?;
+    let venv_path = Path::new(&venv);
+    let venv_name = venv_path.file_name()?.to_str()?;
+
+    if venv_name == ".venv" || venv_name == "venv" {
+        let project_dir = venv_path.parent()?.file_name()?;
+        return Some(project_dir.to_string_lossy().to_string());
+    }
+
+    let re = Regex::new(r"^(.+)-[\w-]{8}-(py)?\d+\.\d+$").unwrap();
+    let project = re.captures(venv_name)?.get(1)?.as_str();
+    Some(project.to_string())
+}
+
+/// Read the `prompt` that venv and virtualenv show for the environment
+///
+/// Older versions of virtualenv store it with the parentheses around it, like `(foo) `.
 fn get_prompt_from_venv(venv_path: &Path) -> Option<String> {
-    Ini::load_from_file(venv_path.join("pyvenv.cfg"))
-        .ok()?
-        .general_section()
-        .get("prompt")
-        .map(String::from)
+    let venv_cfg = Ini::load_from_file(venv_path.join("pyvenv.cfg")).ok()?;
+    let prompt = venv_cfg.general_section().get("prompt")?.trim();
+    let prompt = prompt
+        .strip_prefix('(')
+        .and_then(|prompt| prompt.strip_suffix(')'))
+        .unwrap_or(prompt)
+        .trim();
+
+    if prompt.is_empty() {
+        None
+    } else {
+        Some(prompt.to_string())
+    }
 }
 
 #[cfg(test)]
@@ -134,20 +201,20 @@ mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
-    use std::fs::{create_dir_all, File};
+    use std::fs::{self, create_dir_all, File};
     use std::io;
     use std::io::Write;
 
     #[test]
-    fn test_format_python_version() {
+    fn test_parse_python_version() {
         let input = "Python 3.7.2";
-        assert_eq!(format_python_version(input), "v3.7.2");
+        assert_eq!(parse_python_version(input), "3.7.2");
     }
 
     #[test]
-    fn test_format_python_version_anaconda() {
+    fn test_parse_python_version_anaconda() {
         let input = "Python 3.6.10 :: Anaconda, Inc.";
-        assert_eq!(format_python_version(input), "v3.6.10");
+        assert_eq!(parse_python_version(input), "3.6.10");
     }
 
     #[test]
@@ -172,6 +239,66 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    fn folder_with_python_version_and_pinned_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".python-version"), "3.8.0\n3.7.9\n")?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .config(toml::toml! {
+                [python]
+                format = "via [$symbol$version( \\(installed $installed\\))]($style) "
+                python_binary = "python2"
+                use_pinned_version = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Yellow.bold().paint("🐍 v3.8.0 (installed v2.7.17)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_python_version_not_matching_interpreter() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".python-version"), "3.7\n")?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .config(toml::toml! {
+                [python]
+                format = "via [$symbol$version( \\(pinned $pinned_version\\))]($style) "
+                python_binary = "python3"
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("🐍 v3.8.0 (pinned v3.7)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_python_version_matching_interpreter() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".python-version"), "3.8\n")?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .config(toml::toml! {
+                [python]
+                python_binary = "python3"
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Yellow.bold().paint("🐍 v3.8.0")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
     #[test]
     fn folder_with_requirements_txt() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -349,6 +476,72 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    fn with_active_poetry_venv() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .env(
+                "VIRTUAL_ENV",
+                "/home/user/.cache/pypoetry/virtualenvs/my-app-Xn5b_t2Q-py3.8",
+            )
+            .config(toml::toml! {
+                [python]
+                format = "via [$symbol$version( \\($project_env\\))]($style) "
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Yellow.bold().paint("🐍 v3.8.0 (my-app)")
+        ));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn with_active_in_project_venv() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .env("VIRTUAL_ENV", "/home/user/src/my-app/.venv")
+            .config(toml::toml! {
+                [python]
+                format = "via [$symbol$version( \\($project_env\\))( $virtualenv)]($style) "
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Yellow.bold().paint("🐍 v3.8.0 (my-app) .venv")
+        ));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn with_active_venv_not_managed_by_a_project_tool() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .env("VIRTUAL_ENV", "/foo/bar/my_venv")
+            .config(toml::toml! {
+                [python]
+                format = "via [$symbol$version( \\($project_env\\))]($style) "
+            })
+            .collect();
+
+        let expected = Some(format!("via {} ", Color::Yellow.bold().paint("🐍 v3.8.0")));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
     #[test]
     fn with_active_venv_and_prompt() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -376,6 +569,52 @@ prompt = 'foo'
         dir.close()
     }
 
+    #[test]
+    fn with_active_venv_and_legacy_prompt() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        create_dir_all(dir.path().join("my_venv"))?;
+        fs::write(
+            dir.path().join("my_venv").join("pyvenv.cfg"),
+            "home = something\nprompt = (foo) \n",
+        )?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .env("VIRTUAL_ENV", dir.path().join("my_venv").to_str().unwrap())
+            .collect();
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Yellow.bold().paint("🐍 v3.8.0 (foo)")
+        ));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn with_active_venv_and_empty_prompt() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        create_dir_all(dir.path().join("my_venv"))?;
+        fs::write(
+            dir.path().join("my_venv").join("pyvenv.cfg"),
+            "home = something\nprompt =\n",
+        )?;
+
+        let actual = ModuleRenderer::new("python")
+            .path(dir.path())
+            .env("VIRTUAL_ENV", dir.path().join("my_venv").to_str().unwrap())
+            .collect();
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Yellow.bold().paint("🐍 v3.8.0 (my_venv)")
+        ));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
     fn check_python2_renders(dir: &tempfile::TempDir, starship_config: Option<toml::Value>) {
         let config = starship_config.unwrap_or(toml::toml! {
             [python]
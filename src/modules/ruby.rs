@@ -1,7 +1,11 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::utils::pinned_version::satisfies_pin;
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::ruby::RubyConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Ruby version
@@ -10,18 +14,31 @@ use crate::utils;
 ///     - Current directory contains a `.rb` file
 ///     - Current directory contains a `Gemfile` or `.ruby-version` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("ruby");
+    let config = RubyConfig::try_load(module.config);
+
     let is_rb_project = context
         .try_begin_scan()?
-        .set_files(&["Gemfile", ".ruby-version"])
-        .set_extensions(&["rb"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_rb_project {
         return None;
     }
 
-    let mut module = context.new_module("ruby");
-    let config = RubyConfig::try_load(module.config);
+    let ruby_version = Lazy::new(|| parse_ruby_version(&utils::exec_cmd("ruby", &["-v"])?.stdout));
+    let pinned_version = get_pinned_ruby_version(context);
+    let format_version = |version: &str| {
+        VersionFormatter::format_module_version(
+            context,
+            module.get_name(),
+            version,
+            config.version_format,
+        )
+    };
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -29,13 +46,19 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => match &pinned_version {
+                    Some(pinned_version)
+                        if !satisfies_pin(ruby_version.as_deref()?, pinned_version) =>
+                    {
+                        Some(Ok(config.not_capable_style))
+                    }
+                    _ => Some(Ok(config.style)),
+                },
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => {
-                    format_ruby_version(&utils::exec_cmd("ruby", &["-v"])?.stdout.as_str()).map(Ok)
-                }
+                "version" => format_version(ruby_version.as_deref()?).map(Ok),
+                "pinned_version" => format_version(pinned_version.as_deref()?).map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -52,7 +75,35 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn format_ruby_version(ruby_version: &str) -> Option<String> {
+/// The version pinned by `.ruby-version` or the `ruby` directive of the `Gemfile`
+fn get_pinned_ruby_version(context: &Context) -> Option<String> {
+    let ruby_version_file = utils::read_file(context.current_dir.join(".ruby-version"))
+        .ok()
+        .and_then(|contents| {
+            // rbenv and chruby allow a `ruby-` prefix, like `ruby-2.7.1`
+            let pin = contents.lines().next()?.trim();
+            let pin = pin.strip_prefix("ruby-").unwrap_or(pin);
+            if pin.starts_with(|c: char| c.is_ascii_digit()) {
+                Some(pin.to_string())
+            } else {
+                None
+            }
+        });
+
+    ruby_version_file.or_else(|| {
+        let gemfile = utils::read_file(context.current_dir.join("Gemfile")).ok()?;
+        parse_gemfile_ruby_version(&gemfile)
+    })
+}
+
+/// A `ruby "2.7.1"` directive in a `Gemfile`, ignoring requirements like `"~> 2.7"`
+fn parse_gemfile_ruby_version(gemfile: &str) -> Option<String> {
+    let re = Regex::new(r#"(?m)^\s*ruby\s*\(?\s*["'](?P<version>\d[\d.]*)["']"#).ok()?;
+    let captures = re.captures(gemfile)?;
+    Some(captures["version"].to_string())
+}
+
+fn parse_ruby_version(ruby_version: &str) -> Option<String> {
     let version = ruby_version
         // split into ["ruby", "2.6.0p0", "linux/amd64"]
         .split_whitespace()
@@ -63,10 +114,7 @@ fn format_ruby_version(ruby_version: &str) -> Option<String> {
         // return "2.6.0"
         .next()?;
 
-    let mut formatted_version = String::with_capacity(version.len() + 1);
-    formatted_version.push('v');
-    formatted_version.push_str(version);
-    Some(formatted_version)
+    Some(version.to_string())
 }
 
 #[cfg(test)]
@@ -74,7 +122,7 @@ mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io;
 
     #[test]
@@ -125,20 +173,64 @@ mod tests {
     }
 
     #[test]
-    fn test_format_ruby_version() -> io::Result<()> {
+    fn folder_with_matching_ruby_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".ruby-version"), "ruby-2.5.1\n")?;
+
+        let actual = ModuleRenderer::new("ruby")
+            .path(dir.path())
+            .config(toml::toml! {
+                [ruby]
+                format = "via [$symbol$version( \\(pinned $pinned_version\\))]($style) "
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("💎 v2.5.1 (pinned v2.5.1)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_mismatched_gemfile_ruby() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("Gemfile"),
+            "source \"https://rubygems.org\"\n\nruby '2.7.1'\n\ngem \"rails\"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("ruby").path(dir.path()).collect();
+
+        let expected = Some(format!("via {} ", Color::Yellow.bold().paint("💎 v2.5.1")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_parse_gemfile_ruby_version() {
+        assert_eq!(
+            parse_gemfile_ruby_version("ruby \"3.0.0\", engine: \"jruby\""),
+            Some("3.0.0".to_string())
+        );
+        assert_eq!(parse_gemfile_ruby_version("ruby \"~> 2.7\""), None);
+        assert_eq!(parse_gemfile_ruby_version("gem \"ruby-progressbar\""), None);
+    }
+
+    #[test]
+    fn test_parse_ruby_version() -> io::Result<()> {
         assert_eq!(
-            format_ruby_version("ruby 2.1.10p492 (2016-04-01 revision 54464) [x86_64-darwin19.0]"),
-            Some("v2.1.10".to_string())
+            parse_ruby_version("ruby 2.1.10p492 (2016-04-01 revision 54464) [x86_64-darwin19.0]"),
+            Some("2.1.10".to_string())
         );
         assert_eq!(
-            format_ruby_version("ruby 2.5.1p57 (2018-03-29 revision 63029) [x86_64-linux-gnu]"),
-            Some("v2.5.1".to_string())
+            parse_ruby_version("ruby 2.5.1p57 (2018-03-29 revision 63029) [x86_64-linux-gnu]"),
+            Some("2.5.1".to_string())
         );
         assert_eq!(
-            format_ruby_version(
-                "ruby 2.7.0p0 (2019-12-25 revision 647ee6f091) [x86_64-linux-musl]"
-            ),
-            Some("v2.7.0".to_string())
+            parse_ruby_version("ruby 2.7.0p0 (2019-12-25 revision 647ee6f091) [x86_64-linux-musl]"),
+            Some("2.7.0".to_string())
         );
 
         Ok(())
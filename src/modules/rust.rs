@@ -1,13 +1,15 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
 
+use once_cell::sync::Lazy;
 use serde::Deserialize;
 
+use super::utils::pinned_version::{is_older_than, satisfies_pin};
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::rust::RustConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 
 /// Creates a module with the current Rust version
 ///
@@ -15,18 +17,24 @@ use crate::formatter::StringFormatter;
 ///     - Current directory contains a file with a `.rs` extension
 ///     - Current directory contains a `Cargo.toml` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("rust");
+    let config = RustConfig::try_load(module.config);
+
     let is_rs_project = context
         .try_begin_scan()?
-        .set_files(&["Cargo.toml"])
-        .set_extensions(&["rs"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_rs_project {
         return None;
     }
 
-    let mut module = context.new_module("rust");
-    let config = RustConfig::try_load(module.config);
+    // Finding the version may run `rustc`, so it's only done if it's shown or styled
+    let module_version = Lazy::new(|| get_module_version(context, &config));
+    let msrv = get_msrv(context);
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -34,13 +42,31 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => {
+                    if let Some(msrv) = &msrv {
+                        if is_older_than(module_version.as_deref()?, msrv) {
+                            return Some(Ok(config.not_capable_style));
+                        }
+                    }
+                    let channel_style = if config.channel_styles.is_empty() {
+                        None
+                    } else {
+                        let channel = get_channel(module_version.as_deref()?);
+                        config.channel_styles.get(channel).copied()
+                    };
+                    Some(Ok(channel_style.unwrap_or(config.style)))
+                }
                 _ => None,
             })
             .map(|variable| match variable {
-                // This may result in multiple calls to `get_module_version` when a user have
-                // multiple `$version` variables defined in `format`.
-                "version" => get_module_version(context).map(Ok),
+                "version" => {
+                    format_module_version(context, &config, module_version.as_deref()?).map(Ok)
+                }
+                "channel" => module_version
+                    .as_deref()
+                    .map(|version| Ok(get_channel(version).to_owned())),
+                "installed" => get_installed_version(context, &config).map(Ok),
+                "msrv" => msrv.as_ref().map(|msrv| Ok(msrv.to_owned())),
                 _ => None,
             })
             .parse(None)
@@ -57,7 +83,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_module_version(context: &Context) -> Option<String> {
+fn get_module_version(context: &Context, config: &RustConfig) -> Option<String> {
     // `$CARGO_HOME/bin/rustc(.exe) --version` may attempt installing a rustup toolchain.
     // https://github.com/starship/starship/issues/417
     //
@@ -74,27 +100,165 @@ fn get_module_version(context: &Context) -> Option<String> {
     // - `rustup show`
     // - `rustup show active-toolchain`
     // - `rustup which`
+    if config.use_pinned_version {
+        // Skips `rustup override list` as well, since it is as slow as running `rustc`
+        if let Some(pinned_version) = get_pinned_version(context) {
+            return Some(pinned_version);
+        }
+    }
+
+    // Running `rustc` through the rustup proxy is slow, so first try to find the version of the
+    // toolchain from the files rustup keeps
+    if let Some(version) = get_toolchain_version_from_rustup_home(context) {
+        return Some(version);
+    }
+
     let module_version = if let Some(toolchain) = env_rustup_toolchain(context)
         .or_else(|| execute_rustup_override_list(&context.current_dir))
         .or_else(|| find_rust_toolchain_file(&context))
     {
         match execute_rustup_run_rustc_version(&toolchain) {
-            RustupRunRustcVersionOutcome::RustcVersion(stdout) => format_rustc_version(stdout),
+            RustupRunRustcVersionOutcome::RustcVersion(stdout) => parse_rustc_version(stdout),
             RustupRunRustcVersionOutcome::ToolchainName(toolchain) => toolchain,
             RustupRunRustcVersionOutcome::RustupNotWorking => {
                 // If `rustup` is not in `$PATH` or cannot be executed for other reasons, we can
                 // safely execute `rustc --version`.
-                format_rustc_version(execute_rustc_version()?)
+                parse_rustc_version(execute_rustc_version()?)
             }
             RustupRunRustcVersionOutcome::Err => return None,
         }
     } else {
-        format_rustc_version(execute_rustc_version()?)
+        parse_rustc_version(execute_rustc_version()?)
     };
 
     Some(module_version)
 }
 
+/// Toolchain names like `nightly-2021-02-17` are shown as they are, if the toolchain isn't
+/// installed
+fn format_module_version(context: &Context, config: &RustConfig, version: &str) -> Option<String> {
+    if version.starts_with(|c: char| c.is_ascii_digit()) {
+        VersionFormatter::format_module_version(context, "rust", version, config.version_format)
+    } else {
+        Some(version.to_owned())
+    }
+}
+
+/// The release channel of a version or toolchain name, e.g. `1.52.0-nightly` is on `nightly`
+fn get_channel(version: &str) -> &'static str {
+    if version.contains("nightly") {
+        "nightly"
+    } else if version.contains("beta") {
+        "beta"
+    } else {
+        "stable"
+    }
+}
+
+/// The minimum supported Rust version, from the `rust-version` of the package in `Cargo.toml`
+fn get_msrv(context: &Context) -> Option<String> {
+    let cargo_toml = fs::read_to_string(context.current_dir.join("Cargo.toml")).ok()?;
+    let cargo_toml: toml::Value = toml::from_str(&cargo_toml).ok()?;
+    let msrv = cargo_toml.get("package")?.get("rust-version")?.as_str()?;
+    Some(msrv.to_owned())
+}
+
+/// The version of `rustc` on the `PATH`, if it doesn' match the pinned toolchain
+fn get_installed_version(context: &Context, config: &RustConfig) -> Option<String> {
+    if !config.use_pinned_version {
+        return None;
+    }
+    let pinned_version = get_pinned_version(context)?;
+    let installed_version = parse_rustc_version(execute_rustc_version()?);
+    if satisfies_pin(&installed_version, &pinned_version) {
+        return None;
+    }
+
+    VersionFormatter::format_module_version(
+        context,
+        "rust",
+        &installed_version,
+        config.version_format,
+    )
+}
+
+/// A toolchain pinned to a release, like `1.50.0` or `1.50-x86_64-unknown-linux-gnu`
+fn get_pinned_version(context: &Context) -> Option<String> {
+    let toolchain = env_rustup_toolchain(context).or_else(|| find_rust_toolchain_file(context))?;
+    let version = toolchain.split('-').next()?;
+
+    if version.starts_with(|c: char| c.is_ascii_digit()) {
+        Some(version.to_string())
+    } else {
+        None
+    }
+}
+
+fn get_rustup_home(context: &Context) -> Option<PathBuf> {
+    context
+        .get_env("RUSTUP_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs_next::home_dir().map(|home| home.join(".rustup")))
+}
+
+/// Find the version of the active toolchain from rustup's settings and the manifest of the
+/// installed toolchain, using the same override precedence as rustup
+fn get_toolchain_version_from_rustup_home(context: &Context) -> Option<String> {
+    let rustup_home = get_rustup_home(context)?;
+    let settings = fs::read_to_string(rustup_home.join("settings.toml"))
+        .ok()
+        .and_then(|settings| toml::from_str::<toml::Value>(&settings).ok())?;
+
+    let toolchain = env_rustup_toolchain(context)
+        .or_else(|| find_override_in_settings(&settings, &context.current_dir))
+        .or_else(|| find_rust_toolchain_file(context))
+        .or_else(|| Some(settings.get("default_toolchain")?.as_str()?.to_owned()))?;
+
+    // Toolchains are installed with the host triple, e.g. `stable-x86_64-unknown-linux-gnu`
+    let toolchains_dir = rustup_home.join("toolchains");
+    let toolchain_dir = Some(toolchains_dir.join(&toolchain))
+        .filter(|dir| dir.is_dir())
+        .or_else(|| {
+            let host_triple = settings.get("default_host_triple")?.as_str()?;
+            Some(toolchains_dir.join(format!("{}-{}", toolchain, host_triple)))
+        })?;
+
+    let manifest = fs::read_to_string(
+        toolchain_dir
+            .join("lib")
+            .join("rustlib")
+            .join("multirust-channel-manifest.toml"),
+    )
+    .ok()?;
+    parse_rustc_version_from_manifest(&manifest)
+}
+
+/// rustup keeps the overrides set with `rustup override set` in its settings
+fn find_override_in_settings(settings: &toml::Value, cwd: &Path) -> Option<String> {
+    settings
+        .get("overrides")?
+        .as_table()?
+        .iter()
+        .filter(|(dir, _)| cwd.starts_with(dir))
+        .max_by_key(|(dir, _)| dir.len())
+        .and_then(|(_, toolchain)| toolchain.as_str())
+        .map(ToOwned::to_owned)
+}
+
+/// Read the version of `rustc` from a channel manifest, without parsing the whole manifest
+fn parse_rustc_version_from_manifest(manifest: &str) -> Option<String> {
+    let version = manifest
+        .lines()
+        .skip_while(|line| line.trim() != "[pkg.rustc]")
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .find_map(|line| line.trim().strip_prefix("version = "))?;
+
+    // e.g. `"1.50.0 (cb75ad5db 2021-02-10)"`
+    let version = version.trim_matches('"').split_whitespace().next()?;
+    Some(version.to_owned())
+}
+
 fn env_rustup_toolchain(context: &Context) -> Option<String> {
     let val = context.get_env("RUSTUP_TOOLCHAIN")?;
     Some(val.trim().to_owned())
@@ -141,10 +305,11 @@ fn find_rust_toolchain_file(context: &Context) -> Option<String> {
 
     fn read_channel(path: &Path) -> Option<String> {
         let contents = fs::read_to_string(path).ok()?;
+        let is_toml = path.extension() == Some("toml".as_ref());
 
         match contents.lines().count() {
             0 => None,
-            1 => Some(contents),
+            1 if !is_toml => Some(contents),
             _ => {
                 toml::from_str::<OverrideFile>(&contents)
                     .ok()?
@@ -156,18 +321,22 @@ fn find_rust_toolchain_file(context: &Context) -> Option<String> {
         .map(|c| c.trim().to_owned())
     }
 
-    if let Ok(true) = context
-        .dir_contents()
-        .map(|dir| dir.has_file("rust-toolchain"))
-    {
-        if let Some(toolchain) = read_channel(Path::new("rust-toolchain")) {
-            return Some(toolchain);
+    const TOOLCHAIN_FILES: [&str; 2] = ["rust-toolchain", "rust-toolchain.toml"];
+
+    if let Ok(dir) = context.dir_contents() {
+        for file in TOOLCHAIN_FILES.iter().filter(|file| dir.has_file(file)) {
+            if let Some(toolchain) = read_channel(Path::new(file)) {
+                return Some(toolchain);
+            }
         }
     }
 
     let mut dir = &*context.current_dir;
     loop {
-        if let Some(toolchain) = read_channel(&dir.join("rust-toolchain")) {
+        if let Some(toolchain) = TOOLCHAIN_FILES
+            .iter()
+            .find_map(|file| read_channel(&dir.join(file)))
+        {
             return Some(toolchain);
         }
         dir = dir.parent()?;
@@ -205,11 +374,11 @@ fn execute_rustc_version() -> Option<String> {
     }
 }
 
-fn format_rustc_version(mut rustc_stdout: String) -> String {
+fn parse_rustc_version(mut rustc_stdout: String) -> String {
     let offset = &rustc_stdout.find('(').unwrap_or_else(|| rustc_stdout.len());
     let formatted_version: String = rustc_stdout.drain(..offset).collect();
 
-    format!("v{}", formatted_version.replace("rustc", "").trim())
+    formatted_version.replace("rustc", "").trim().to_string()
 }
 
 #[derive(Debug, PartialEq)]
@@ -227,6 +396,8 @@ mod tests {
     use std::process::{ExitStatus, Output};
 
     use super::*;
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
 
     #[test]
     fn test_extract_toolchain_from_rustup_override_list() {
@@ -320,18 +491,18 @@ mod tests {
     }
 
     #[test]
-    fn test_format_rustc_version() {
+    fn test_parse_rustc_version() {
         let nightly_input = String::from("rustc 1.34.0-nightly (b139669f3 2019-04-10)");
-        assert_eq!(format_rustc_version(nightly_input), "v1.34.0-nightly");
+        assert_eq!(parse_rustc_version(nightly_input), "1.34.0-nightly");
 
         let beta_input = String::from("rustc 1.34.0-beta.1 (2bc1d406d 2019-04-10)");
-        assert_eq!(format_rustc_version(beta_input), "v1.34.0-beta.1");
+        assert_eq!(parse_rustc_version(beta_input), "1.34.0-beta.1");
 
         let stable_input = String::from("rustc 1.34.0 (91856ed52 2019-04-10)");
-        assert_eq!(format_rustc_version(stable_input), "v1.34.0");
+        assert_eq!(parse_rustc_version(stable_input), "1.34.0");
 
         let version_without_hash = String::from("rustc 1.34.0");
-        assert_eq!(format_rustc_version(version_without_hash), "v1.34.0");
+        assert_eq!(parse_rustc_version(version_without_hash), "1.34.0");
     }
 
     #[test]
@@ -373,6 +544,200 @@ mod tests {
             find_rust_toolchain_file(&context),
             Some("1.34.0".to_owned())
         );
+        dir.close()?;
+
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("rust-toolchain.toml"),
+            "[toolchain]\nchannel = \"1.34.0\"",
+        )?;
+
+        let context = Context::new_with_dir(Default::default(), dir.path());
+
+        assert_eq!(
+            find_rust_toolchain_file(&context),
+            Some("1.34.0".to_owned())
+        );
+        dir.close()
+    }
+
+    fn install_toolchain(
+        rustup_home: &Path,
+        toolchain: &str,
+        rustc_version: &str,
+    ) -> io::Result<()> {
+        let rustlib_dir = rustup_home
+            .join("toolchains")
+            .join(toolchain)
+            .join("lib")
+            .join("rustlib");
+        fs::create_dir_all(&rustlib_dir)?;
+        fs::write(
+            rustlib_dir.join("multirust-channel-manifest.toml"),
+            format!(
+                "date = \"2021-02-11\"\nmanifest-version = \"2\"\n\n[pkg.cargo]\nversion = \"0.51.0 (43b129a20 2021-01-27)\"\n\n[pkg.rustc]\nversion = \"{}\"\n",
+                rustc_version
+            ),
+        )
+    }
+
+    #[test]
+    fn test_toolchain_version_from_rustup_home() -> io::Result<()> {
+        let rustup_home = tempfile::tempdir()?;
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("Cargo.toml"), "")?;
+        let overridden_dir = dir.path().join("overridden");
+        fs::create_dir(&overridden_dir)?;
+        fs::write(
+            rustup_home.path().join("settings.toml"),
+            format!(
+                "default_host_triple = \"x86_64-unknown-linux-gnu\"\ndefault_toolchain = \"stable-x86_64-unknown-linux-gnu\"\nprofile = \"default\"\nversion = \"12\"\n\n[overrides]\n{:?} = \"nightly-x86_64-unknown-linux-gnu\"\n",
+                overridden_dir.to_string_lossy()
+            ),
+        )?;
+        install_toolchain(
+            rustup_home.path(),
+            "stable-x86_64-unknown-linux-gnu",
+            "1.50.0 (cb75ad5db 2021-02-10)",
+        )?;
+        install_toolchain(
+            rustup_home.path(),
+            "nightly-x86_64-unknown-linux-gnu",
+            "1.52.0-nightly (152f66092 2021-02-17)",
+        )?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_HOME", rustup_home.path().to_string_lossy())
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("🦀 v1.50.0")));
+        assert_eq!(expected, actual);
+
+        fs::write(overridden_dir.join("main.rs"), "")?;
+        let actual = ModuleRenderer::new("rust")
+            .path(&overridden_dir)
+            .env("RUSTUP_HOME", rustup_home.path().to_string_lossy())
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("🦀 v1.52.0-nightly")
+        ));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_HOME", rustup_home.path().to_string_lossy())
+            .env("RUSTUP_TOOLCHAIN", "nightly")
+            .collect();
+        assert_eq!(expected, actual);
+
+        rustup_home.close()?;
+        dir.close()
+    }
+
+    #[test]
+    fn test_channel() -> io::Result<()> {
+        let rustup_home = tempfile::tempdir()?;
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("Cargo.toml"), "")?;
+        fs::write(
+            rustup_home.path().join("settings.toml"),
+            "default_toolchain = \"nightly-x86_64-unknown-linux-gnu\"\n",
+        )?;
+        install_toolchain(
+            rustup_home.path(),
+            "nightly-x86_64-unknown-linux-gnu",
+            "1.52.0-nightly (152f66092 2021-02-17)",
+        )?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_HOME", rustup_home.path().to_string_lossy())
+            .config(toml::toml! {
+                [rust]
+                format = "[$channel]($style)"
+                [rust.channel_styles]
+                nightly = "bold purple"
+                beta = "bold yellow"
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Purple.bold().paint("nightly")));
+        assert_eq!(expected, actual);
+
+        rustup_home.close()?;
+        dir.close()
+    }
+
+    #[test]
+    fn test_msrv() -> io::Result<()> {
+        let rustup_home = tempfile::tempdir()?;
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"starship\"\nrust-version = \"1.56\"\n",
+        )?;
+        fs::write(
+            rustup_home.path().join("settings.toml"),
+            "default_toolchain = \"stable-x86_64-unknown-linux-gnu\"\n",
+        )?;
+        install_toolchain(
+            rustup_home.path(),
+            "stable-x86_64-unknown-linux-gnu",
+            "1.50.0 (cb75ad5db 2021-02-10)",
+        )?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .env("RUSTUP_HOME", rustup_home.path().to_string_lossy())
+            .config(toml::toml! {
+                [rust]
+                format = "via [$symbol$version( \\(msrv $msrv\\))]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Yellow.bold().paint("🦀 v1.50.0 (msrv 1.56)")
+        ));
+        assert_eq!(expected, actual);
+
+        rustup_home.close()?;
+        dir.close()
+    }
+
+    #[test]
+    fn test_get_channel() {
+        assert_eq!(get_channel("1.52.0-nightly"), "nightly");
+        assert_eq!(get_channel("beta-x86_64-unknown-linux-gnu"), "beta");
+        assert_eq!(get_channel("1.50.0"), "stable");
+    }
+
+    #[test]
+    fn test_pinned_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("Cargo.toml"), "")?;
+        fs::write(
+            dir.path().join("rust-toolchain.toml"),
+            "[toolchain]\nchannel = \"1.50.0\"",
+        )?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .config(toml::toml! {
+                [rust]
+                use_pinned_version = true
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("🦀 v1.50.0")));
+        assert_eq!(expected, actual);
+        dir.close()?;
+
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("Cargo.toml"), "")?;
+        fs::write(dir.path().join("rust-toolchain"), "nightly")?;
+
+        let context = Context::new_with_dir(Default::default(), dir.path());
+
+        assert_eq!(get_pinned_version(&context), None);
         dir.close()
     }
 }
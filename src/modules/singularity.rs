@@ -3,11 +3,13 @@ use super::{Context, Module, RootModuleConfig};
 use crate::configs::singularity::SingularityConfig;
 use crate::formatter::StringFormatter;
 
-/// Creates a module with the current Singularity image
+/// Creates a module with the current Apptainer/Singularity image
 ///
-/// Will display the Singularity image if `$SINGULARITY_NAME` is set.
+/// Will display the image if `$APPTAINER_NAME` or `$SINGULARITY_NAME` is set.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let singularity_env = context.get_env("SINGULARITY_NAME");
+    let singularity_env = context
+        .get_env("APPTAINER_NAME")
+        .or_else(|| context.get_env("SINGULARITY_NAME"));
     singularity_env.as_ref()?;
 
     let mut module = context.new_module("singularity");
@@ -69,4 +71,19 @@ mod tests {
         assert_eq!(expected, actual);
         Ok(())
     }
+
+    #[test]
+    fn apptainer_env_set() -> io::Result<()> {
+        let actual = ModuleRenderer::new("singularity")
+            .env("APPTAINER_NAME", "rocky.sif")
+            .collect();
+
+        let expected = Some(format!(
+            "{} ",
+            Color::Blue.bold().dimmed().paint("[rocky.sif]")
+        ));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
 }
@@ -1,96 +1,202 @@
 use super::{Context, Module, RootModuleConfig};
 
-use crate::configs::status::StatusConfig;
-use crate::formatter::StringFormatter;
+use crate::configs::status::{StatusClassConfig, StatusConfig};
+use crate::formatter::{StringFormatter, StringFormatterError};
+use crate::segment::Segment;
+use indexmap::IndexMap;
 
 type ExitCode = i64;
 type SignalNumber = u32;
 
 /// Creates a module with the status of the last command
 ///
-/// Will display the status only if it is not 0
+/// Will display the status only if it is not 0, if any command of the last
+/// pipeline failed when `pipestatus` is enabled, or if a class of
+/// `status.classes` contains 0
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let exit_code = context
         .properties
         .get("status_code")
         .map_or("0", String::as_str);
 
-    if exit_code == "0" {
-        None
-    } else {
-        let mut module = context.new_module("status");
-        let config = StatusConfig::try_load(module.config);
+    let mut module = context.new_module("status");
+    let config = StatusConfig::try_load(module.config);
 
-        // As we default to disabled=true, we have to check here after loading our config module,
-        // before it was only checking against whatever is in the config starship.toml
-        if config.disabled {
-            return None;
-        };
-
-        let exit_code_int: ExitCode = match exit_code.parse() {
-            Ok(i) => i,
-            Err(_) => return None,
-        };
-
-        let common_meaning = status_common_meaning(exit_code_int);
-
-        let raw_signal_number = match config.recognize_signal_code {
-            true => status_to_signal(exit_code_int),
-            false => None,
-        };
-        let signal_number = raw_signal_number.map(|sn| sn.to_string());
-        let signal_name = raw_signal_number
-            .and_then(|sn| status_signal_name(sn).or_else(|| signal_number.as_deref()));
-
-        // If not a signal and not a common meaning, it should at least print the raw exit code number
-        let maybe_exit_code_number = match common_meaning.is_none() && signal_name.is_none() {
-            true => Some(exit_code),
-            false => None,
-        };
-
-        let parsed = StringFormatter::new(config.format).and_then(|formatter| {
-            formatter
-                .map_meta(|var, _| match var {
-                    "symbol" => match exit_code_int {
-                        126 if config.map_symbol => Some(config.not_executable_symbol),
-                        127 if config.map_symbol => Some(config.not_found_symbol),
-                        130 if config.recognize_signal_code && config.map_symbol => {
-                            Some(config.sigint_symbol)
-                        }
-                        x if (129..256).contains(&x)
-                            && config.recognize_signal_code
-                            && config.map_symbol =>
-                        {
-                            Some(config.signal_symbol)
-                        }
-                        _ => Some(config.symbol),
-                    },
-                    _ => None,
-                })
-                .map_style(|variable| match variable {
-                    "style" => Some(Ok(config.style)),
-                    _ => None,
-                })
-                .map(|variable| match variable {
-                    "status" => Some(Ok(exit_code)),
-                    "int" => Some(Ok(exit_code)),
-                    "maybe_int" => Ok(maybe_exit_code_number.as_deref()).transpose(),
-                    "common_meaning" => Ok(common_meaning.as_deref()).transpose(),
-                    "signal_number" => Ok(signal_number.as_deref()).transpose(),
-                    "signal_name" => Ok(signal_name.as_deref()).transpose(),
-                    _ => None,
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    };
+
+    // Only pipelines with more than one command are displayed as a pipestatus
+    let pipestatus = match &context.pipestatus {
+        Some(pipestatus) if config.pipestatus && pipestatus.len() > 1 => Some(pipestatus),
+        _ => None,
+    };
+
+    let pipeline_succeeded = match pipestatus {
+        Some(pipestatus) => pipestatus.iter().all(|exit_code| exit_code == "0"),
+        None => true,
+    };
+    if exit_code == "0" && pipeline_succeeded && status_class(0, &config.classes).is_none() {
+        return None;
+    }
+
+    let parsed = match pipestatus {
+        Some(pipestatus) => {
+            let segments = pipestatus
+                .iter()
+                .enumerate()
+                .map(|(i, exit_code)| {
+                    let mut segments = format_exit_code(
+                        exit_code,
+                        config.pipestatus_segment_format,
+                        None,
+                        &config,
+                    )?;
+                    if i + 1 < pipestatus.len() {
+                        segments.push(Segment::new(None, config.pipestatus_separator));
+                    }
+                    Ok(segments)
                 })
-                .parse(None)
-        });
-
-        module.set_segments(match parsed {
-            Ok(segments) => segments,
-            Err(_error) => {
-                log::warn!("Error parsing format string in `status.format`");
-                return None;
+                .collect::<Result<Vec<Vec<Segment>>, StringFormatterError>>()
+                .map(|segments| segments.into_iter().flatten().collect::<Vec<Segment>>());
+
+            segments.and_then(|segments| {
+                format_exit_code(exit_code, config.pipestatus_format, Some(segments), &config)
+            })
+        }
+        None => format_exit_code(exit_code, config.format, None, &config),
+    };
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `status`:\n{}", error);
+            return None;
+        }
+    });
+    Some(module)
+}
+
+fn format_exit_code<'a>(
+    exit_code: &'a str,
+    format: &'a str,
+    pipestatus: Option<Vec<Segment>>,
+    config: &'a StatusConfig,
+) -> Result<Vec<Segment>, StringFormatterError> {
+    let exit_code_int: ExitCode = match exit_code.parse() {
+        Ok(i) => i,
+        Err(_) => {
+            return Err(StringFormatterError::Custom(format!(
+                "Invalid exit code `{}`",
+                exit_code
+            )))
+        }
+    };
+
+    let raw_signal_number = match config.recognize_signal_code {
+        true => status_to_signal(exit_code_int),
+        false => None,
+    };
+    let signal_number = raw_signal_number.map(|sn| sn.to_string());
+    let signal_name = raw_signal_number
+        .and_then(|sn| status_signal_name(sn).or_else(|| signal_number.as_deref()));
+    let common_meaning = match raw_signal_number {
+        Some(sn) => status_signal_meaning(sn),
+        None => status_common_meaning(exit_code_int),
+    };
+
+    // Show the full name of the signal (e.g. `SIGSEGV`) in place of the raw exit code
+    let status = match raw_signal_number.and_then(status_signal_name) {
+        Some(name) if config.show_signal_name => format!("SIG{}", name),
+        _ => exit_code.to_string(),
+    };
+    let map_signal_symbol =
+        config.recognize_signal_code && (config.map_symbol || config.show_signal_name);
+
+    // If not a signal and not a common meaning, it should at least print the raw exit code number
+    let maybe_exit_code_number = match common_meaning.is_none() && signal_name.is_none() {
+        true => Some(exit_code),
+        false => None,
+    };
+
+    let class = status_class(exit_code_int, &config.classes);
+    let class_symbol = class.and_then(|class| class.symbol);
+    let style = class.and_then(|class| class.style).unwrap_or(config.style);
+
+    StringFormatter::new(format).and_then(|formatter| {
+        formatter
+            .map_meta(|var, _| match var {
+                "symbol" => match exit_code_int {
+                    _ if class_symbol.is_some() => class_symbol,
+                    126 if config.map_symbol => Some(config.not_executable_symbol),
+                    127 if config.map_symbol => Some(config.not_found_symbol),
+                    130 if map_signal_symbol => Some(config.sigint_symbol),
+                    x if (129..256).contains(&x) && map_signal_symbol => Some(config.signal_symbol),
+                    _ => Some(config.symbol),
+                },
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "status" => Some(Ok(status.as_str())),
+                "int" => Some(Ok(exit_code)),
+                "maybe_int" => Ok(maybe_exit_code_number.as_deref()).transpose(),
+                "common_meaning" => Ok(common_meaning.as_deref()).transpose(),
+                "signal_number" => Ok(signal_number.as_deref()).transpose(),
+                "signal_name" => Ok(signal_name.as_deref()).transpose(),
+                _ => None,
+            })
+            .map_variables_to_segments(|variable| match variable {
+                "pipestatus" => pipestatus.clone().map(Ok),
+                _ => None,
+            })
+            .parse(None)
+    })
+}
+
+/// Find the first class in `classes` that contains the exit code
+///
+/// Classes are keyed either by name (`success`, `misuse`, `not_executable`,
+/// `not_found` or `signal`), by a single exit code (`1`) or by an inclusive
+/// range of exit codes (`64-78`).
+fn status_class<'a, 'b>(
+    exit_code: ExitCode,
+    classes: &'b IndexMap<String, StatusClassConfig<'a>>,
+) -> Option<&'b StatusClassConfig<'a>> {
+    classes
+        .iter()
+        .find(|(key, _)| match parse_status_class(key) {
+            Some((start, end)) => (start..=end).contains(&exit_code),
+            None => {
+                log::warn!("Invalid exit code class `{}` in `status.classes`", key);
+                false
+            }
+        })
+        .map(|(_, class)| class)
+}
+
+fn parse_status_class(key: &str) -> Option<(ExitCode, ExitCode)> {
+    match key {
+        "success" => Some((0, 0)),
+        "misuse" => Some((2, 2)),
+        "not_executable" => Some((126, 126)),
+        "not_found" => Some((127, 127)),
+        "signal" => Some((129, 255)),
+        _ => {
+            if let Ok(exit_code) = key.parse() {
+                return Some((exit_code, exit_code));
             }
-        });
-        Some(module)
+            // Skip the first character, so that the start of the range can be negative
+            let separator = key.get(1..)?.find('-')? + 1;
+            let start = key[..separator].trim().parse().ok()?;
+            let end = key[separator + 1..].trim().parse().ok()?;
+            Some((start, end))
+        }
     }
 }
 
@@ -140,6 +246,45 @@ fn status_signal_name(signal: SignalNumber) -> Option<&'static str> {
         20 => Some("TSTP"),   // 128 + 20
         21 => Some("TTIN"),   // 128 + 21
         22 => Some("TTOU"),   // 128 + 22
+        23 => Some("URG"),    // 128 + 23
+        24 => Some("XCPU"),   // 128 + 24
+        25 => Some("XFSZ"),   // 128 + 25
+        26 => Some("VTALRM"), // 128 + 26
+        27 => Some("PROF"),   // 128 + 27
+        28 => Some("WINCH"),  // 128 + 28
+        29 => Some("IO"),     // 128 + 29
+        30 => Some("PWR"),    // 128 + 30
+        31 => Some("SYS"),    // 128 + 31
+        _ => None,
+    }
+}
+
+/// The meaning of a signal that ended the command, for `$common_meaning`
+fn status_signal_meaning(signal: SignalNumber) -> Option<&'static str> {
+    match signal {
+        1 => Some("HANGUP"),
+        2 => Some("INTERRUPTED"),
+        3 => Some("QUIT"),
+        4 => Some("ILLEGAL INSTRUCTION"),
+        5 => Some("TRACE TRAP"),
+        6 => Some("ABORTED"),
+        7 => Some("BUS ERROR"),
+        8 => Some("FLOATING POINT EXCEPTION"),
+        9 => Some("KILLED"),
+        10 => Some("USER DEFINED SIGNAL 1"),
+        11 => Some("SEGMENTATION FAULT"),
+        12 => Some("USER DEFINED SIGNAL 2"),
+        13 => Some("BROKEN PIPE"),
+        14 => Some("ALARM CLOCK"),
+        15 => Some("TERMINATED"),
+        16 => Some("STACK FAULT"),
+        19..=22 => Some("STOPPED"),
+        24 => Some("CPU TIME LIMIT EXCEEDED"),
+        25 => Some("FILE SIZE LIMIT EXCEEDED"),
+        26 => Some("VIRTUAL TIMER EXPIRED"),
+        27 => Some("PROFILING TIMER EXPIRED"),
+        30 => Some("POWER FAILURE"),
+        31 => Some("BAD SYSTEM CALL"),
         _ => None,
     }
 }
@@ -149,6 +294,7 @@ mod tests {
     use ansi_term::Color;
     use std::io;
 
+    use super::parse_status_class;
     use crate::test::ModuleRenderer;
 
     #[test]
@@ -217,7 +363,7 @@ mod tests {
             Some("USAGE"),
             Some("NOPERM"),
             Some("NOTFOUND"),
-            Some("INT"),
+            Some("INTERRUPTEDINT"),
             None,
         ];
 
@@ -352,4 +498,261 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn pipestatus_disabled() -> io::Result<()> {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                format = "$status"
+                disabled = false
+            })
+            .status(0)
+            .pipestatus(&[1, 0])
+            .collect();
+        assert_eq!(None, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn pipestatus_failure_in_pipeline() -> io::Result<()> {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                pipestatus = true
+                pipestatus_format = "$pipestatus => $status"
+                pipestatus_segment_format = "$status"
+                disabled = false
+            })
+            .status(0)
+            .pipestatus(&[1, 0, 127])
+            .collect();
+        let expected = Some(String::from("1|0|127 => 0"));
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn pipestatus_successful_pipeline() -> io::Result<()> {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                pipestatus = true
+                disabled = false
+            })
+            .status(0)
+            .pipestatus(&[0, 0])
+            .collect();
+        assert_eq!(None, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn pipestatus_single_command() -> io::Result<()> {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                format = "$status"
+                pipestatus = true
+                disabled = false
+            })
+            .status(1)
+            .pipestatus(&[1])
+            .collect();
+        let expected = Some(String::from("1"));
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn pipestatus_default_format() -> io::Result<()> {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                pipestatus = true
+                pipestatus_separator = " "
+                disabled = false
+            })
+            .status(2)
+            .pipestatus(&[0, 2])
+            .collect();
+        let style = Color::Red.bold();
+        let expected = Some(format!(
+            "[{} {}] => {} ",
+            style.paint("0"),
+            style.paint("2"),
+            style.paint("✖2")
+        ));
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn show_signal_name() -> io::Result<()> {
+        let exit_values = [1, 130, 137, 139, 159, 160];
+        let exit_values_name = [
+            "✖1",
+            "🧱SIGINT",
+            "⚡SIGKILL",
+            "⚡SIGSEGV",
+            "⚡SIGSYS",
+            "⚡160",
+        ];
+
+        for (status, name) in exit_values.iter().zip(exit_values_name.iter()) {
+            let expected = Some(name.to_string());
+            let actual = ModuleRenderer::new("status")
+                .config(toml::toml! {
+                    [status]
+                    format = "$symbol$status"
+                    show_signal_name = true
+                    disabled = false
+                })
+                .status(*status)
+                .collect();
+            assert_eq!(expected, actual);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn show_signal_name_without_signal_recognition() -> io::Result<()> {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                format = "$symbol$status"
+                show_signal_name = true
+                recognize_signal_code = false
+                disabled = false
+            })
+            .status(139)
+            .collect();
+        let expected = Some(String::from("✖139"));
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn exit_code_classes() -> io::Result<()> {
+        let exit_values = [1, 2, 64, 78, 79, 127, 139];
+        let exit_values_output = [
+            Color::Red.bold().paint("✖1"),
+            Color::Yellow.paint("❓2"),
+            Color::Purple.paint("⚠64"),
+            Color::Purple.paint("⚠78"),
+            Color::Red.bold().paint("✖79"),
+            Color::Blue.paint("🔍127"),
+            Color::Red.bold().paint("⚡139"),
+        ];
+
+        for (status, output) in exit_values.iter().zip(exit_values_output.iter()) {
+            let expected = Some(output.to_string());
+            let actual = ModuleRenderer::new("status")
+                .config(toml::toml! {
+                    [status]
+                    format = "[$symbol$status]($style)"
+                    disabled = false
+                    [status.classes]
+                    misuse = { symbol = "❓", style = "yellow" }
+                    "64-78" = { symbol = "⚠", style = "purple" }
+                    not_found = { symbol = "🔍", style = "blue" }
+                    signal = { symbol = "⚡" }
+                })
+                .status(*status)
+                .collect();
+            assert_eq!(expected, actual);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn success_class() -> io::Result<()> {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                disabled = false
+                [status.classes]
+                success = { symbol = "✔", style = "bold green" }
+            })
+            .status(0)
+            .collect();
+        let expected = Some(format!("{} ", Color::Green.bold().paint("✔0")));
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn success_class_in_pipeline() -> io::Result<()> {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                pipestatus = true
+                disabled = false
+                [status.classes]
+                success = { style = "green" }
+            })
+            .status(0)
+            .pipestatus(&[1, 0])
+            .collect();
+        let expected = Some(format!(
+            "[{}|{}] => {} ",
+            Color::Red.bold().paint("1"),
+            Color::Green.paint("0"),
+            Color::Green.paint("✖0")
+        ));
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_exit_code_classes() {
+        assert_eq!(parse_status_class("success"), Some((0, 0)));
+        assert_eq!(parse_status_class("signal"), Some((129, 255)));
+        assert_eq!(parse_status_class("3"), Some((3, 3)));
+        assert_eq!(parse_status_class("-3"), Some((-3, -3)));
+        assert_eq!(parse_status_class("64-78"), Some((64, 78)));
+        assert_eq!(parse_status_class("-5--1"), Some((-5, -1)));
+        assert_eq!(parse_status_class("everything"), None);
+    }
+
+    #[test]
+    fn common_meaning() -> io::Result<()> {
+        let exit_values = [1, 2, 126, 127, 128, 130, 137, 139, 101];
+        let exit_values_meaning = [
+            Some("ERROR"),
+            Some("USAGE"),
+            Some("NOPERM"),
+            Some("NOTFOUND"),
+            None,
+            Some("INTERRUPTED"),
+            Some("KILLED"),
+            Some("SEGMENTATION FAULT"),
+            None,
+        ];
+
+        for (status, meaning) in exit_values.iter().zip(exit_values_meaning.iter()) {
+            let expected = meaning.map(|m| m.to_string());
+            let actual = ModuleRenderer::new("status")
+                .config(toml::toml! {
+                    [status]
+                    format = "$common_meaning"
+                    disabled = false
+                })
+                .status(*status)
+                .collect();
+            assert_eq!(expected, actual);
+        }
+
+        Ok(())
+    }
 }
@@ -1,7 +1,8 @@
+use super::utils::pinned_version::is_older_than;
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::swift::SwiftConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
 /// Creates a module with the current Swift version
@@ -10,19 +11,32 @@ use crate::utils;
 ///     - The current directory contains a `Package.swift` file
 ///     - The current directory contains a file with extension `.swift`
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("swift");
+    let config: SwiftConfig = SwiftConfig::try_load(module.config);
+
     let is_swift_project = context
         .try_begin_scan()?
-        .set_extensions(&["swift"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_swift_project {
         return None;
     }
 
-    let swift_version = utils::exec_cmd("swift", &["--version"])?.stdout;
-
-    let mut module = context.new_module("swift");
-    let config: SwiftConfig = SwiftConfig::try_load(module.config);
+    let swift_version = parse_swift_version(&utils::exec_cmd("swift", &["--version"])?.stdout);
+    let expected = get_swift_tools_version(context).filter(|tools_version| {
+        matches!(&swift_version, Some(swift_version) if is_older_than(swift_version, tools_version))
+    });
+    let format_version = |version: &str| {
+        VersionFormatter::format_module_version(
+            context,
+            module.get_name(),
+            version,
+            config.version_format,
+        )
+    };
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -31,11 +45,18 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => {
+                    if expected.is_none() {
+                        Some(Ok(config.style))
+                    } else {
+                        Some(Ok(config.not_capable_style))
+                    }
+                }
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => parse_swift_version(&swift_version).map(Ok),
+                "version" => format_version(swift_version.as_deref()?).map(Ok),
+                "expected" => format_version(expected.as_deref()?).map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -52,6 +73,27 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// The tools version that `Package.swift` requires, from its first line, which looks like
+/// `// swift-tools-version:5.3`
+fn get_swift_tools_version(context: &Context) -> Option<String> {
+    let manifest = utils::read_file(context.current_dir.join("Package.swift")).ok()?;
+    parse_swift_tools_version(manifest.lines().next()?)
+}
+
+fn parse_swift_tools_version(line: &str) -> Option<String> {
+    let comment = line.trim().strip_prefix("//")?.trim_start();
+    let version = comment
+        .strip_prefix("swift-tools-version")?
+        .trim_start()
+        .strip_prefix(':')?
+        .trim_start();
+    // Settings may follow the version, like `5.3;(experimental)`
+    let version = version
+        .split(|c: char| c == ';' || c.is_whitespace())
+        .next()?;
+    Some(version.to_string())
+}
+
 fn parse_swift_version(swift_version: &str) -> Option<String> {
     // split into ["Apple", "Swift", "version", "5.2.2", ...] or
     //            ["Swift", "version", "5.3-dev", ...]
@@ -60,27 +102,27 @@ fn parse_swift_version(swift_version: &str) -> Option<String> {
     // return "5.2.2" or "5.3-dev"
     let version = splited.next()?;
 
-    Some(format!("v{}", version))
+    Some(version.to_string())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_swift_version;
+    use super::{parse_swift_tools_version, parse_swift_version};
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io;
 
     #[test]
     fn test_parse_swift_version() {
         let input = "Apple Swift version 5.2.2";
-        assert_eq!(parse_swift_version(input), Some(String::from("v5.2.2")));
+        assert_eq!(parse_swift_version(input), Some(String::from("5.2.2")));
     }
 
     #[test]
     fn test_parse_swift_version_without_org_name() {
         let input = "Swift version 5.3-dev (LLVM ..., Swift ...)";
-        assert_eq!(parse_swift_version(input), Some(String::from("v5.3-dev")));
+        assert_eq!(parse_swift_version(input), Some(String::from("5.3-dev")));
     }
 
     #[test]
@@ -118,4 +160,39 @@ mod tests {
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn folder_with_newer_tools_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("Package.swift"),
+            "// swift-tools-version:5.5\nimport PackageDescription\n",
+        )?;
+        let actual = ModuleRenderer::new("swift")
+            .path(dir.path())
+            .config(toml::toml! {
+                [swift]
+                format = "via [$symbol$version( requires $expected)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("🐦 v5.2.2 requires v5.5")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_parse_swift_tools_version() {
+        assert_eq!(
+            parse_swift_tools_version("// swift-tools-version:5.3"),
+            Some(String::from("5.3"))
+        );
+        assert_eq!(
+            parse_swift_tools_version("//swift-tools-version: 5.2.2;(experimental)"),
+            Some(String::from("5.2.2"))
+        );
+        assert_eq!(parse_swift_tools_version("import PackageDescription"), None);
+    }
 }
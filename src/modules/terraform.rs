@@ -1,11 +1,15 @@
+use super::utils::pinned_version::{read_pinned_version, satisfies_pin};
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::terraform::TerraformConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VersionFormatter};
 use crate::utils;
 
+use once_cell::sync::Lazy;
+use regex::Regex;
+use semver::{Version, VersionReq};
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Creates a module with the current Terraform version and workspace
 ///
@@ -13,18 +17,64 @@ use std::path::PathBuf;
 ///     - Current directory contains a `.terraform` directory
 ///     - Current directory contains a file with the `.tf` extension
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("terraform");
+    let config: TerraformConfig = TerraformConfig::try_load(module.config);
+
     let is_terraform_project = context
         .try_begin_scan()?
-        .set_folders(&[".terraform"])
-        .set_extensions(&["tf", "hcl"])
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
         .is_match();
 
     if !is_terraform_project {
         return None;
     }
 
-    let mut module = context.new_module("terraform");
-    let config: TerraformConfig = TerraformConfig::try_load(module.config);
+    let pinned_version = if config.use_pinned_version {
+        read_pinned_version(&context.current_dir, &[".terraform-version"])
+    } else {
+        None
+    };
+    let installed_version = Lazy::new(|| {
+        utils::exec_cmd("terraform", &["version"])
+            .and_then(|output| parse_terraform_version(&output.stdout))
+    });
+
+    // Running `terraform version` is slow, so prefer the pinned version or the one recorded in
+    // the state
+    let terraform_version = if config.format.contains("$version") {
+        pinned_version
+            .clone()
+            .or_else(|| get_terraform_version_from_state(context))
+            .or_else(|| {
+                if config.version_from_binary {
+                    installed_version.clone()
+                } else {
+                    None
+                }
+            })
+    } else {
+        None
+    };
+    // `required_version` is checked against the installed terraform when executing it is
+    // enabled, and otherwise against the version that tfenv would run, or the one recorded in
+    // the state
+    let is_capable = match get_required_version(&context.current_dir) {
+        Some(required_version) => {
+            let checked_version = if config.version_from_binary {
+                installed_version.clone()
+            } else {
+                read_pinned_version(&context.current_dir, &[".terraform-version"])
+                    .or_else(|| get_terraform_version_from_state(context))
+            };
+            match checked_version {
+                Some(version) => check_required_version(&version, &required_version),
+                None => true,
+            }
+        }
+        None => true,
+    };
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -33,14 +83,39 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => {
+                    if is_capable {
+                        Some(Ok(config.style))
+                    } else {
+                        Some(Ok(config.not_capable_style))
+                    }
+                }
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => format_terraform_version(
-                    &utils::exec_cmd("terraform", &["version"])?.stdout.as_str(),
-                )
-                .map(Ok),
+                "version" => {
+                    let terraform_version = VersionFormatter::format_module_version(
+                        context,
+                        module.get_name(),
+                        terraform_version.as_ref()?,
+                        config.version_format,
+                    )?;
+                    Some(Ok(terraform_version + " "))
+                }
+                "installed" => {
+                    let pinned_version = pinned_version.as_ref()?;
+                    let installed_version = installed_version.as_ref()?;
+                    if satisfies_pin(installed_version, pinned_version) {
+                        return None;
+                    }
+                    VersionFormatter::format_module_version(
+                        context,
+                        module.get_name(),
+                        installed_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
                 "workspace" => get_terraform_workspace(context).map(Ok),
                 _ => None,
             })
@@ -66,19 +141,37 @@ fn get_terraform_workspace(context: &Context) -> Option<String> {
         return workspace_override;
     }
 
-    // Data directory containing current workspace can be overwritten by an env var
-    let datadir = match context.get_env("TF_DATA_DIR") {
-        Some(s) => PathBuf::from(s),
-        None => context.current_dir.join(".terraform"),
-    };
-    match utils::read_file(datadir.join("environment")) {
+    match utils::read_file(get_data_dir(context).join("environment")) {
         Err(ref e) if e.kind() == io::ErrorKind::NotFound => Some("default".to_string()),
         Ok(s) => Some(s),
         _ => None,
     }
 }
 
-fn format_terraform_version(version: &str) -> Option<String> {
+// Data directory containing current workspace can be overwritten by an env var
+fn get_data_dir(context: &Context) -> PathBuf {
+    match context.get_env("TF_DATA_DIR") {
+        Some(s) => PathBuf::from(s),
+        None => context.current_dir.join(".terraform"),
+    }
+}
+
+/// Read the version of terraform that last wrote the backend or local state
+fn get_terraform_version_from_state(context: &Context) -> Option<String> {
+    let state_files = [
+        get_data_dir(context).join("terraform.tfstate"),
+        context.current_dir.join("terraform.tfstate"),
+    ];
+
+    state_files.iter().find_map(|state_file| {
+        let json = utils::read_file(state_file).ok()?;
+        let state: serde_json::Value = serde_json::from_str(&json).ok()?;
+        let version = state.get("terraform_version")?.as_str()?;
+        Some(version.to_string())
+    })
+}
+
+fn parse_terraform_version(version: &str) -> Option<String> {
     // `terraform version` output looks like this
     // Terraform v0.12.14
     // With potential extra output if it detects you are not running the latest version
@@ -87,12 +180,96 @@ fn format_terraform_version(version: &str) -> Option<String> {
             .lines()
             .next()?
             .trim_start_matches("Terraform ")
+            .trim_start_matches('v')
             .trim()
-            .to_owned()
-            + " ",
+            .to_owned(),
     )
 }
 
+/// Find the `required_version` constraint in the `*.tf` files of a directory
+fn get_required_version(base_dir: &Path) -> Option<String> {
+    let re = Regex::new(r#"required_version\s*=\s*"([^"]*)""#).unwrap();
+    let mut tf_files: Vec<PathBuf> = base_dir
+        .read_dir()
+        .ok()?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("tf"))
+        .collect();
+    tf_files.sort();
+
+    tf_files.iter().find_map(|path| {
+        let contents = utils::read_file(path).ok()?;
+        let captures = re.captures(&contents)?;
+        Some(captures.get(1)?.as_str().to_string())
+    })
+}
+
+/// Check whether a terraform version like `0.12.14` satisfies a `required_version` constraint
+fn check_required_version(terraform_version: &str, required_version: &str) -> bool {
+    let r = match to_version_req(required_version).and_then(|req| VersionReq::parse(&req).ok()) {
+        Some(r) => r,
+        None => return true,
+    };
+    let re = Regex::new(r"\d+\.\d+\.\d+").unwrap();
+    let version = match re.find(terraform_version) {
+        Some(version) => version.as_str(),
+        None => return true,
+    };
+    let v = match Version::parse(version) {
+        Ok(v) => v,
+        Err(_e) => return true,
+    };
+    r.matches(&v)
+}
+
+/// Translate a terraform version constraint like `~> 0.12, != 0.12.3` to a semver requirement
+///
+/// `!=` constraints have no semver equivalent and are ignored.
+fn to_version_req(constraint: &str) -> Option<String> {
+    let mut requirements = Vec::new();
+    for part in constraint.split(',') {
+        let part = part.trim();
+        let operator_len = part
+            .find(|c: char| c.is_ascii_digit() || c == 'v')
+            .unwrap_or(part.len());
+        let (operator, version) = part.split_at(operator_len);
+        let components = version
+            .trim_start_matches('v')
+            .split('.')
+            .map(|component| component.trim().parse::<u64>().ok())
+            .collect::<Option<Vec<u64>>>()?;
+        let padded = |components: &[u64]| {
+            let mut padded = components.to_vec();
+            padded.resize(3, 0);
+            format!("{}.{}.{}", padded[0], padded[1], padded[2])
+        };
+
+        match operator.trim() {
+            "" | "=" => requirements.push(format!("={}", padded(&components))),
+            "!=" => {}
+            // Allows only the rightmost component of the version to increase
+            "~>" => {
+                requirements.push(format!(">={}", padded(&components)));
+                if components.len() > 1 {
+                    let mut upper = components[..components.len() - 1].to_vec();
+                    *upper.last_mut()? += 1;
+                    requirements.push(format!("<{}", padded(&upper)));
+                }
+            }
+            op @ ">=" | op @ "<=" | op @ ">" | op @ "<" => {
+                requirements.push(format!("{}{}", op, padded(&components)))
+            }
+            _ => return None,
+        }
+    }
+
+    if requirements.is_empty() {
+        None
+    } else {
+        Some(requirements.join(", "))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,44 +279,38 @@ mod tests {
     use std::io::{self, Write};
 
     #[test]
-    fn test_format_terraform_version_release() {
+    fn test_parse_terraform_version_release() {
         let input = "Terraform v0.12.14";
-        assert_eq!(
-            format_terraform_version(input),
-            Some("v0.12.14 ".to_string())
-        );
+        assert_eq!(parse_terraform_version(input), Some("0.12.14".to_string()));
     }
 
     #[test]
-    fn test_format_terraform_version_prerelease() {
+    fn test_parse_terraform_version_prerelease() {
         let input = "Terraform v0.12.14-rc1";
         assert_eq!(
-            format_terraform_version(input),
-            Some("v0.12.14-rc1 ".to_string())
+            parse_terraform_version(input),
+            Some("0.12.14-rc1".to_string())
         );
     }
 
     #[test]
-    fn test_format_terraform_version_development() {
+    fn test_parse_terraform_version_development() {
         let input = "Terraform v0.12.14-dev (cca89f74)";
         assert_eq!(
-            format_terraform_version(input),
-            Some("v0.12.14-dev (cca89f74) ".to_string())
+            parse_terraform_version(input),
+            Some("0.12.14-dev (cca89f74)".to_string())
         );
     }
 
     #[test]
-    fn test_format_terraform_version_multiline() {
+    fn test_parse_terraform_version_multiline() {
         let input = "Terraform v0.12.13
 
 Your version of Terraform is out of date! The latest version
 is 0.12.14. You can update by downloading from www.terraform.io/downloads.html
 
 ";
-        assert_eq!(
-            format_terraform_version(input),
-            Some("v0.12.13 ".to_string())
-        );
+        assert_eq!(parse_terraform_version(input), Some("0.12.13".to_string()));
     }
 
     #[test]
@@ -153,6 +324,7 @@ is 0.12.14. You can update by downloading from www.terraform.io/downloads.html
             .config(toml::toml! {
                 [terraform]
                 format = "via [$symbol$version$workspace]($style) "
+                version_from_binary = true
             })
             .collect();
 
@@ -178,6 +350,7 @@ is 0.12.14. You can update by downloading from www.terraform.io/downloads.html
             .config(toml::toml! {
                 [terraform]
                 format = "via [$symbol$version$workspace]($style) "
+                version_from_binary = true
             })
             .collect();
 
@@ -200,6 +373,30 @@ is 0.12.14. You can update by downloading from www.terraform.io/downloads.html
         dir.close()
     }
 
+    #[test]
+    fn folder_with_terraform_version_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.tf"))?;
+        fs::write(dir.path().join(".terraform-version"), "0.13.5\n")?;
+
+        let actual = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                format = "via [$symbol$version$workspace( \\(installed $installed\\))]($style) "
+                use_pinned_version = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105)
+                .bold()
+                .paint("💠 v0.13.5 default (installed v0.12.14)")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
     #[test]
     fn folder_with_tf_file() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -291,4 +488,227 @@ is 0.12.14. You can update by downloading from www.terraform.io/downloads.html
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn test_to_version_req() {
+        assert_eq!(to_version_req("0.12.14"), Some("=0.12.14".to_string()));
+        assert_eq!(
+            to_version_req(">= 0.12, < 0.14"),
+            Some(">=0.12.0, <0.14.0".to_string())
+        );
+        assert_eq!(
+            to_version_req("~> 0.12"),
+            Some(">=0.12.0, <1.0.0".to_string())
+        );
+        assert_eq!(
+            to_version_req("~> 0.12.1, != 0.12.3"),
+            Some(">=0.12.1, <0.13.0".to_string())
+        );
+        assert_eq!(to_version_req("latest"), None);
+    }
+
+    #[test]
+    fn required_version_satisfied() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut file = File::create(dir.path().join("versions.tf"))?;
+        file.write_all(b"terraform {\n  required_version = \"~> 0.12.0\"\n}\n")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                format = "via [$symbol$version$workspace]($style) "
+                version_from_binary = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105).bold().paint("💠 v0.12.14 default")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn required_version_not_satisfied() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut file = File::create(dir.path().join("versions.tf"))?;
+        file.write_all(b"terraform {\n  required_version = \">= 0.13\"\n}\n")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                format = "via [$symbol$version$workspace]($style) "
+                version_from_binary = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("💠 v0.12.14 default")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn required_version_not_satisfied_default_format() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut file = File::create(dir.path().join("versions.tf"))?;
+        file.write_all(b"terraform {\n  required_version = \">= 0.13\"\n}\n")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                version_from_binary = true
+            })
+            .collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("💠 default")));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn required_version_checks_installed_not_state() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut file = File::create(dir.path().join("versions.tf"))?;
+        file.write_all(b"terraform {\n  required_version = \">= 0.13\"\n}\n")?;
+        file.sync_all()?;
+        let mut file = File::create(dir.path().join("terraform.tfstate"))?;
+        file.write_all(b"{\"version\": 4, \"terraform_version\": \"0.14.5\"}")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                format = "via [$symbol$version$workspace]($style) "
+                version_from_binary = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Red.bold().paint("💠 v0.14.5 default")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn required_version_not_checked_without_binary() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut file = File::create(dir.path().join("versions.tf"))?;
+        file.write_all(b"terraform {\n  required_version = \">= 0.13\"\n}\n")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("terraform").path(dir.path()).collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105).bold().paint("💠 default")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn required_version_checks_pin_without_binary() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut file = File::create(dir.path().join("versions.tf"))?;
+        file.write_all(b"terraform {\n  required_version = \">= 0.13\"\n}\n")?;
+        file.sync_all()?;
+        let mut file = File::create(dir.path().join(".terraform-version"))?;
+        file.write_all(b"0.12.31\n")?;
+        file.sync_all()?;
+        let mut file = File::create(dir.path().join("terraform.tfstate"))?;
+        file.write_all(b"{\"version\": 4, \"terraform_version\": \"0.14.5\"}")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("terraform").path(dir.path()).collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("💠 default")));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn required_version_checks_state_without_binary() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut file = File::create(dir.path().join("versions.tf"))?;
+        file.write_all(b"terraform {\n  required_version = \">= 0.13\"\n}\n")?;
+        file.sync_all()?;
+        let mut file = File::create(dir.path().join("terraform.tfstate"))?;
+        file.write_all(b"{\"version\": 4, \"terraform_version\": \"0.12.31\"}")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("terraform").path(dir.path()).collect();
+        let expected = Some(format!("via {} ", Color::Red.bold().paint("💠 default")));
+        assert_eq!(expected, actual);
+
+        let mut file = File::create(dir.path().join("terraform.tfstate"))?;
+        file.write_all(b"{\"version\": 4, \"terraform_version\": \"0.14.5\"}")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("terraform").path(dir.path()).collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105).bold().paint("💠 default")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn version_from_backend_state() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tf_dir = dir.path().join(".terraform");
+        fs::create_dir(&tf_dir)?;
+        let mut file = File::create(tf_dir.join("terraform.tfstate"))?;
+        file.write_all(b"{\"version\": 3, \"terraform_version\": \"0.14.5\"}")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                format = "via [$symbol$version$workspace]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105).bold().paint("💠 v0.14.5 default")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn version_without_state_or_binary() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.tf"))?;
+
+        let actual = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                format = "via [$symbol$version$workspace]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105).bold().paint("💠 default")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
 }
@@ -1,3 +1,4 @@
+use super::utils::remote;
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::username::UsernameConfig;
@@ -16,12 +17,15 @@ const USERNAME_ENV_VAR: &str = "USERNAME";
 /// Will display the username if any of the following criteria are met:
 ///     - The current user isn't the same as the one that is logged in (`$LOGNAME` != `$USER`)
 ///     - The current user is root (UID = 0)
-///     - The user is currently connected as an SSH session (`$SSH_CONNECTION`)
+///     - The user is currently connected as a remote session (see `remote_detection`)
+///     - The current user is listed in `show_for_users`
+///
+/// unless the current user is listed in `hide_for_users`.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let username = context.get_env(USERNAME_ENV_VAR)?;
     let logname = context.get_env("LOGNAME");
 
-    let user_uid = get_uid();
+    let user_uid = get_uid(context);
 
     let is_not_login = logname.is_some() && username != logname.unwrap();
     let is_root = user_uid == ROOT_UID;
@@ -29,12 +33,26 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("username");
     let config: UsernameConfig = UsernameConfig::try_load(module.config);
 
-    if is_not_login || is_ssh_connection(&context) || is_root || config.show_always {
+    let is_hidden_user = config.hide_for_users.contains(&username.as_str());
+    let is_shown_user = config.show_for_users.contains(&username.as_str());
+
+    if is_hidden_user {
+        return None;
+    }
+
+    if is_not_login
+        || is_root
+        || is_shown_user
+        || config.show_always
+        || remote::is_remote_session(context, &config.remote_detection)
+    {
         let parsed = StringFormatter::new(config.format).and_then(|formatter| {
             formatter
                 .map_style(|variable| match variable {
                     "style" => {
-                        let module_style = if is_root {
+                        let module_style = if let Some(style) = config.user_styles.get(&username) {
+                            style
+                        } else if is_root {
                             config.style_root
                         } else {
                             config.style_user
@@ -63,12 +81,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
-fn is_ssh_connection(context: &Context) -> bool {
-    let ssh_env = ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"];
-    ssh_env.iter().any(|env| context.get_env(env).is_some())
-}
-
-fn get_uid() -> Option<u32> {
+#[cfg(not(test))]
+fn get_uid(_context: &Context) -> Option<u32> {
     utils::exec_cmd("id", &["-u"])?
         .stdout
         .trim()
@@ -76,6 +90,19 @@ fn get_uid() -> Option<u32> {
         .ok()
 }
 
+/// The uid given by tests in `STARSHIP_TEST_UID`, so they don't depend on who runs them
+#[cfg(test)]
+fn get_uid(context: &Context) -> Option<u32> {
+    match context.get_env("STARSHIP_TEST_UID") {
+        Some(uid) => uid.parse::<u32>().ok(),
+        None => utils::exec_cmd("id", &["-u"])?
+            .stdout
+            .trim()
+            .parse::<u32>()
+            .ok(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::ModuleRenderer;
@@ -191,4 +218,91 @@ mod tests {
         assert_eq!(expected, actual);
         Ok(())
     }
+
+    #[test]
+    fn show_for_users() -> io::Result<()> {
+        let actual = ModuleRenderer::new("username")
+            .env("LOGNAME", "deploy")
+            .env(super::USERNAME_ENV_VAR, "deploy")
+            .env("STARSHIP_TEST_UID", "1000")
+            .config(toml::toml! {
+                [username]
+                show_for_users = ["deploy"]
+            })
+            .collect();
+        let expected = Some(format!("{} in ", Color::Yellow.bold().paint("deploy")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn not_shown_for_other_users() -> io::Result<()> {
+        let actual = ModuleRenderer::new("username")
+            .env("LOGNAME", "astronaut")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .env("STARSHIP_TEST_UID", "1000")
+            .config(toml::toml! {
+                [username]
+                show_for_users = ["deploy"]
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn hide_for_users() -> io::Result<()> {
+        let actual = ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .env("SSH_CONNECTION", "192.168.223.17 36673 192.168.223.229 22")
+            .env("STARSHIP_TEST_UID", "1000")
+            .config(toml::toml! {
+                [username]
+                show_always = true
+                hide_for_users = ["astronaut"]
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn user_styles() -> io::Result<()> {
+        let actual = ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "deploy")
+            .env("STARSHIP_TEST_UID", "1000")
+            .config(toml::toml! {
+                [username]
+                show_always = true
+                [username.user_styles]
+                deploy = "bold purple"
+            })
+            .collect();
+        let expected = Some(format!("{} in ", Color::Purple.bold().paint("deploy")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
+
+    #[test]
+    fn ssh_connection_in_tmux() -> io::Result<()> {
+        let actual = ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .env("TMUX", "/tmp/tmux-1000/default,1234,0")
+            .env("STARSHIP_TEST_UID", "1000")
+            .config(toml::toml! {
+                [username]
+                remote_detection = ["env", "tmux"]
+            })
+            .collect();
+        let expected = Some(format!("{} in ", Color::Yellow.bold().paint("astronaut")));
+
+        assert_eq!(expected, actual);
+        Ok(())
+    }
 }
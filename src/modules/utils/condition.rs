@@ -0,0 +1,264 @@
+use regex::Regex;
+
+use crate::context::Context;
+
+/// Evaluates a condition like `env.AWS_PROFILE == "prod" && !file(".envrc")` in-process
+///
+/// The supported terms are:
+///     - `env.NAME`: the environment variable is set and not empty
+///     - `env.NAME == "value"`, `env.NAME != "value"`: the variable equals a value
+///     - `env.NAME =~ "regex"`: the variable matches a regular expression
+///     - `file("path")`: the path exists, relative to the current directory
+///     - `os == "linux"`, `os != "windows"`: the operating system, as in `std::env::consts::OS`
+///
+/// Terms can be combined with `!`, `&&`, `||` and parentheses.
+pub fn evaluate(condition: &str, context: &Context) -> Result<bool, String> {
+    let tokens = tokenize(condition)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        context,
+    };
+
+    let result = parser.parse_or()?;
+    match parser.peek() {
+        None => Ok(result),
+        Some(token) => Err(format!("Unexpected {:?}", token)),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    And,
+    Or,
+    Not,
+    Eq,
+    NotEq,
+    Matches,
+    OpenParen,
+    CloseParen,
+}
+
+fn tokenize(condition: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = condition.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            '=' if chars.next_if_eq(&'=').is_some() => Token::Eq,
+            '=' if chars.next_if_eq(&'~').is_some() => Token::Matches,
+            '!' if chars.next_if_eq(&'=').is_some() => Token::NotEq,
+            '!' => Token::Not,
+            '"' | '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') if c == '"' => value.extend(chars.next()),
+                        Some(end) if end == c => break,
+                        Some(other) => value.push(other),
+                        None => return Err("Unterminated string".to_string()),
+                    }
+                }
+                Token::Str(value)
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some(next) =
+                    chars.next_if(|c| c.is_alphanumeric() || *c == '_' || *c == '.')
+                {
+                    ident.push(next);
+                }
+                Token::Ident(ident)
+            }
+            other => return Err(format!("Unexpected character {:?}", other)),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a, 'b> {
+    tokens: &'a [Token],
+    position: usize,
+    context: &'a Context<'b>,
+}
+
+impl<'a, 'b> Parser<'a, 'b> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("Expected {:?}, found {:?}", expected, token)),
+            None => Err(format!("Expected {:?}", expected)),
+        }
+    }
+
+    fn expect_string(&mut self) -> Result<&'a str, String> {
+        match self.next() {
+            Some(Token::Str(value)) => Ok(value),
+            Some(token) => Err(format!("Expected a string, found {:?}", token)),
+            None => Err("Expected a string".to_string()),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<bool, String> {
+        let mut result = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            result |= self.parse_and()?;
+        }
+        Ok(result)
+    }
+
+    fn parse_and(&mut self) -> Result<bool, String> {
+        let mut result = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            result &= self.parse_unary()?;
+        }
+        Ok(result)
+    }
+
+    fn parse_unary(&mut self) -> Result<bool, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(!self.parse_unary()?),
+            Some(Token::OpenParen) => {
+                let result = self.parse_or()?;
+                self.expect(&Token::CloseParen)?;
+                Ok(result)
+            }
+            Some(Token::Ident(ident)) => self.parse_term(ident),
+            Some(token) => Err(format!("Unexpected {:?}", token)),
+            None => Err("Unexpected end of condition".to_string()),
+        }
+    }
+
+    fn parse_term(&mut self, ident: &str) -> Result<bool, String> {
+        if ident == "file" {
+            self.expect(&Token::OpenParen)?;
+            let path = self.expect_string()?;
+            self.expect(&Token::CloseParen)?;
+            return Ok(self.context.current_dir.join(path).exists());
+        }
+
+        let value = if ident == "os" {
+            Some(std::env::consts::OS.to_string())
+        } else if let Some(name) = ident.strip_prefix("env.") {
+            self.context.get_env(name)
+        } else {
+            return Err(format!("Unknown term {:?}", ident));
+        };
+
+        match self.peek() {
+            Some(Token::Eq) => {
+                self.next();
+                Ok(value.as_deref() == Some(self.expect_string()?))
+            }
+            Some(Token::NotEq) => {
+                self.next();
+                Ok(value.as_deref() != Some(self.expect_string()?))
+            }
+            Some(Token::Matches) => {
+                self.next();
+                let regex = Regex::new(self.expect_string()?).map_err(|error| error.to_string())?;
+                Ok(matches!(value, Some(value) if regex.is_match(&value)))
+            }
+            _ if ident == "os" => Err("Expected a comparison after `os`".to_string()),
+            _ => Ok(matches!(value, Some(value) if !value.is_empty())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use std::fs::File;
+    use std::io;
+    use std::path::Path;
+
+    fn evaluate_in(condition: &str, dir: &Path) -> Result<bool, String> {
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir);
+        context.config = StarshipConfig { config: None };
+        context.env.insert("PROFILE", "prod-eu".to_string());
+        context.env.insert("EMPTY", String::new());
+        evaluate(condition, &context)
+    }
+
+    #[test]
+    fn test_env_conditions() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let evaluate = |condition| evaluate_in(condition, dir.path());
+
+        assert_eq!(evaluate("env.PROFILE"), Ok(true));
+        assert_eq!(evaluate("env.EMPTY"), Ok(false));
+        assert_eq!(evaluate("env.PROFILE == 'prod-eu'"), Ok(true));
+        assert_eq!(evaluate(r#"env.PROFILE != "prod-eu""#), Ok(false));
+        assert_eq!(evaluate(r#"env.PROFILE =~ "^prod-""#), Ok(true));
+        assert_eq!(evaluate("env.UNSET =~ '.*'"), Ok(false));
+        dir.close()
+    }
+
+    #[test]
+    fn test_file_and_os_conditions() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".envrc"))?.sync_all()?;
+        let evaluate = |condition| evaluate_in(condition, dir.path());
+
+        assert_eq!(evaluate("file('.envrc')"), Ok(true));
+        assert_eq!(evaluate("file('missing')"), Ok(false));
+        assert_eq!(evaluate("os == 'none'"), Ok(false));
+        assert_eq!(evaluate("os != 'none'"), Ok(true));
+        dir.close()
+    }
+
+    #[test]
+    fn test_combined_conditions() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let evaluate = |condition| evaluate_in(condition, dir.path());
+
+        assert_eq!(evaluate("!env.UNSET && env.PROFILE"), Ok(true));
+        assert_eq!(evaluate("env.UNSET || os == 'none'"), Ok(false));
+        assert_eq!(evaluate("env.UNSET || env.PROFILE && !env.EMPTY"), Ok(true));
+        assert_eq!(evaluate("!(env.UNSET || env.PROFILE)"), Ok(false));
+        dir.close()
+    }
+
+    #[test]
+    fn test_invalid_conditions() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let evaluate = |condition| evaluate_in(condition, dir.path());
+
+        for condition in &[
+            "",
+            "env.PROFILE ==",
+            "file(.envrc)",
+            "os",
+            "foo",
+            "(env.PROFILE",
+            "env.PROFILE)",
+            "'unterminated",
+            "env.PROFILE =~ '('",
+        ] {
+            assert!(evaluate(condition).is_err(), "{:?}", condition);
+        }
+        dir.close()
+    }
+}
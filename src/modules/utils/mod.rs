@@ -1,4 +1,8 @@
+pub mod condition;
 pub mod directory;
+#[cfg(any(feature = "languages", feature = "cloud"))]
+pub mod pinned_version;
+pub mod remote;
 
 #[cfg(target_os = "windows")]
 pub mod directory_win;
@@ -0,0 +1,147 @@
+use std::path::Path;
+
+#[cfg(feature = "languages")]
+use crate::context::Context;
+use crate::utils;
+
+/// Reads the version pinned by a version manager file (e.g. `.node-version`) in `dir`
+///
+/// The files are tried in order and the first pinned version wins. Aliases like `lts/*` or
+/// `system` can't be resolved without running the version manager, so they are skipped.
+pub fn read_pinned_version(dir: &Path, files: &[&str]) -> Option<String> {
+    files.iter().find_map(|file| {
+        let contents = utils::read_file(dir.join(file)).ok()?;
+        let pin = contents
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))?
+            .trim_start_matches('v');
+
+        if pin.starts_with(|c: char| c.is_ascii_digit()) {
+            Some(pin.to_string())
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(feature = "languages")]
+/// Reads the nearest asdf tool versions file, like asdf does when looking up a version
+pub fn find_tool_versions(context: &Context) -> Option<String> {
+    let file_name = context
+        .get_env("ASDF_DEFAULT_TOOL_VERSIONS_FILENAME")
+        .unwrap_or_else(|| ".tool-versions".to_string());
+    context
+        .current_dir
+        .ancestors()
+        .find_map(|dir| utils::read_file(dir.join(&file_name)).ok())
+}
+
+#[cfg(feature = "languages")]
+/// Parses the tools and their versions from a `.tool-versions` file
+///
+/// A tool may list fallback versions, and only the first one is kept.
+pub fn parse_tool_versions(contents: &str) -> Vec<(&str, &str)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split('#').next()?.split_whitespace();
+            Some((words.next()?, words.next()?))
+        })
+        .collect()
+}
+
+/// Whether an installed version satisfies a pin, which may leave out the lower components
+/// (e.g. `14` is satisfied by `14.2.0`)
+pub fn satisfies_pin(installed: &str, pin: &str) -> bool {
+    let installed: Vec<&str> = installed
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .collect();
+
+    pin.split('.')
+        .enumerate()
+        .all(|(index, component)| installed.get(index) == Some(&component))
+}
+
+#[cfg(feature = "languages")]
+/// Whether a version like `1.52.0-nightly` is older than a minimum version like `1.56`
+///
+/// Missing components are zero, so `1.56` is the same as `1.56.0`. Versions that can't be
+/// compared, like toolchain names, are never older.
+pub fn is_older_than(version: &str, minimum: &str) -> bool {
+    let components = |version: &str| -> Option<[u64; 3]> {
+        let mut components = [0; 3];
+        let version = version
+            .trim_start_matches('v')
+            .split(|c: char| c == '-' || c.is_whitespace())
+            .next()?;
+        for (index, component) in version.split('.').enumerate() {
+            // Pre-releases like Go's `1.21rc1` are compared as their release
+            let digits = component.find(|c: char| !c.is_ascii_digit());
+            let digits = &component[..digits.unwrap_or(component.len())];
+            *components.get_mut(index)? = digits.parse().ok()?;
+        }
+        Some(components)
+    };
+
+    match (components(version), components(minimum)) {
+        (Some(version), Some(minimum)) => version < minimum,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::io;
+
+    #[test]
+    fn test_read_pinned_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".nvmrc"), "lts/*\n")?;
+        fs::write(dir.path().join(".node-version"), "# pinned\nv14.2.0\n")?;
+        File::create(dir.path().join(".python-version"))?.sync_all()?;
+
+        assert_eq!(read_pinned_version(dir.path(), &[".nvmrc"]), None);
+        assert_eq!(
+            read_pinned_version(dir.path(), &[".nvmrc", ".node-version"]),
+            Some("14.2.0".to_string())
+        );
+        assert_eq!(read_pinned_version(dir.path(), &[".python-version"]), None);
+        assert_eq!(read_pinned_version(dir.path(), &[".tool-versions"]), None);
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(feature = "languages")]
+    fn test_parse_tool_versions() {
+        let contents = "nodejs 14.2.0 system\n\n  # comment\nrust   ref:master\nbroken\n";
+        assert_eq!(
+            parse_tool_versions(contents),
+            vec![("nodejs", "14.2.0"), ("rust", "ref:master")]
+        );
+    }
+
+    #[test]
+    fn test_satisfies_pin() {
+        assert!(satisfies_pin("v14.2.0\n", "14"));
+        assert!(satisfies_pin("14.2.0", "14.2.0"));
+        assert!(!satisfies_pin("14.2.0", "14.3"));
+        assert!(!satisfies_pin("14.2", "14.2.0"));
+        assert!(!satisfies_pin("1.40.0", "1.4"));
+    }
+
+    #[test]
+    #[cfg(feature = "languages")]
+    fn test_is_older_than() {
+        assert!(is_older_than("1.50.0", "1.56"));
+        assert!(is_older_than("1.55.0-nightly", "1.56.0"));
+        assert!(is_older_than("1.20.5", "1.21rc1"));
+        assert!(!is_older_than("1.56", "1.56.0"));
+        assert!(!is_older_than("v1.60.0", "1.56"));
+        assert!(!is_older_than("nightly-2021-02-17", "1.56"));
+    }
+}
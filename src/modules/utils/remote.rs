@@ -0,0 +1,113 @@
+use crate::context::Context;
+use crate::utils;
+
+/// Names of the ways a remote session can be detected, in the order they are tried
+///
+/// - `env`: `$SSH_CONNECTION`, `$SSH_CLIENT` or `$SSH_TTY` are set
+/// - `tmux`: the tmux session was last attached to over SSH
+/// - `logind`: systemd-logind reports the login session as remote
+/// - `who`: the terminal's login record has a remote host, which survives `su -l`
+pub const DETECTORS: &[&str] = &["env", "tmux", "logind", "who"];
+
+/// Whether the shell is running in a remote session, according to any of the
+/// given detectors
+pub fn is_remote_session(context: &Context, detectors: &[&str]) -> bool {
+    detectors.iter().any(|&detector| {
+        let is_remote = match detector {
+            "env" => has_ssh_env(context),
+            "tmux" => is_tmux_attached_over_ssh(context),
+            "logind" => is_logind_session_remote(),
+            "who" => has_remote_login_record(),
+            _ => {
+                log::warn!(
+                    "Unknown remote session detector `{}`, expected one of {:?}",
+                    detector,
+                    DETECTORS
+                );
+                false
+            }
+        };
+        if is_remote {
+            log::trace!("Remote session detected by `{}`", detector);
+        }
+        is_remote
+    })
+}
+
+fn has_ssh_env(context: &Context) -> bool {
+    let ssh_env = ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"];
+    ssh_env.iter().any(|env| context.get_env(env).is_some())
+}
+
+/// tmux updates `SSH_CONNECTION` in the session environment on every attach,
+/// while the shell's own environment keeps the value from when it was started
+fn is_tmux_attached_over_ssh(context: &Context) -> bool {
+    if context.get_env("TMUX").is_none() {
+        return false;
+    }
+
+    match utils::exec_cmd("tmux", &["show-environment", "SSH_CONNECTION"]) {
+        // A removed variable is shown as `-SSH_CONNECTION`
+        Some(output) => output.stdout.trim().starts_with("SSH_CONNECTION="),
+        None => false,
+    }
+}
+
+fn is_logind_session_remote() -> bool {
+    match utils::exec_cmd(
+        "loginctl",
+        &["show-session", "self", "--property=Remote", "--value"],
+    ) {
+        Some(output) => output.stdout.trim() == "yes",
+        None => false,
+    }
+}
+
+fn has_remote_login_record() -> bool {
+    match utils::exec_cmd("who", &["-m"]) {
+        Some(output) => is_remote_login_record(&output.stdout),
+        None => false,
+    }
+}
+
+/// Parse a line of `who -m` output, e.g. `matchai pts/0 2021-01-09 10:02 (192.168.0.2)`
+///
+/// Local logins either have no host, an X display like `(:0)`, or a
+/// multiplexer pane like `(tmux(1234).%0)`.
+fn is_remote_login_record(record: &str) -> bool {
+    let host = match (record.find('('), record.rfind(')')) {
+        (Some(start), Some(end)) if start < end => &record[start + 1..end],
+        _ => return false,
+    };
+
+    !host.is_empty() && !host.starts_with(':') && !host.starts_with("tmux(")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_login_record() {
+        assert!(is_remote_login_record(
+            "matchai  pts/0        2021-01-09 10:02 (192.168.0.2)\n"
+        ));
+        assert!(is_remote_login_record(
+            "matchai  pts/0        2021-01-09 10:02 (laptop.local)\n"
+        ));
+    }
+
+    #[test]
+    fn local_login_record() {
+        assert!(!is_remote_login_record(""));
+        assert!(!is_remote_login_record(
+            "matchai  tty1         2021-01-09 10:02\n"
+        ));
+        assert!(!is_remote_login_record(
+            "matchai  pts/1        2021-01-09 10:02 (:0)\n"
+        ));
+        assert!(!is_remote_login_record(
+            "matchai  pts/2        2021-01-09 10:02 (tmux(1234).%0)\n"
+        ));
+    }
+}
@@ -0,0 +1,347 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::context::Context;
+use crate::utils;
+
+/// The colors of a palette by the name of their slot, e.g. `accent = "#88c0d0"`
+pub type Palette = HashMap<String, String>;
+
+thread_local! {
+    // The palette of the context whose styles are being parsed on this thread
+    static PALETTE: RefCell<Option<Arc<Palette>>> = const { RefCell::new(None) };
+}
+
+/// Makes the colors of a palette available to style strings on this thread, until the
+/// returned guard is dropped
+///
+/// Styles are parsed without a context, and a module is computed on a single thread, so the
+/// palette of its context is set for the thread while it's computed.
+pub fn use_palette(palette: Arc<Palette>) -> PaletteGuard {
+    let previous = PALETTE.with(|current| current.replace(Some(palette)));
+    PaletteGuard(previous)
+}
+
+pub struct PaletteGuard(Option<Arc<Palette>>);
+
+impl Drop for PaletteGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        PALETTE.with(|current| current.replace(previous));
+    }
+}
+
+/// The color of a slot in the palette used on this thread
+pub fn lookup(slot: &str) -> Option<String> {
+    PALETTE.with(|current| current.borrow().as_ref()?.get(slot).cloned())
+}
+
+/// Loads the palette selected by `palette` from the `[palettes]` of the config
+///
+/// With `palette = "auto"`, the `light` or `dark` palette is picked for the background of the
+/// terminal.
+pub fn load(context: &Context) -> Palette {
+    let config = context.config.get_root_config();
+    let name = match config.palette {
+        "" => return Palette::new(),
+        "auto" => match detect_background(context, config.palette_query_timeout) {
+            Some(Background::Light) => "light",
+            Some(Background::Dark) => "dark",
+            None => {
+                log::debug!("Unable to detect the background of the terminal");
+                return Palette::new();
+            }
+        },
+        name => name,
+    };
+
+    let palette = context.config.get_config(&["palettes", name]);
+    let palette = match palette.and_then(toml::Value::as_table) {
+        Some(palette) => palette,
+        None => {
+            log::warn!("The palette `{}` isn't defined in [palettes]", name);
+            return Palette::new();
+        }
+    };
+    palette
+        .iter()
+        .filter_map(|(slot, color)| Some((slot.to_lowercase(), color.as_str()?.to_string())))
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Background {
+    Light,
+    Dark,
+}
+
+impl Background {
+    fn of_color((r, g, b): (f64, f64, f64)) -> Self {
+        let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        if luminance > 0.5 {
+            Background::Light
+        } else {
+            Background::Dark
+        }
+    }
+}
+
+/// Detects whether the terminal has a light or dark background
+///
+/// `COLORFGBG` is used when the terminal sets it. Otherwise the terminal is asked for its
+/// background color once per session, since its answer may take up to the timeout. Outside
+/// of a session, e.g. when starship is run by hand, the terminal isn't asked.
+fn detect_background(context: &Context, timeout: u64) -> Option<Background> {
+    if let Some(colorfgbg) = context.get_env("COLORFGBG") {
+        return parse_colorfgbg(&colorfgbg);
+    }
+    if cfg!(test) {
+        return None;
+    }
+
+    let session = context.get_env("STARSHIP_SESSION_KEY")?;
+    let cache_dir = context
+        .get_env_os("STARSHIP_CACHE")
+        .map(PathBuf::from)
+        .or_else(|| dirs_next::home_dir().map(|home| home.join(".cache/starship")))?;
+    let cache_file = cache_dir.join(format!("background_{}", session));
+    if let Ok(cached) = fs::read_to_string(&cache_file) {
+        return match cached.as_str() {
+            "light" => Some(Background::Light),
+            "dark" => Some(Background::Dark),
+            _ => None,
+        };
+    }
+
+    // The file of the session is written before asking, so that the terminal is asked at most
+    // once, even if the answer comes after the timeout or the prompt is interrupted
+    utils::remove_stale_session_files(&cache_dir);
+    let written = fs::create_dir_all(&cache_dir).and_then(|_| fs::write(&cache_file, "unknown"));
+    if let Err(error) = written {
+        log::debug!("Unable to cache the background of the terminal: {}", error);
+        return None;
+    }
+
+    let background = match query_background(Duration::from_millis(timeout)) {
+        Answer::Color(response) => parse_osc_color(&response).map(Background::of_color),
+        Answer::Unanswered => None,
+        Answer::TypedAhead => {
+            // Ask again at the next prompt
+            let _ = fs::remove_file(&cache_file);
+            return None;
+        }
+    };
+    let cached = match background {
+        Some(Background::Light) => "light",
+        Some(Background::Dark) => "dark",
+        None => "unknown",
+    };
+    if let Err(error) = fs::write(&cache_file, cached) {
+        log::debug!("Unable to cache the background of the terminal: {}", error);
+    }
+    background
+}
+
+/// Parses `COLORFGBG`, e.g. `15;0`, whose last number is the ANSI color of the background
+fn parse_colorfgbg(colorfgbg: &str) -> Option<Background> {
+    match colorfgbg.rsplit(';').next()?.parse::<u8>().ok()? {
+        7 | 9..=15 => Some(Background::Light),
+        _ => Some(Background::Dark),
+    }
+}
+
+/// Parses the answer to an OSC 10, 11 or 4 query, e.g. `\x1b]11;rgb:ffff/ffff/ffff\x07`,
+/// into the components of the color between 0 and 1
+fn parse_osc_color(response: &str) -> Option<(f64, f64, f64)> {
+    let rgb = response.split("rgb:").nth(1)?;
+    let rgb = rgb.trim_end_matches(&['\x07', '\\', '\x1b'][..]);
+    let mut components = rgb.split('/').map(|component| {
+        let max = 16_u32.checked_pow(component.len() as u32)? - 1;
+        let value = u32::from_str_radix(component, 16).ok()?;
+        Some(f64::from(value) / f64::from(max))
+    });
+    let color = (
+        components.next()??,
+        components.next()??,
+        components.next()??,
+    );
+    match components.next() {
+        None => Some(color),
+        Some(_) => None,
+    }
+}
+
+/// The outcome of asking the terminal for its background color
+#[cfg_attr(windows, allow(dead_code))]
+enum Answer {
+    /// The answer of the terminal, e.g. `\x1b]11;rgb:ffff/ffff/ffff\x07`
+    Color(String),
+    /// The terminal can't be asked, or didn't answer before the timeout
+    Unanswered,
+    /// The terminal wasn't asked, because keys typed ahead were waiting to be read
+    TypedAhead,
+}
+
+/// Asks the terminal for its background color with OSC 11, and returns its answer
+///
+/// The answer is read from the same input as the keys typed by the user, so the terminal isn't
+/// asked while typed keys are waiting, and the answer is read byte by byte to leave the keys
+/// typed after it alone. Keys typed while waiting for the answer are lost.
+#[cfg(not(windows))]
+fn query_background(timeout: Duration) -> Answer {
+    use nix::poll::{poll, PollFd, PollFlags};
+    use nix::sys::termios::{self, LocalFlags, SetArg};
+    use std::fs::OpenOptions;
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+    use std::time::Instant;
+
+    let mut tty = match OpenOptions::new().read(true).write(true).open("/dev/tty") {
+        Ok(tty) => tty,
+        Err(_) => return Answer::Unanswered,
+    };
+    let fd = tty.as_raw_fd();
+    let original = match termios::tcgetattr(fd) {
+        Ok(original) => original,
+        Err(_) => return Answer::Unanswered,
+    };
+    let mut raw = original.clone();
+    // Read the answer as it comes, without echoing it
+    raw.local_flags
+        .remove(LocalFlags::ICANON | LocalFlags::ECHO);
+    if termios::tcsetattr(fd, SetArg::TCSANOW, &raw).is_err() {
+        return Answer::Unanswered;
+    }
+
+    let is_readable = |timeout: Duration| {
+        let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+        matches!(poll(&mut fds, timeout.as_millis() as i32), Ok(n) if n > 0)
+    };
+    let answer = if is_readable(Duration::from_millis(0)) {
+        Answer::TypedAhead
+    } else if tty.write_all(b"\x1b]11;?\x07").is_ok() {
+        let start = Instant::now();
+        let mut response = Vec::new();
+        let mut byte = [0; 1];
+        // The answer ends with BEL or ST (`ESC \`)
+        while !response.ends_with(b"\x07") && !response.ends_with(b"\x1b\\") {
+            let remaining = timeout.saturating_sub(start.elapsed());
+            if remaining.as_millis() == 0 || !is_readable(remaining) {
+                break;
+            }
+            match tty.read(&mut byte) {
+                Ok(1) => response.push(byte[0]),
+                _ => break,
+            }
+        }
+        match String::from_utf8(response) {
+            Ok(response) => Answer::Color(response),
+            Err(_) => Answer::Unanswered,
+        }
+    } else {
+        Answer::Unanswered
+    };
+
+    let _ = termios::tcsetattr(fd, SetArg::TCSANOW, &original);
+    answer
+}
+
+#[cfg(windows)]
+fn query_background(_timeout: Duration) -> Answer {
+    Answer::Unanswered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{parse_style_string, StarshipConfig};
+    use ansi_term::Color;
+
+    fn load_palette(config: toml::Value, env: &[(&'static str, &str)]) -> Palette {
+        let dir = tempfile::tempdir().unwrap();
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir.path());
+        context.config = StarshipConfig {
+            config: Some(config),
+        };
+        for (key, value) in env {
+            context.env.insert(key, value.to_string());
+        }
+        load(&context)
+    }
+
+    #[test]
+    fn styles_use_palette_slots() {
+        let palette: Palette = vec![("accent".to_string(), "#88c0d0".to_string())]
+            .into_iter()
+            .collect();
+
+        assert_eq!(parse_style_string("bold accent"), None);
+        {
+            let _palette = use_palette(Arc::new(palette));
+            assert_eq!(
+                parse_style_string("bold accent bg:accent"),
+                Some(
+                    Color::RGB(0x88, 0xc0, 0xd0)
+                        .on(Color::RGB(0x88, 0xc0, 0xd0))
+                        .bold()
+                )
+            );
+            assert_eq!(parse_style_string("red"), Some(Color::Red.normal()));
+        }
+        assert_eq!(parse_style_string("accent"), None);
+    }
+
+    #[test]
+    fn palette_is_picked_for_background() {
+        let config = toml::toml! {
+            palette = "auto"
+            [palettes.light]
+            accent = "blue"
+            [palettes.dark]
+            Accent = "cyan"
+        };
+
+        let light = load_palette(config.clone(), &[("COLORFGBG", "0;15")]);
+        assert_eq!(light.get("accent").map(String::as_str), Some("blue"));
+        let dark = load_palette(config.clone(), &[("COLORFGBG", "15;default;0")]);
+        assert_eq!(dark.get("accent").map(String::as_str), Some("cyan"));
+        assert!(load_palette(config, &[]).is_empty());
+    }
+
+    #[test]
+    fn palette_is_picked_by_name() {
+        let config = toml::toml! {
+            palette = "nord"
+            [palettes.nord]
+            accent = "#88c0d0"
+        };
+        let palette = load_palette(config, &[]);
+        assert_eq!(palette.get("accent").map(String::as_str), Some("#88c0d0"));
+
+        let missing = toml::toml! {
+            palette = "missing"
+        };
+        assert!(load_palette(missing, &[]).is_empty());
+    }
+
+    #[test]
+    fn osc_colors() {
+        assert_eq!(
+            parse_osc_color("\x1b]11;rgb:ffff/ffff/ffff\x07"),
+            Some((1.0, 1.0, 1.0))
+        );
+        assert_eq!(
+            parse_osc_color("\x1b]11;rgb:00/80/ff\x1b\\"),
+            Some((0.0, 128.0 / 255.0, 1.0))
+        );
+        assert_eq!(parse_osc_color("\x1b]11;rgb:00/80\x07"), None);
+        assert_eq!(parse_osc_color(""), None);
+
+        assert_eq!(Background::of_color((1.0, 1.0, 0.9)), Background::Light);
+        assert_eq!(Background::of_color((0.1, 0.1, 0.2)), Background::Dark);
+    }
+}
@@ -1,86 +1,416 @@
-use ansi_term::ANSIStrings;
+use ansi_term::{ANSIStrings, Color, Style};
 use clap::ArgMatches;
 use rayon::prelude::*;
+use serde_json::json;
 use std::collections::BTreeSet;
 use std::fmt::{self, Debug, Write as FmtWrite};
 use std::io::{self, Write};
-use std::time::Duration;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthChar;
 
+use crate::config::RootModuleConfig;
+use crate::configs::tmux::TmuxConfig;
 use crate::configs::PROMPT_ORDER;
 use crate::context::{Context, Shell};
 use crate::formatter::{StringFormatter, VariableHolder};
 use crate::module::Module;
 use crate::module::ALL_MODULES;
+use crate::module_cache::ModuleCache;
 use crate::modules;
+use crate::palette;
 use crate::segment::Segment;
+use crate::trace;
 
 pub fn prompt(args: ArgMatches) {
+    let trace_file = args.value_of("trace_file").map(PathBuf::from);
+    if trace_file.is_some() {
+        trace::enable();
+    }
+
+    let output_format = args.value_of("output_format").unwrap_or("ansi").to_owned();
+    let transient = args.is_present("transient");
+    let right = args.is_present("right");
+    let with_right = args.is_present("with_right");
     let context = Context::new(args);
     let stdout = io::stdout();
     let mut handle = stdout.lock();
-    write!(handle, "{}", get_prompt(context)).unwrap();
+    match output_format.as_str() {
+        "json" => writeln!(handle, "{}", get_prompt_json(&context)).unwrap(),
+        "html" => writeln!(handle, "{}", get_prompt_html(&context)).unwrap(),
+        _ if transient => write!(handle, "{}", get_transient_prompt(context)).unwrap(),
+        _ if right => write!(handle, "{}", get_right_prompt(context)).unwrap(),
+        _ if with_right => write!(handle, "{}", get_prompt_with_right(context)).unwrap(),
+        _ => write!(handle, "{}", get_prompt(context)).unwrap(),
+    }
+
+    if let Some(trace_file) = trace_file {
+        if let Err(error) = trace::write_to(&trace_file) {
+            log::error!("Unable to write the trace to {:?}: {}", trace_file, error);
+        }
+    }
 }
 
 pub fn get_prompt(context: Context) -> String {
     let config = context.config.get_root_config();
+    render_prompt(&context, config.format, config.add_newline, None)
+}
+
+/// The prompt with the prompt of `right_format` drawn on its last line, for bash, which has
+/// no right prompt of its own
+pub fn get_prompt_with_right(context: Context) -> String {
+    let config = context.config.get_root_config();
+    render_prompt(
+        &context,
+        config.format,
+        config.add_newline,
+        Some(config.right_format),
+    )
+}
+
+/// The prompt that replaces the prompt of a command once it's run, from `transient_format`
+pub fn get_transient_prompt(context: Context) -> String {
+    let config = context.config.get_root_config();
+    render_prompt(&context, config.transient_format, false, None)
+}
+
+/// The prompt of `right_format`, for shells that draw it on the right of the terminal
+pub fn get_right_prompt(context: Context) -> String {
+    let config = context.config.get_root_config();
+    render_prompt(&context, config.right_format, false, None)
+}
+
+fn render_prompt(
+    context: &Context,
+    format: &str,
+    add_newline: bool,
+    right_format: Option<&str>,
+) -> String {
     let mut buf = String::new();
 
+    let use_color = context.use_color();
+
     // A workaround for a fish bug (see #739,#279). Applying it to all shells
     // breaks things (see #808,#824,#834). Should only be printed in fish.
-    if let Shell::Fish = context.shell {
+    if let (Shell::Fish, true) = (context.shell, use_color) {
         buf.push_str("\x1b[J"); // An ASCII control code to clear screen
     }
 
-    let formatter = if let Ok(formatter) = StringFormatter::new(config.format) {
-        formatter
-    } else {
-        log::error!("Error parsing `format`");
-        buf.push('>');
-        return buf;
-    };
-    let modules = formatter.get_variables();
-    let formatter = formatter.map_variables_to_segments(|module| {
-        // Make $all display all modules
-        if module == "all" {
-            Some(Ok(PROMPT_ORDER
-                .par_iter()
-                .flat_map(|module| {
-                    handle_module(module, &context, &modules)
-                        .into_iter()
-                        .flat_map(|module| module.segments)
-                        .collect::<Vec<Segment>>()
-                })
-                .collect::<Vec<_>>()))
-        } else if context.is_module_disabled_in_config(&module) {
-            None
-        } else {
-            // Get segments from module
-            Some(Ok(handle_module(module, &context, &modules)
-                .into_iter()
-                .flat_map(|module| module.segments)
-                .collect::<Vec<Segment>>()))
+    let segments = match format_prompt_modules(format, context) {
+        Some(segments) if !use_color => strip_styles(segments),
+        Some(segments) => segments,
+        None => {
+            log::error!("Error parsing the format of the prompt: {:?}", format);
+            buf.push('>');
+            return buf;
         }
-    });
+    };
 
     // Creates a root module and prints it.
     let mut root_module = Module::new("Starship Root", "The root module", None);
-    root_module.set_segments(
-        formatter
-            .parse(None)
-            .expect("Unexpected error returned in root format variables"),
-    );
+    root_module.set_segments(segments);
 
     let module_strings = root_module.ansi_strings_for_shell(context.shell);
-    if config.add_newline {
+    if add_newline {
         writeln!(buf).unwrap();
     }
     write!(buf, "{}", ANSIStrings(&module_strings)).unwrap();
 
+    // Bash has no right prompt, so it's drawn by moving the cursor at the start of the last line
+    let right_format = right_format.filter(|format| !format.is_empty());
+    if let (Shell::Bash, Some(right_format)) = (context.shell, right_format) {
+        let left_text: String = root_module
+            .segments
+            .iter()
+            .map(|s| s.value.as_str())
+            .collect();
+        let left_width = better_width(left_text.rsplit('\n').next().unwrap_or_default());
+        if let Some(right_prompt) = bash_right_prompt(context, right_format, left_width) {
+            let last_line = buf.rfind('\n').map_or(0, |newline| newline + 1);
+            buf.insert_str(last_line, &right_prompt);
+        }
+    }
+
     buf
 }
 
+/// The right prompt for bash, which saves the cursor, draws the prompt against the right edge
+/// of the terminal and restores the cursor, all hidden from readline with `\[` and `\]`
+///
+/// It's left out when it doesn't fit on the line with the prompt, and on dumb terminals.
+fn bash_right_prompt(context: &Context, format: &str, left_width: usize) -> Option<String> {
+    if context.get_env("TERM").as_deref() == Some("dumb") {
+        return None;
+    }
+    let segments = match format_prompt_modules(format, context)? {
+        segments if !context.use_color() => strip_styles(segments),
+        segments => segments,
+    };
+    let text: String = segments.iter().map(|s| s.value.as_str()).collect();
+    if text.contains('\n') {
+        log::warn!("`right_format` can't span several lines in bash");
+        return None;
+    }
+    let width = better_width(&text);
+    if width == 0 || left_width + width >= context.width {
+        return None;
+    }
+
+    let column = context.width - width + 1;
+    let right_prompt = ANSIStrings(
+        &segments
+            .iter()
+            .map(Segment::ansi_string)
+            .collect::<Vec<_>>(),
+    )
+    .to_string();
+    Some(format!("\\[\x1b7\x1b[{}G{}\x1b8\\]", column, right_prompt))
+}
+
+/// The modules of the prompt as JSON, so that other programs can render them as they please
+///
+/// Each module has its name, how long it took in milliseconds, and its segments with their
+/// text and style. Colors are named as in the config, e.g. `"red"`, `"208"` or `"#ff8800"`.
+pub fn get_prompt_json(context: &Context) -> String {
+    let modules: Vec<serde_json::Value> = compute_modules(context)
+        .iter()
+        .filter(|module| !module.is_empty())
+        .map(|module| {
+            json!({
+                "name": module.get_name(),
+                "duration_ms": module.duration.as_millis() as u64,
+                "segments": segments_json(&module.segments),
+            })
+        })
+        .collect();
+
+    json!({ "modules": modules }).to_string()
+}
+
+/// The shown modules as JSON, with their value, description, duration and what they detected
+pub fn get_explain_json(context: &Context) -> String {
+    let modules: Vec<serde_json::Value> = compute_modules(context)
+        .iter()
+        .filter(|module| module.get_name() != "line_break" && !module.is_empty())
+        .map(|module| {
+            json!({
+                "name": module.get_name(),
+                "description": module.get_description(),
+                "value": module.get_segments().join(""),
+                "duration_ms": module.duration.as_millis() as u64,
+                "detected_by": module.detected_by,
+                "segments": segments_json(&module.segments),
+            })
+        })
+        .collect();
+
+    json!({ "modules": modules }).to_string()
+}
+
+fn segments_json(segments: &[Segment]) -> Vec<serde_json::Value> {
+    segments
+        .iter()
+        .map(|segment| {
+            let style = segment.style.unwrap_or_default();
+            json!({
+                "text": segment.value,
+                "fg": style.foreground.map(color_name),
+                "bg": style.background.map(color_name),
+                "bold": style.is_bold,
+                "italic": style.is_italic,
+                "underline": style.is_underline,
+                "dimmed": style.is_dimmed,
+            })
+        })
+        .collect()
+}
+
+/// The prompt as HTML with inline styles, to preview it on a web page
+///
+/// Named and 256 colors are shown with the xterm palette, since the terminal's is unknown.
+pub fn get_prompt_html(context: &Context) -> String {
+    let config = context.config.get_root_config();
+    let segments = match format_modules(config.format, context) {
+        Some(segments) => segments,
+        None => {
+            log::error!("Error parsing `format`");
+            Vec::new()
+        }
+    };
+
+    let mut html = String::from("<pre class=\"starship-prompt\">");
+    for segment in &segments {
+        let text = segment
+            .value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        let style = segment.style.unwrap_or_default();
+        let mut css = Vec::new();
+        css.extend(
+            style
+                .foreground
+                .map(|fg| format!("color: {}", css_color(fg))),
+        );
+        css.extend(
+            style
+                .background
+                .map(|bg| format!("background-color: {}", css_color(bg))),
+        );
+        let flags = [
+            (style.is_bold, "font-weight: bold"),
+            (style.is_dimmed, "opacity: 0.5"),
+            (style.is_italic, "font-style: italic"),
+            (style.is_underline, "text-decoration: underline"),
+            (style.is_strikethrough, "text-decoration: line-through"),
+            (style.is_hidden, "visibility: hidden"),
+        ];
+        css.extend(
+            flags
+                .iter()
+                .filter(|(is_set, _)| *is_set)
+                .map(|(_, css)| css.to_string()),
+        );
+
+        if css.is_empty() {
+            html.push_str(&text);
+        } else {
+            write!(html, "<span style=\"{}\">{}</span>", css.join("; "), text).unwrap();
+        }
+    }
+    html.push_str("</pre>");
+    html
+}
+
+/// Converts a color to CSS, using the xterm palette for named and 256 colors
+fn css_color(color: Color) -> String {
+    const XTERM_COLORS: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+
+    let (r, g, b) = match color {
+        Color::Black => XTERM_COLORS[0],
+        Color::Red => XTERM_COLORS[1],
+        Color::Green => XTERM_COLORS[2],
+        Color::Yellow => XTERM_COLORS[3],
+        Color::Blue => XTERM_COLORS[4],
+        Color::Purple => XTERM_COLORS[5],
+        Color::Cyan => XTERM_COLORS[6],
+        Color::White => XTERM_COLORS[7],
+        Color::Fixed(number) if number < 16 => XTERM_COLORS[number as usize],
+        // A 6x6x6 cube of colors
+        Color::Fixed(number) if number < 232 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let number = number - 16;
+            (level(number / 36), level(number / 6 % 6), level(number % 6))
+        }
+        // A ramp of grays
+        Color::Fixed(number) => {
+            let gray = 8 + (number - 232) * 10;
+            (gray, gray, gray)
+        }
+        Color::RGB(r, g, b) => (r, g, b),
+    };
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Prints the modules in the `[tmux]` format for the tmux status line
+pub fn tmux(args: ArgMatches) {
+    let format = args.value_of("format").map(str::to_owned);
+    let context = Context::new(args);
+    let config = TmuxConfig::try_load(context.config.get_config(&["tmux"]));
+    println!(
+        "{}",
+        get_tmux_status(format.as_deref().unwrap_or(config.format), &context)
+    );
+}
+
+/// Renders modules with tmux's `#[fg=...]` styles instead of ANSI escapes
+pub fn get_tmux_status(format: &str, context: &Context) -> String {
+    let segments = match format_modules(format, context) {
+        Some(segments) => segments,
+        None => {
+            log::error!("Error parsing `format` in [tmux]");
+            return String::new();
+        }
+    };
+
+    let mut status: String = segments
+        .iter()
+        .map(|segment| {
+            // `#` starts a tmux format
+            let text = segment.value.replace('#', "##");
+            format!("#[{}]{}", tmux_style(segment.style), text)
+        })
+        .collect();
+    status.push_str("#[default]");
+    status
+}
+
+/// Converts a style to tmux, starting from the default style of the status line
+fn tmux_style(style: Option<Style>) -> String {
+    let color = |color| match color {
+        Color::Fixed(number) => format!("colour{}", number),
+        Color::Purple => "magenta".to_string(),
+        color => color_name(color),
+    };
+
+    let style = style.unwrap_or_default();
+    let mut attributes = vec!["default".to_string()];
+    attributes.extend(style.foreground.map(|fg| format!("fg={}", color(fg))));
+    attributes.extend(style.background.map(|bg| format!("bg={}", color(bg))));
+    let flags = [
+        (style.is_bold, "bold"),
+        (style.is_dimmed, "dim"),
+        (style.is_italic, "italics"),
+        (style.is_underline, "underscore"),
+        (style.is_blink, "blink"),
+        (style.is_reverse, "reverse"),
+        (style.is_hidden, "hidden"),
+        (style.is_strikethrough, "strikethrough"),
+    ];
+    attributes.extend(
+        flags
+            .iter()
+            .filter(|(is_set, _)| *is_set)
+            .map(|(_, flag)| flag.to_string()),
+    );
+    attributes.join(",")
+}
+
+/// The name of a color in style strings, e.g. `red`, `208` or `#ff8800`
+pub(crate) fn color_name(color: Color) -> String {
+    match color {
+        Color::Black => "black".to_string(),
+        Color::Red => "red".to_string(),
+        Color::Green => "green".to_string(),
+        Color::Yellow => "yellow".to_string(),
+        Color::Blue => "blue".to_string(),
+        Color::Purple => "purple".to_string(),
+        Color::Cyan => "cyan".to_string(),
+        Color::White => "white".to_string(),
+        Color::Fixed(number) => number.to_string(),
+        Color::RGB(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+    }
+}
+
 pub fn module(module_name: &str, args: ArgMatches) {
     let context = Context::new(args);
     let module = get_module(module_name, context).unwrap_or_default();
@@ -88,7 +418,19 @@ pub fn module(module_name: &str, args: ArgMatches) {
 }
 
 pub fn get_module(module_name: &str, context: Context) -> Option<String> {
-    modules::handle(module_name, &context).map(|m| m.to_string())
+    let mut module = modules::handle(module_name, &context)?;
+    if !context.use_color() {
+        module.segments = strip_styles(module.segments);
+    }
+    Some(module.to_string())
+}
+
+/// Removes the styles of segments, to print them without ANSI escapes
+fn strip_styles(segments: Vec<Segment>) -> Vec<Segment> {
+    segments
+        .into_iter()
+        .map(|segment| Segment::new(None, segment.value))
+        .collect()
 }
 
 pub fn timings(args: ArgMatches) {
@@ -138,7 +480,12 @@ pub fn timings(args: ArgMatches) {
 }
 
 pub fn explain(args: ArgMatches) {
+    let output_format = args.value_of("output_format").unwrap_or("text").to_owned();
     let context = Context::new(args);
+    if output_format == "json" {
+        println!("{}", get_explain_json(&context));
+        return;
+    }
 
     struct ModuleInfo {
         value: String,
@@ -232,7 +579,161 @@ pub fn explain(args: ArgMatches) {
     }
 }
 
-fn compute_modules<'a>(context: &'a Context) -> Vec<Module<'a>> {
+/// Computes the segments of a format made of modules, like the root `format`
+fn format_modules(format: &str, context: &Context) -> Option<Vec<Segment>> {
+    format_segments(format, context, false)
+}
+
+/// The segments of a prompt printed by the shell, with the powerline separators of the config
+/// between its modules
+fn format_prompt_modules(format: &str, context: &Context) -> Option<Vec<Segment>> {
+    let separator = context.config.get_root_config().powerline_separator;
+    if separator.is_empty() {
+        return format_modules(format, context);
+    }
+    let segments = format_segments(format, context, true)?;
+    Some(add_powerline_separators(segments, separator))
+}
+
+/// Marks where a module starts and ends among the segments of the prompt. A prompt can't
+/// contain NUL, as shells can't print it.
+const MODULE_BOUNDARY: &str = "\0";
+
+fn format_segments(format: &str, context: &Context, mark_modules: bool) -> Option<Vec<Segment>> {
+    let formatter = StringFormatter::new(format).ok()?;
+    let _span = trace::span("prompt", || "format");
+    let _palette = palette::use_palette(context.palette());
+    let modules = formatter.get_variables();
+
+    // In the async mode of zsh, the placeholder prompt reuses the segments of the modules that
+    // were slow at the last prompt, which the final prompt caches
+    let async_phase = context.properties.get("async").map(String::as_str);
+    let cache_path = async_phase.and_then(|_| ModuleCache::path(context));
+    let cache = Mutex::new(
+        cache_path
+            .as_deref()
+            .map(|path| ModuleCache::load(path, &context.current_dir))
+            .unwrap_or_default(),
+    );
+    let lock_cache = || {
+        cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    };
+    let module_segments = |module: &str| -> Vec<Segment> {
+        if async_phase == Some("placeholder") {
+            if let Some(segments) = lock_cache().get_slow(module) {
+                return segments;
+            }
+        }
+        let start = Instant::now();
+        let segments = handle_module(module, context, &modules)
+            .into_iter()
+            .flat_map(|module| module.segments)
+            .collect::<Vec<Segment>>();
+        if async_phase == Some("final") {
+            lock_cache().insert(module, start.elapsed(), &segments);
+        }
+        segments
+    };
+    let marked_segments = |module: &str| -> Vec<Segment> {
+        let segments = module_segments(module);
+        if !mark_modules {
+            return segments;
+        }
+        let boundary = || Segment::new(None, MODULE_BOUNDARY);
+        std::iter::once(boundary())
+            .chain(segments)
+            .chain(std::iter::once(boundary()))
+            .collect()
+    };
+
+    let formatter = formatter.map_variables_to_segments(|module| {
+        // Make $all display all modules
+        if module == "all" {
+            Some(Ok(PROMPT_ORDER
+                .par_iter()
+                .flat_map(|module| marked_segments(module))
+                .collect::<Vec<_>>()))
+        } else if context.is_module_disabled_in_config(&module) {
+            None
+        } else {
+            // Get segments from module
+            Some(Ok(marked_segments(module)))
+        }
+    });
+
+    let segments = formatter
+        .parse(None)
+        .expect("Unexpected error returned in root format variables");
+    if let (Some("final"), Some(cache_path)) = (async_phase, cache_path) {
+        lock_cache().save(&cache_path);
+    }
+    Some(segments)
+}
+
+/// Inserts a powerline separator between the modules of the prompt, and after the last one
+///
+/// The segments are split into blocks at the boundaries of the modules, so that the text of
+/// the format between two modules is a block of its own. A separator is drawn between two
+/// blocks when either of them has a background: in the background that ends, on the background
+/// that starts, so that the blocks seem to point into each other. When a block with a
+/// background follows one without, the separator is reversed, so that it's cut out of the
+/// background that starts.
+fn add_powerline_separators(segments: Vec<Segment>, separator: &str) -> Vec<Segment> {
+    let background = |segment: &Segment| segment.style.and_then(|style| style.background);
+    let separator_segment = |previous: &Segment, next: Option<&Segment>| {
+        let style = match (background(previous), next.and_then(background)) {
+            (Some(ends), Some(starts)) if ends == starts => Style {
+                background: Some(starts),
+                ..previous.style.unwrap_or_default()
+            },
+            (ends @ Some(_), starts) => Style {
+                foreground: ends,
+                background: starts,
+                ..Style::default()
+            },
+            (None, starts) => Style {
+                foreground: starts,
+                is_reverse: true,
+                ..Style::default()
+            },
+        };
+        Segment::new(Some(style), separator)
+    };
+
+    let mut with_separators = Vec::with_capacity(segments.len());
+    // The last drawn segment of the previous block, and whether a block has started since
+    let mut previous: Option<Segment> = None;
+    let mut at_boundary = false;
+    for segment in segments {
+        if segment.value == MODULE_BOUNDARY {
+            at_boundary = true;
+            continue;
+        }
+        // Empty segments aren't drawn, so they don't start a block
+        if segment.value.is_empty() {
+            with_separators.push(segment);
+            continue;
+        }
+        if let (true, Some(last)) = (at_boundary, &previous) {
+            if background(last).is_some() || background(&segment).is_some() {
+                with_separators.push(separator_segment(last, Some(&segment)));
+            }
+        }
+        at_boundary = false;
+        previous = Some(segment.clone());
+        with_separators.push(segment);
+    }
+    if let Some(last) = previous.filter(|last| background(last).is_some()) {
+        with_separators.push(separator_segment(&last, None));
+    }
+    with_separators
+}
+
+/// Computes the modules of the root `format` in the order of the format, each with its segments
+/// and duration
+pub fn compute_modules<'a>(context: &'a Context) -> Vec<Module<'a>> {
     let mut prompt_order: Vec<Module<'a>> = Vec::new();
 
     let config = context.config.get_root_config();
@@ -244,7 +745,7 @@ fn compute_modules<'a>(context: &'a Context) -> Vec<Module<'a>> {
     };
     let modules = formatter.get_variables();
 
-    for module in &modules {
+    for module in &formatter.get_variables_in_order() {
         // Manually add all modules if `$all` is encountered
         if module == "all" {
             for module in PROMPT_ORDER.iter() {
@@ -273,6 +774,7 @@ fn handle_module<'a>(
         }
     }
 
+    let _palette = palette::use_palette(context.palette());
     let mut modules: Vec<Option<Module>> = Vec::new();
 
     if ALL_MODULES.contains(&module) {
@@ -312,6 +814,16 @@ fn handle_module<'a>(
                     ),
             },
         }
+    } else if let Some(name) = module.strip_prefix("plugin.") {
+        // Write out a plugin module if it's registered in the config
+        if context.config.get_plugin_config(name).is_some() {
+            modules.push(modules::plugin::module(name, context));
+        } else {
+            log::debug!(
+                "top level format contains plugin module \"{}\", but no configuration was provided.",
+                name,
+            );
+        }
     } else {
         log::debug!(
             "Expected top level format to contain value from {:?}. Instead received {}",
@@ -345,7 +857,7 @@ fn should_add_implicit_custom_module(
         .unwrap_or(false)
 }
 
-fn better_width(s: &str) -> usize {
+pub fn better_width(s: &str) -> usize {
     s.graphemes(true).map(grapheme_width).sum()
 }
 
@@ -370,3 +882,271 @@ fn test_grapheme_aware_better_width() {
     assert_eq!(1, better_width("Ü"));
     assert_eq!(11, better_width("normal text"));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use std::collections::HashMap;
+    use std::io;
+
+    #[test]
+    fn prompt_as_json() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir.path());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                format = "$character"
+                [character]
+                format = "$symbol"
+                success_symbol = "[>](bold #ff8800 bg:208)"
+            }),
+        };
+
+        let output: serde_json::Value = serde_json::from_str(&get_prompt_json(&context))?;
+        assert_eq!(output["modules"][0]["name"], "character");
+        assert_eq!(
+            output["modules"][0]["segments"][0],
+            json!({
+                "text": ">",
+                "fg": "#ff8800",
+                "bg": "208",
+                "bold": true,
+                "italic": false,
+                "underline": false,
+                "dimmed": false,
+            })
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn modules_from_explicit_inputs() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config = StarshipConfig {
+            config: Some(toml::toml! {
+                format = "$cmd_duration$character"
+                [cmd_duration]
+                format = "$duration"
+                min_time = 0
+                [character]
+                format = "$symbol"
+                vicmd_symbol = "[N](green)"
+            }),
+        };
+        let mut properties = HashMap::new();
+        properties.insert("cmd_duration", "3000".to_string());
+        properties.insert("keymap", "vicmd".to_string());
+        let context = Context::new_with_properties(config, dir.path(), Shell::Zsh, properties);
+
+        let modules = compute_modules(&context);
+        let modules: Vec<(&str, Vec<&str>)> = modules
+            .iter()
+            .map(|module| (module.get_name().as_str(), module.get_segments()))
+            .collect();
+        assert_eq!(
+            modules,
+            vec![("cmd_duration", vec!["3s"]), ("character", vec!["N"])]
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn powerline_separators_carry_backgrounds() {
+        let blue = Color::Blue.normal().on(Color::Blue);
+        let boundary = || Segment::new(None, MODULE_BOUNDARY);
+        let segments = vec![
+            boundary(),
+            Segment::new(Some(Color::White.on(Color::Blue)), "~"),
+            Segment::new(Some(blue), "/src"),
+            boundary(),
+            boundary(),
+            Segment::new(Some(Color::Black.on(Color::Green)), "main"),
+            boundary(),
+            boundary(),
+            Segment::new(None, ""),
+            boundary(),
+            Segment::new(None, " "),
+            boundary(),
+            Segment::new(Some(Color::Red.on(Color::Yellow)), "!"),
+            boundary(),
+            boundary(),
+            Segment::new(Some(Color::Blue.on(Color::Yellow)), "?"),
+            boundary(),
+        ];
+
+        let segments = add_powerline_separators(segments, ">");
+        let rendered: Vec<(Option<Style>, &str)> = segments
+            .iter()
+            .map(|segment| (segment.style, segment.value.as_str()))
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![
+                (Some(Color::White.on(Color::Blue)), "~"),
+                (Some(blue), "/src"),
+                (Some(Color::Blue.on(Color::Green)), ">"),
+                (Some(Color::Black.on(Color::Green)), "main"),
+                (None, ""),
+                (Some(Color::Green.normal()), ">"),
+                (None, " "),
+                (Some(Color::Yellow.reverse()), ">"),
+                (Some(Color::Red.on(Color::Yellow)), "!"),
+                (Some(Color::Red.on(Color::Yellow)), ">"),
+                (Some(Color::Blue.on(Color::Yellow)), "?"),
+                (Some(Color::Yellow.normal()), ">"),
+            ]
+        );
+    }
+
+    #[test]
+    fn powerline_separators_only_in_shell_prompt() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir.path());
+        context.shell = Shell::Unknown;
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                format = "$character"
+                add_newline = false
+                powerline_separator = ">"
+                [character]
+                format = "$symbol"
+                success_symbol = "[#](green bg:red)"
+            }),
+        };
+
+        assert_eq!(
+            get_tmux_status("$character", &context),
+            "#[default,fg=green,bg=red]###[default]"
+        );
+        assert_eq!(
+            get_prompt(context),
+            format!(
+                "{}{}",
+                Color::Green.on(Color::Red).paint("#"),
+                Color::Red.paint(">")
+            )
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn tmux_styles() {
+        assert_eq!(tmux_style(None), "default");
+        assert_eq!(
+            tmux_style(Some(Color::Fixed(208).on(Color::RGB(0, 136, 255)).bold())),
+            "default,fg=colour208,bg=#0088ff,bold"
+        );
+        assert_eq!(
+            tmux_style(Some(Color::Red.italic().underline())),
+            "default,fg=red,italics,underscore"
+        );
+        assert_eq!(
+            tmux_style(Some(Color::Purple.on(Color::Purple))),
+            "default,fg=magenta,bg=magenta"
+        );
+    }
+
+    #[test]
+    fn tmux_status_escapes_text() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir.path());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [character]
+                format = "$symbol"
+                success_symbol = "[#](green)"
+            }),
+        };
+
+        assert_eq!(
+            get_tmux_status("$character", &context),
+            "#[default,fg=green]###[default]"
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn css_colors() {
+        assert_eq!(css_color(Color::Red), "#cd0000");
+        assert_eq!(css_color(Color::Fixed(9)), "#ff0000");
+        assert_eq!(css_color(Color::Fixed(208)), "#ff8700");
+        assert_eq!(css_color(Color::Fixed(244)), "#808080");
+        assert_eq!(css_color(Color::RGB(0, 136, 255)), "#0088ff");
+    }
+
+    #[test]
+    fn transient_prompt() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir.path());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                format = "$directory$character"
+                [character]
+                format = "$symbol"
+                success_symbol = ">"
+            }),
+        };
+        context.shell = Shell::Unknown;
+
+        assert_eq!(get_transient_prompt(context), ">");
+        dir.close()
+    }
+
+    #[test]
+    fn right_prompt_in_bash() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let bash_prompt = |width, with_right| {
+            let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir.path());
+            context.config = StarshipConfig {
+                config: Some(toml::toml! {
+                    format = "$line_break$character"
+                    right_format = "$character$character"
+                    add_newline = false
+                    [character]
+                    format = "$symbol"
+                    success_symbol = "[>](red)"
+                }),
+            };
+            context.shell = Shell::Bash;
+            context.env.insert("TERM", "xterm".to_string());
+            context.width = width;
+            if with_right {
+                get_prompt_with_right(context)
+            } else {
+                get_prompt(context)
+            }
+        };
+        let left = "\\[\x1b[31m\\]>\\[\x1b[0m\\]";
+
+        assert_eq!(
+            bash_prompt(20, true),
+            format!("\n\\[\x1b7\x1b[19G\x1b[31m>>\x1b[0m\x1b8\\]{}", left)
+        );
+        // The right prompt is left out when it doesn't fit
+        assert_eq!(bash_prompt(3, true), format!("\n{}", left));
+        // It's only drawn when the init script asks for it
+        assert_eq!(bash_prompt(20, false), format!("\n{}", left));
+        dir.close()
+    }
+
+    #[test]
+    fn prompt_as_html() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = Context::new_with_dir(clap::ArgMatches::default(), dir.path());
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                format = "<$character"
+                [character]
+                format = "$symbol"
+                success_symbol = "[>](bold green)"
+            }),
+        };
+
+        assert_eq!(
+            get_prompt_html(&context),
+            "<pre class=\"starship-prompt\">&lt;<span style=\"color: #00cd00; font-weight: bold\">&gt;</span></pre>"
+        );
+        dir.close()
+    }
+}
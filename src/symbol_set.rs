@@ -0,0 +1,323 @@
+use toml::Value;
+
+/// The symbols of the built-in modules in each symbol set, as
+/// `(module, option, [nerd-font, unicode, ascii])`
+///
+/// Options that are tables of symbols, like `nodejs.package_manager_symbols`, have an entry for
+/// each of their keys, as `option.key`.
+///
+/// The nerd-font set uses the glyphs of Nerd Fonts where one exists, the unicode set only uses
+/// characters found in common fonts, and the ascii set works on any terminal, including the
+/// Linux console.
+const SYMBOLS: &[(&str, &str, [&str; 3])] = &[
+    ("asdf", "symbol", ["\u{f487} ", "🧰 ", "asdf "]),
+    ("aws", "symbol", ["\u{f270} ", "☁️  ", "aws "]),
+    ("battery", "full_symbol", ["\u{f578}", "🔋", "="]),
+    ("battery", "charging_symbol", ["\u{f583}", "⚡", "+"]),
+    ("battery", "discharging_symbol", ["\u{f582}", "🔋", "-"]),
+    ("battery", "unknown_symbol", ["\u{f57c}", "🔋", "?"]),
+    ("battery", "empty_symbol", ["\u{f58d}", "🪫", "!"]),
+    (
+        "character",
+        "success_symbol",
+        ["[❯](bold green)", "[❯](bold green)", "[>](bold green)"],
+    ),
+    (
+        "character",
+        "error_symbol",
+        ["[❯](bold red)", "[❯](bold red)", "[>](bold red)"],
+    ),
+    (
+        "character",
+        "vicmd_symbol",
+        ["[❮](bold green)", "[❮](bold green)", "[<](bold green)"],
+    ),
+    (
+        "character",
+        "vimcmd_visual_symbol",
+        ["[❮](bold yellow)", "[❮](bold yellow)", "[<](bold yellow)"],
+    ),
+    (
+        "character",
+        "vimcmd_replace_symbol",
+        ["[❮](bold purple)", "[❮](bold purple)", "[<](bold purple)"],
+    ),
+    (
+        "character",
+        "vimcmd_replace_one_symbol",
+        ["[❮](bold purple)", "[❮](bold purple)", "[<](bold purple)"],
+    ),
+    ("cmake", "symbol", ["\u{fa36} ", "△ ", "cmake "]),
+    ("conda", "symbol", ["🅒 ", "🅒 ", "conda "]),
+    ("crystal", "symbol", ["\u{e62f} ", "🔮 ", "cr "]),
+    ("dart", "symbol", ["\u{e798} ", "🎯 ", "dart "]),
+    ("directory", "read_only", [" \u{f023}", "🔒", " ro"]),
+    ("docker_context", "symbol", ["\u{e7b0} ", "🐳 ", "docker "]),
+    (
+        "docker_context",
+        "remote_symbol",
+        ["\u{e7b0} ", "🐳 ", "docker "],
+    ),
+    ("dotnet", "symbol", ["\u{e77f} ", "•NET ", ".NET "]),
+    ("elixir", "symbol", ["\u{e62d} ", "💧 ", "exs "]),
+    ("elm", "symbol", ["\u{e62c} ", "🌳 ", "elm "]),
+    ("erlang", "symbol", ["\u{e7b1} ", "Ⓔ ", "erl "]),
+    ("gcloud", "symbol", ["\u{f1a0} ", "☁️ ", "gcp "]),
+    ("git_branch", "symbol", ["\u{e0a0} ", "⎇ ", "git "]),
+    ("git_branch", "truncation_symbol", ["…", "…", "..."]),
+    ("git_commit", "tag_symbol", ["\u{f02b} ", "🏷  ", "tag "]),
+    ("git_status", "ahead", ["⇡", "⇡", ">"]),
+    ("git_status", "behind", ["⇣", "⇣", "<"]),
+    ("git_status", "diverged", ["⇕", "⇕", "<>"]),
+    ("git_status", "deleted", ["✘", "✘", "x"]),
+    ("git_status", "renamed", ["»", "»", "r"]),
+    ("golang", "symbol", ["\u{e724} ", "🐹 ", "go "]),
+    ("helm", "symbol", ["\u{2388} ", "⎈ ", "helm "]),
+    ("hg_branch", "symbol", ["\u{e0a0} ", "⎇ ", "hg "]),
+    ("hg_branch", "truncation_symbol", ["…", "…", "..."]),
+    ("java", "symbol", ["\u{e738} ", "☕ ", "java "]),
+    (
+        "java",
+        "vendor_symbols.graalvm",
+        ["graalvm", "graalvm", "graalvm"],
+    ),
+    (
+        "java",
+        "vendor_symbols.temurin",
+        ["temurin", "temurin", "temurin"],
+    ),
+    (
+        "java",
+        "vendor_symbols.adoptopenjdk",
+        ["adoptopenjdk", "adoptopenjdk", "adoptopenjdk"],
+    ),
+    (
+        "java",
+        "vendor_symbols.corretto",
+        ["corretto", "corretto", "corretto"],
+    ),
+    ("java", "vendor_symbols.zulu", ["zulu", "zulu", "zulu"]),
+    (
+        "java",
+        "vendor_symbols.openj9",
+        ["openj9", "openj9", "openj9"],
+    ),
+    (
+        "java",
+        "vendor_symbols.sapmachine",
+        ["sapmachine", "sapmachine", "sapmachine"],
+    ),
+    (
+        "java",
+        "vendor_symbols.liberica",
+        ["liberica", "liberica", "liberica"],
+    ),
+    (
+        "java",
+        "vendor_symbols.microsoft",
+        ["microsoft", "microsoft", "microsoft"],
+    ),
+    (
+        "java",
+        "vendor_symbols.oracle",
+        ["oracle", "oracle", "oracle"],
+    ),
+    ("jobs", "symbol", ["\u{f013}", "✦", "*"]),
+    ("jobs", "stopped_symbol", ["\u{f013}", "✦", "*"]),
+    ("julia", "symbol", ["\u{e624} ", "ஃ ", "jl "]),
+    ("kotlin", "symbol", ["\u{e634} ", "🅺 ", "kt "]),
+    ("kubernetes", "symbol", ["\u{2638} ", "☸ ", "k8s "]),
+    ("lua", "symbol", ["\u{e620} ", "🌙 ", "lua "]),
+    ("memory_usage", "symbol", ["\u{f2db} ", "🐏 ", "mem "]),
+    ("nim", "symbol", ["👑 ", "👑 ", "nim "]),
+    ("nix_shell", "symbol", ["\u{f313} ", "❄️  ", "nix "]),
+    ("nodejs", "symbol", ["\u{e718} ", "⬢ ", "node "]),
+    (
+        "nodejs",
+        "package_manager_symbols.npm",
+        ["\u{e71e}", "npm", "npm"],
+    ),
+    (
+        "nodejs",
+        "package_manager_symbols.yarn",
+        ["\u{e6a7}", "yarn", "yarn"],
+    ),
+    (
+        "nodejs",
+        "package_manager_symbols.pnpm",
+        ["\u{e865}", "pnpm", "pnpm"],
+    ),
+    (
+        "nodejs",
+        "package_manager_symbols.bun",
+        ["bun", "bun", "bun"],
+    ),
+    ("ocaml", "symbol", ["\u{e67a} ", "🐫 ", "ml "]),
+    ("openstack", "symbol", ["\u{f0c2} ", "☁️  ", "os "]),
+    ("package", "symbol", ["\u{f487} ", "📦 ", "pkg "]),
+    ("perl", "symbol", ["\u{e769} ", "🐪 ", "pl "]),
+    ("php", "symbol", ["\u{e73d} ", "🐘 ", "php "]),
+    ("podman", "symbol", ["\u{f1b3} ", "🦭 ", "podman "]),
+    ("purescript", "symbol", ["<=> ", "<=> ", "purs "]),
+    ("python", "symbol", ["\u{e73c} ", "🐍 ", "py "]),
+    ("ruby", "symbol", ["\u{e739} ", "💎 ", "rb "]),
+    ("rust", "symbol", ["\u{e7a8} ", "🦀 ", "rs "]),
+    ("shlvl", "symbol", ["\u{f120} ", "↕️  ", "shlvl "]),
+    ("singularity", "symbol", ["\u{f1b2} ", "", ""]),
+    ("status", "symbol", ["\u{f00d}", "✖", "x"]),
+    (
+        "status",
+        "not_executable_symbol",
+        ["\u{f05e}", "🚫", "noexec"],
+    ),
+    ("status", "not_found_symbol", ["\u{f002}", "🔍", "notfound"]),
+    ("status", "sigint_symbol", ["\u{f04d}", "🧱", "int"]),
+    ("status", "signal_symbol", ["\u{f0e7}", "⚡", "sig"]),
+    ("swift", "symbol", ["\u{e755} ", "🐦 ", "swift "]),
+    ("terraform", "symbol", ["💠 ", "💠 ", "tf "]),
+    ("zig", "symbol", ["↯ ", "↯ ", "zig "]),
+];
+
+/// Fills in the symbols of the `symbol_set` chosen in the config, for every option of a
+/// built-in module that isn't set in the config itself
+///
+/// Without a `symbol_set`, the config is left as is and modules use their own defaults.
+pub fn apply(config: &mut Value) {
+    let tier = match config.get("symbol_set").and_then(Value::as_str) {
+        None | Some("") => return,
+        Some("nerd-font") => 0,
+        Some("unicode") => 1,
+        Some("ascii") => 2,
+        Some(other) => {
+            log::warn!(
+                "Unknown symbol_set `{}`, expected `nerd-font`, `unicode` or `ascii`",
+                other
+            );
+            return;
+        }
+    };
+    let table = match config.as_table_mut() {
+        Some(table) => table,
+        None => return,
+    };
+
+    for (module, option, symbols) in SYMBOLS {
+        let module_table = table
+            .entry(module.to_string())
+            .or_insert_with(|| Value::Table(toml::value::Table::new()));
+        let mut keys: Vec<&str> = option.split('.').collect();
+        let last = keys.pop().unwrap_or(option);
+        let option_table = keys.into_iter().try_fold(module_table, |table, key| {
+            Some(
+                table
+                    .as_table_mut()?
+                    .entry(key.to_string())
+                    .or_insert_with(|| Value::Table(toml::value::Table::new())),
+            )
+        });
+        if let Some(option_table) = option_table.and_then(Value::as_table_mut) {
+            option_table
+                .entry(last.to_string())
+                .or_insert_with(|| Value::from(symbols[tier]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn symbols_are_filled_in_for_the_symbol_set() {
+        let mut config = toml::toml! {
+            symbol_set = "ascii"
+            [rust]
+            symbol = "R "
+            [git_branch]
+            disabled = true
+        };
+        apply(&mut config);
+
+        assert_eq!(config["rust"]["symbol"].as_str(), Some("R "));
+        assert_eq!(config["git_branch"]["symbol"].as_str(), Some("git "));
+        assert_eq!(config["git_branch"]["disabled"].as_bool(), Some(true));
+        assert_eq!(
+            config["character"]["success_symbol"].as_str(),
+            Some("[>](bold green)")
+        );
+    }
+
+    #[test]
+    fn config_is_unchanged_without_a_known_symbol_set() {
+        for symbol_set in &["", "emoji"] {
+            let mut config = toml::toml! {
+                symbol_set = (*symbol_set)
+                [rust]
+                symbol = "R "
+            };
+            let original = config.clone();
+            apply(&mut config);
+            assert_eq!(config, original);
+        }
+    }
+
+    #[test]
+    fn symbol_tables_are_filled_in_by_key() {
+        let mut config = toml::toml! {
+            symbol_set = "nerd-font"
+            [nodejs.package_manager_symbols]
+            pnpm = "P"
+        };
+        apply(&mut config);
+
+        let symbols = &config["nodejs"]["package_manager_symbols"];
+        assert_eq!(symbols["pnpm"].as_str(), Some("P"));
+        assert_eq!(symbols["npm"].as_str(), Some("\u{e71e}"));
+    }
+
+    #[test]
+    fn non_ascii_symbols_have_a_symbol_set_entry() {
+        // Config files whose module has another name
+        let modules = [("go", "golang")];
+        let configs = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/configs");
+
+        for entry in fs::read_dir(configs).unwrap() {
+            let path = entry.unwrap().path();
+            let file = path.file_stem().and_then(OsStr::to_str).unwrap();
+            let module = modules
+                .iter()
+                .find(|(config_file, _)| *config_file == file)
+                .map_or(file, |(_, module)| *module);
+            let source = fs::read_to_string(&path).unwrap();
+            let defaults = match source.split_once("fn new()") {
+                Some((_, defaults)) => defaults,
+                None => continue,
+            };
+
+            for line in defaults.lines() {
+                let (option, default) = match line.trim().split_once(": ") {
+                    Some(option) => option,
+                    None => continue,
+                };
+                if option.ends_with("symbol") && !default.is_ascii() {
+                    assert!(
+                        SYMBOLS.iter().any(|(m, o, _)| *m == module && *o == option),
+                        "{}.{} has no entry in SYMBOLS",
+                        module,
+                        option
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_symbols_are_ascii() {
+        for (module, option, symbols) in SYMBOLS {
+            assert!(symbols[2].is_ascii(), "{}.{}", module, option);
+        }
+    }
+}
@@ -12,6 +12,7 @@ static FIXTURE_DIR: Lazy<PathBuf> =
     Lazy::new(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/test/fixtures/"));
 
 static GIT_FIXTURE: Lazy<PathBuf> = Lazy::new(|| FIXTURE_DIR.join("git-repo.bundle"));
+#[cfg(feature = "hg")]
 static HG_FIXTURE: Lazy<PathBuf> = Lazy::new(|| FIXTURE_DIR.join("hg-repo.bundle"));
 
 static LOGGER: Lazy<()> = Lazy::new(|| {
@@ -75,11 +76,23 @@ impl<'a> ModuleRenderer<'a> {
         self
     }
 
+    pub fn width(mut self, width: usize) -> Self {
+        self.context.width = width;
+        self
+    }
+
     pub fn jobs(mut self, jobs: u64) -> Self {
         self.context.properties.insert("jobs", jobs.to_string());
         self
     }
 
+    pub fn stopped_jobs(mut self, jobs: u64) -> Self {
+        self.context
+            .properties
+            .insert("stopped_jobs", jobs.to_string());
+        self
+    }
+
     pub fn cmd_duration(mut self, duration: u64) -> Self {
         self.context
             .properties
@@ -87,6 +100,16 @@ impl<'a> ModuleRenderer<'a> {
         self
     }
 
+    pub fn last_command<T>(mut self, command: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.context
+            .properties
+            .insert("last_command", command.into());
+        self
+    }
+
     pub fn keymap<T>(mut self, keymap: T) -> Self
     where
         T: Into<String>,
@@ -102,6 +125,16 @@ impl<'a> ModuleRenderer<'a> {
         self
     }
 
+    pub fn pipestatus(mut self, pipestatus: &[i32]) -> Self {
+        self.context.pipestatus = Some(
+            pipestatus
+                .iter()
+                .map(std::string::ToString::to_string)
+                .collect(),
+        );
+        self
+    }
+
     /// Renders the module returning its output
     pub fn collect(self) -> Option<String> {
         let ret = crate::print::get_module(self.name, self.context);
@@ -115,6 +148,7 @@ impl<'a> ModuleRenderer<'a> {
 
 pub enum FixtureProvider {
     GIT,
+    #[cfg(feature = "hg")]
     HG,
 }
 
@@ -147,6 +181,7 @@ pub fn fixture_repo(provider: FixtureProvider) -> io::Result<TempDir> {
 
             Ok(path)
         }
+        #[cfg(feature = "hg")]
         FixtureProvider::HG => {
             let path = tempfile::tempdir()?;
 
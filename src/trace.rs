@@ -0,0 +1,150 @@
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Records spans of work as a Chrome trace, which can be opened in `chrome://tracing`,
+/// Perfetto or speedscope to see where the time of a prompt goes
+///
+/// Recording is off unless `enable` is called, and a span is then only a flag check.
+struct Recorder {
+    start: Instant,
+    events: Mutex<Vec<Event>>,
+}
+
+struct Event {
+    name: String,
+    category: &'static str,
+    thread: u64,
+    start: Duration,
+    duration: Duration,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RECORDER: Lazy<Recorder> = Lazy::new(|| Recorder {
+    start: Instant::now(),
+    events: Mutex::new(Vec::new()),
+});
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    // A small id per thread, so that the spans of each thread are shown on their own track
+    static THREAD: Cell<u64> = const { Cell::new(0) };
+}
+
+fn thread_id() -> u64 {
+    THREAD.with(|thread| {
+        if thread.get() == 0 {
+            thread.set(NEXT_THREAD.fetch_add(1, Ordering::Relaxed));
+        }
+        thread.get()
+    })
+}
+
+/// Starts recording spans
+pub fn enable() {
+    Lazy::force(&RECORDER);
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A span of work, which is recorded when it is dropped
+pub struct Span {
+    name: String,
+    category: &'static str,
+    start: Instant,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let event = Event {
+            name: std::mem::take(&mut self.name),
+            category: self.category,
+            thread: thread_id(),
+            start: self.start.saturating_duration_since(RECORDER.start),
+            duration: self.start.elapsed(),
+        };
+        if let Ok(mut events) = RECORDER.events.lock() {
+            events.push(event);
+        }
+    }
+}
+
+/// Starts a span in a category like `module` or `command`, if recording is enabled
+///
+/// The name is only built when recording, since it's often formatted.
+pub fn span<N: Into<String>>(category: &'static str, name: impl FnOnce() -> N) -> Option<Span> {
+    if !is_enabled() {
+        return None;
+    }
+    Some(Span {
+        name: name().into(),
+        category,
+        start: Instant::now(),
+    })
+}
+
+/// Writes the recorded spans in the Chrome trace event format
+pub fn write_to(path: &Path) -> io::Result<()> {
+    let events = RECORDER
+        .events
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let trace_events: Vec<serde_json::Value> = events
+        .iter()
+        .map(|event| {
+            json!({
+                "name": event.name,
+                "cat": event.category,
+                "ph": "X",
+                "ts": event.start.as_micros() as u64,
+                "dur": event.duration.as_micros() as u64,
+                "pid": std::process::id(),
+                "tid": event.thread,
+            })
+        })
+        .collect();
+
+    let file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(
+        file,
+        &json!({ "traceEvents": trace_events, "displayTimeUnit": "ms" }),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_spans_are_written_as_chrome_trace() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("trace.json");
+
+        enable();
+        drop(span("module", || "trace_test"));
+        write_to(&path)?;
+
+        let trace: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        let event = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|event| event["name"] == "trace_test")
+            .expect("the span should be recorded");
+        assert_eq!(event["cat"], "module");
+        assert_eq!(event["ph"], "X");
+        assert!(event["ts"].is_u64() && event["dur"].is_u64());
+        dir.close()
+    }
+}
@@ -1,10 +1,11 @@
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{Read, Result};
 use std::path::Path;
 use std::process::Command;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::context::Shell;
+use crate::trace;
 
 /// Return the string contents of a file
 pub fn read_file<P: AsRef<Path>>(file_name: P) -> Result<String> {
@@ -73,6 +74,10 @@ Elixir 1.10 (compiled with Erlang/OTP 22)\n",
             stdout: String::from("0.19.1\n"),
             stderr: String::default(),
         }),
+        "tmux show-environment SSH_CONNECTION" => Some(CommandOutput {
+            stdout: String::from("SSH_CONNECTION=192.168.223.17 36673 192.168.223.229 22\n"),
+            stderr: String::default(),
+        }),
         "go version" => Some(CommandOutput {
             stdout: String::from("go version go1.12.1 linux/amd64\n"),
             stderr: String::default(),
@@ -188,6 +193,14 @@ CMake suite maintained and supported by Kitware (kitware.com/cmake).\n",
             stdout: String::from("3.1.103 [/usr/share/dotnet/sdk]"),
             stderr: String::default(),
         }),
+        "yarn --version" => Some(CommandOutput {
+            stdout: String::from("1.22.10\n"),
+            stderr: String::default(),
+        }),
+        "xprop -root _NET_ACTIVE_WINDOW" => Some(CommandOutput {
+            stdout: String::from("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007\n"),
+            stderr: String::default(),
+        }),
         "terraform version" => Some(CommandOutput {
             stdout: String::from("Terraform v0.12.14\n"),
             stderr: String::default(),
@@ -201,6 +214,55 @@ CMake suite maintained and supported by Kitware (kitware.com/cmake).\n",
     }
 }
 
+/// Execute several commands at once and return their outputs in the same order, so that
+/// their latencies overlap rather than add up
+///
+/// The commands share a deadline: a command still running when it passes is left to finish in
+/// the background, and its output is `None`.
+#[cfg(feature = "languages")]
+pub fn exec_cmds(
+    cmds: &[(&str, &[&str])],
+    timeout: std::time::Duration,
+) -> Vec<Option<CommandOutput>> {
+    use std::sync::mpsc;
+    use std::thread;
+
+    let deadline = Instant::now() + timeout;
+    let (sender, receiver) = mpsc::channel();
+    for (index, (cmd, args)) in cmds.iter().enumerate() {
+        let sender = sender.clone();
+        let cmd = cmd.to_string();
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        thread::spawn(move || {
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            // Sending fails once the deadline has passed, and the output is dropped
+            let _ = sender.send((index, exec_cmd(&cmd, &args)));
+        });
+    }
+    drop(sender);
+
+    let mut outputs: Vec<Option<CommandOutput>> = cmds.iter().map(|_| None).collect();
+    let mut remaining = cmds.len();
+    while remaining > 0 {
+        match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok((index, output)) => {
+                outputs[index] = output;
+                remaining -= 1;
+            }
+            Err(_) => {
+                log::warn!(
+                    "{} of the commands {:?} timed out after {:?}",
+                    remaining,
+                    cmds,
+                    timeout
+                );
+                break;
+            }
+        }
+    }
+    outputs
+}
+
 /// Wraps ANSI color escape sequences in the shell-appropriate wrappers.
 pub fn wrap_colorseq_for_shell(ansi: String, shell: Shell) -> String {
     const ESCAPE_BEGIN: char = '\u{1b}';
@@ -252,6 +314,7 @@ pub fn wrap_seq_for_shell(
 
 fn internal_exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
     log::trace!("Executing command {:?} with args {:?}", cmd, args);
+    let _span = trace::span("command", || format!("{} {}", cmd, args.join(" ")));
 
     let full_path = match which::which(cmd) {
         Ok(full_path) => {
@@ -294,10 +357,88 @@ fn internal_exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
     }
 }
 
+/// Render the time into a nice human-readable string
+pub fn render_time(raw_millis: u128, show_millis: bool) -> String {
+    render_time_components(raw_millis, show_millis).join("")
+}
+
+/// Render the time with a space between each component, e.g. `2m 3s`
+pub fn render_time_spaced(raw_millis: u128, show_millis: bool) -> String {
+    render_time_components(raw_millis, show_millis)
+        .into_iter()
+        .filter(|component| !component.is_empty())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn render_time_components(raw_millis: u128, show_millis: bool) -> Vec<String> {
+    // Calculate a simple breakdown into days/hours/minutes/seconds/milliseconds
+    let (millis, raw_seconds) = (raw_millis % 1000, raw_millis / 1000);
+    let (seconds, raw_minutes) = (raw_seconds % 60, raw_seconds / 60);
+    let (minutes, raw_hours) = (raw_minutes % 60, raw_minutes / 60);
+    let (hours, days) = (raw_hours % 24, raw_hours / 24);
+
+    let components = [days, hours, minutes, seconds];
+    let suffixes = ["d", "h", "m", "s"];
+
+    let mut rendered_components: Vec<String> = components
+        .iter()
+        .zip(&suffixes)
+        .map(render_time_component)
+        .collect();
+    if show_millis || raw_millis < 1000 {
+        rendered_components.push(render_time_component((&millis, &"ms")));
+    }
+    rendered_components
+}
+
+/// Sessions which weren't used for this long are considered over
+const STALE_SESSION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Remove the files that sessions which are over left in the cache directory
+///
+/// Shells don't tell starship when a session ends, so the background of the terminal
+/// (`background_<session>`) and the modules of the last prompt (`modules_<session>.json`) of a
+/// session are removed once they weren't written for a week. This is done when a session
+/// creates its own file, so it's rare.
+pub fn remove_stale_session_files(cache_dir: &Path) {
+    let entries = match fs::read_dir(cache_dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let is_session_file = name.starts_with("background_")
+            || (name.starts_with("modules_") && name.ends_with(".json"));
+        let age = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok());
+        if is_session_file && matches!(age, Some(age) if age > STALE_SESSION) {
+            log::trace!("Removing the stale session file {:?}", entry.path());
+            if let Err(error) = fs::remove_file(entry.path()) {
+                log::debug!("Unable to remove {:?}: {}", entry.path(), error);
+            }
+        }
+    }
+}
+
+/// Render a single component of the time string, giving an empty string if component is zero
+fn render_time_component((component, suffix): (&u128, &&str)) -> String {
+    match component {
+        0 => String::new(),
+        n => format!("{}{}", n, suffix),
+    }
+}
+
 #[cfg(test)]
 #[cfg(not(windows))] // While the exec_cmd should work on Windows these tests assume a Unix-like environment.
 mod tests {
     use super::*;
+    #[cfg(feature = "languages")]
+    use std::time::Duration;
 
     #[test]
     fn exec_mocked_command() {
@@ -362,6 +503,55 @@ mod tests {
         assert_eq!(result, expected)
     }
 
+    #[test]
+    #[cfg(feature = "languages")]
+    fn exec_cmds_in_order() {
+        let result = exec_cmds(
+            &[
+                ("elm", &["--version"]),
+                ("false", &[]),
+                ("dummy_command", &[]),
+            ],
+            Duration::from_secs(5),
+        );
+        let expected = vec![
+            Some(CommandOutput {
+                stdout: String::from("0.19.1\n"),
+                stderr: String::default(),
+            }),
+            None,
+            Some(CommandOutput {
+                stdout: String::from("stdout ok!\n"),
+                stderr: String::from("stderr ok!\n"),
+            }),
+        ];
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    #[cfg(feature = "languages")]
+    fn exec_cmds_share_deadline() {
+        let start = Instant::now();
+        let result = exec_cmds(
+            &[("true", &[]), ("sleep", &["1"]), ("sleep", &["1"])],
+            Duration::from_millis(300),
+        );
+
+        assert!(start.elapsed() < Duration::from_millis(900));
+        assert_eq!(
+            result,
+            vec![
+                Some(CommandOutput {
+                    stdout: String::default(),
+                    stderr: String::default(),
+                }),
+                None,
+                None,
+            ]
+        )
+    }
+
     #[test]
     fn test_color_sequence_wrappers() {
         let test0 = "\x1b2mhellomynamekeyes\x1b2m"; // BEGIN: \x1b     END: m
@@ -399,4 +589,27 @@ mod tests {
         assert_eq!(&bresult4, "herpaderp");
         assert_eq!(&bresult5, "");
     }
+
+    #[test]
+    fn stale_session_files_are_removed() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let last_month = std::time::SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+        for name in &["background_old", "modules_old.json", "session_old.log"] {
+            File::create(dir.path().join(name))?.set_modified(last_month)?;
+        }
+        for name in &["background_new", "modules_new.json"] {
+            File::create(dir.path().join(name))?;
+        }
+
+        remove_stale_session_files(dir.path());
+        let mut left: Vec<String> = fs::read_dir(dir.path())?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<_>>()?;
+        left.sort();
+        assert_eq!(
+            left,
+            vec!["background_new", "modules_new.json", "session_old.log"]
+        );
+        dir.close()
+    }
 }